@@ -0,0 +1,97 @@
+//! Applying patches must scale linearly with the size of the source.
+//!
+//! Patches multi-megabyte sources of increasing size, with the same
+//! density of patches, and fails if the time per line of the largest source
+//! exceeds the one of the smallest by more than the measurement noise.
+//!
+//! Run with `cargo bench --bench apply_patches`.
+
+#[path = "../src/action/patch.rs"]
+#[allow(dead_code)]
+mod patch;
+
+use patch::{apply_patches, Patch};
+pub use proc_macro2::LineColumn;
+
+use std::time::{Duration, Instant};
+
+/// The subset of the `Span` of the crate required by `patch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: LineColumn,
+    pub end: LineColumn,
+}
+
+const LINE: &str = "/// Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod.\n";
+
+/// A source of at least `size` bytes, with a replacement in every tenth
+/// and an insertion in every fiftieth line.
+fn generate(size: usize) -> (String, Vec<Patch>) {
+    let lines = size / LINE.len() + 1;
+    let source = LINE.repeat(lines);
+    let mut patches = Vec::with_capacity(lines / 10 + lines / 50 + 2);
+    for line in (1..=lines).step_by(10) {
+        patches.push(Patch::Replace {
+            replace_span: Span {
+                start: LineColumn { line, column: 10 },
+                end: LineColumn { line, column: 14 },
+            },
+            replacement: "Ipsum".to_owned(),
+        });
+    }
+    for line in (1..=lines).step_by(50) {
+        patches.push(Patch::Insert {
+            insert_at: LineColumn { line, column: 4 },
+            content: "Maybe ".to_owned(),
+        });
+    }
+    (source, patches)
+}
+
+/// Best of a few runs.
+fn measure(source: &str, patches: &[Patch]) -> Duration {
+    (0..5)
+        .map(|_| {
+            let mut sink = Vec::with_capacity(source.len() * 11 / 10);
+            let start = Instant::now();
+            apply_patches(patches.to_vec(), source.as_bytes(), &mut sink)
+                .expect("Patches always apply. qed");
+            let elapsed = start.elapsed();
+            assert!(sink.len() > source.len());
+            elapsed
+        })
+        .min()
+        .expect("At least one run. qed")
+}
+
+fn main() {
+    let mut per_line = Vec::new();
+    for megabytes in [1usize, 2, 4, 8, 16].iter().copied() {
+        let (source, patches) = generate(megabytes << 20);
+        let elapsed = measure(&source, &patches);
+        let ns = elapsed.as_nanos() as f64 / source.lines().count() as f64;
+        println!(
+            "apply_patches {:>3} MB, {:>6} patches: {:>9.3} ms, {:>7.1} MB/s",
+            megabytes,
+            patches.len(),
+            elapsed.as_secs_f64() * 1e3,
+            source.len() as f64 / elapsed.as_secs_f64() / (1 << 20) as f64
+        );
+        per_line.push(ns);
+    }
+
+    // linear behaviour keeps the time per line constant, quadratic one
+    // would grow it by a factor of 16, the bound only leaves room for noise
+    let smallest = per_line[0];
+    let largest = per_line[per_line.len() - 1];
+    println!(
+        "time per line grows by a factor of {:.2}",
+        largest / smallest
+    );
+    assert!(
+        largest < smallest * 1.5,
+        "Time per line grows with the source size: {:.2} ns vs {:.2} ns",
+        smallest,
+        largest
+    );
+}
@@ -1,9 +1,13 @@
 use std::env;
+#[cfg(any(feature = "nlprules", feature = "bundled-dictionary"))]
 use std::io::BufReader;
 use std::path::PathBuf;
-use xz2::bufread::{XzDecoder, XzEncoder};
+#[cfg(feature = "nlprules")]
+use xz2::bufread::XzDecoder;
+#[cfg(any(feature = "nlprules", feature = "bundled-dictionary"))]
+use xz2::bufread::XzEncoder;
 
-fn main() -> std::result::Result<(), Box<(dyn std::error::Error + 'static)>> {
+fn main() -> std::result::Result<(), Box<dyn std::error::Error + 'static>> {
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=Cargo.toml");
 
@@ -34,7 +38,7 @@ fn main() -> std::result::Result<(), Box<(dyn std::error::Error + 'static)>> {
                     std::io::copy(&mut encoder, &mut sink)?;
                     Ok(())
                 },
-                &|mut path: PathBuf| -> Result<PathBuf, Box<(dyn std::error::Error + Send + Sync + 'static)>> {
+                &|mut path: PathBuf| -> Result<PathBuf, Box<dyn std::error::Error + Send + Sync + 'static>> {
                     let mut ext = path.extension().map(|s| {
                         s.to_os_string()
                             .into_string()
@@ -67,6 +71,45 @@ fn main() -> std::result::Result<(), Box<(dyn std::error::Error + 'static)>> {
             .validate()?;
     }
 
+    #[cfg(feature = "bundled-dictionary")]
+    {
+        use std::fs::File;
+
+        const DICTIONARY_DIR: &str = "hunspell-data";
+        // a directory with a plain `en_US.dic` and `en_US.aff` to bundle
+        // instead of the committed ones
+        const SOURCE_VAR: &str = "CARGO_SPELLCHECK_BUNDLE_DICTIONARY_FROM";
+
+        println!("cargo:rerun-if-env-changed={}", SOURCE_VAR);
+
+        let cwd = env::current_dir().expect("Current dir must exist. qed");
+        let dir = cwd.join(DICTIONARY_DIR);
+
+        for extension in &["dic", "aff"] {
+            let name = format!("en_US.{}.xz", extension);
+            let target = out.join(&name);
+            if let Some(source) = env::var_os(SOURCE_VAR) {
+                let source = PathBuf::from(&source).join(format!("en_US.{}", extension));
+                println!("cargo:rerun-if-changed={}", source.display());
+                let source = File::open(&source)
+                    .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+                let mut encoder = XzEncoder::new(BufReader::new(source), 9);
+                std::io::copy(&mut encoder, &mut File::create(&target)?)?;
+            } else {
+                let committed = dir.join(&name);
+                println!("cargo:rerun-if-changed={}", committed.display());
+                std::fs::copy(&committed, &target).map_err(|e| {
+                    format!(
+                        "Failed to copy {}: {}, set {} to a directory with `en_US.dic` and `en_US.aff` to bundle those instead",
+                        committed.display(),
+                        e,
+                        SOURCE_VAR
+                    )
+                })?;
+            }
+        }
+    }
+
     let _ = out;
     Ok(())
 }
@@ -3,7 +3,7 @@
 //! A `BandAid` covers the mistake with a suggested
 //! replacement, as picked by the user.
 
-use crate::Span;
+use crate::{LineColumn, Span};
 
 /// A chosen suggestion for a certain span
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -12,9 +12,24 @@ pub struct BandAid {
     pub content: String,
     /// range which will be replaced
     pub span: Span,
+    /// Insert `content` at the start of `span` instead of replacing the
+    /// covered content.
+    ///
+    /// A `Span` is inclusive, a single character has `start == end`, so an
+    /// insertion must be explicit.
+    pub insert: bool,
 }
 
 impl BandAid {
+    /// Insert `content` at `at`, without replacing anything.
+    pub fn insertion(content: String, at: LineColumn) -> Self {
+        Self {
+            content,
+            span: Span { start: at, end: at },
+            insert: true,
+        }
+    }
+
     /// Check if the bandaid covers `line` which is 1 indexed.
     pub fn covers_line(&self, line: usize) -> bool {
         self.span.covers_line(line)
@@ -26,6 +41,7 @@ impl From<(String, &Span)> for BandAid {
         Self {
             content: replacement,
             span: *span,
+            insert: false,
         }
     }
 }
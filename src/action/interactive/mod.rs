@@ -0,0 +1,321 @@
+//! Interactive picking of replacements, contained in a suggestion.
+//!
+//! The result of that pick is a bandaid.
+//!
+//! All suggestions are reviewed in a full screen terminal user interface,
+//! which allows to move back and forth between suggestions, revisit and
+//! change earlier decisions, and to review all pending changes before
+//! they are written to disk.
+
+use super::*;
+
+use crossterm;
+
+use crossterm::{
+    cursor,
+    event::{Event, KeyCode},
+    terminal, QueueableCommand,
+};
+
+use std::io::stdout;
+
+mod render;
+mod session;
+
+use self::session::Session;
+
+const HELP: &'static str = r##"y, Enter  - apply the highlighted replacement
+n         - do not apply any replacement
+a         - apply the highlighted replacement to all undecided identical words of the file
+A         - apply the highlighted replacement to all undecided identical words
+s         - do not apply any replacement to all undecided identical words of the file
+S         - do not apply any replacement to all undecided identical words
+Up, Down  - highlight the previous / next replacement or choice
+e         - enter a custom replacement
++         - add the word to the first of the extra dictionaries
+i         - keep the word and insert a comment to suppress the finding
+Right, J  - see the next suggestion
+Left, K   - see the previous suggestion
+j         - leave this suggestion undecided, see the next undecided one
+k         - leave this suggestion undecided, see the previous undecided one
+Tab       - see the first suggestion of the next file
+Shift-Tab - see the first suggestion of the previous file
+g         - select a suggestion to go to by its number
+d         - do not apply the undecided suggestions of the current file
+u         - undo the last decision
+q, Esc    - review all pending changes before writing them to disk
+Ctrl-C    - quit, forget all decisions
+?         - print help
+"##;
+
+/// Helper strict to assure we leave the terminals raw mode
+pub struct ScopedRaw;
+
+impl ScopedRaw {
+    /// Enter raw terminal mode on the alternate screen.
+    ///
+    /// Must be left before using `log::info!(..)` or any
+    /// other printing macros or functions.
+    fn new() -> Result<Self> {
+        crossterm::terminal::enable_raw_mode()?;
+        stdout()
+            .queue(terminal::EnterAlternateScreen)?
+            .queue(cursor::Hide)?;
+        stdout().flush()?;
+        Ok(Self)
+    }
+
+    /// Helper to restore the previous terminal state.
+    ///
+    /// Also called on `drop`.
+    pub fn restore_terminal() -> Result<()> {
+        stdout()
+            .queue(terminal::LeaveAlternateScreen)?
+            .queue(crossterm::cursor::Show)?;
+        crossterm::terminal::disable_raw_mode()?;
+        stdout().flush().map_err(|e| anyhow::anyhow!(e))
+    }
+}
+
+impl Drop for ScopedRaw {
+    fn drop(&mut self) {
+        let _ = Self::restore_terminal();
+    }
+}
+
+/// The user picked something. This is the pick representation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum UserSelection {
+    /// continue as if whatever returned this was never called.
+    Nop,
+    /// Stop execution, forget all previous choices.
+    Abort,
+    /// Stop fixing chunks, move on to applying the ones chosen so far.
+    Quit,
+}
+
+/// Choices besides covering the mistake, listed after the custom entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Extra {
+    /// Add the flagged word to the dictionary of the user.
+    AddToDictionary,
+    /// Keep the content and suppress the finding with an inline comment.
+    Suppress,
+}
+
+/// Statefulness for the selection process
+#[derive(Debug, Clone)]
+struct State<'s, 't>
+where
+    't: 's,
+{
+    /// Which suggestion is operated upon.
+    pub suggestion: &'s Suggestion<'t>,
+    /// The content the user provided for the suggestion, if any.
+    pub custom_replacement: String,
+    pub cursor_offset: u16,
+    /// Which index to show as highlighted.
+    pub pick_idx: usize,
+    /// Total number of pickable slots.
+    pub n_items: usize,
+    /// Available choices besides the replacements.
+    pub extras: Vec<Extra>,
+}
+
+impl<'s, 't> State<'s, 't>
+where
+    't: 's,
+{
+    /// Create the state for a suggestion, offering the given `extras`.
+    ///
+    /// Without any replacements provided by the checker, the custom
+    /// entry is selected initially.
+    pub fn new(suggestion: &'s Suggestion<'t>, extras: Vec<Extra>) -> Self {
+        Self {
+            suggestion,
+            custom_replacement: String::new(),
+            cursor_offset: 0,
+            pick_idx: 0usize,
+            // all items provided by the checkers plus the user provided
+            // plus the extras
+            n_items: suggestion.replacements.len() + 1 + extras.len(),
+            extras,
+        }
+    }
+
+    /// Selects the next line.
+    pub fn select_next(&mut self) {
+        self.pick_idx = (self.pick_idx + 1).rem_euclid(self.n_items);
+    }
+
+    /// Selects the previous line.
+    pub fn select_previous(&mut self) {
+        self.pick_idx = (self.pick_idx + self.n_items - 1).rem_euclid(self.n_items);
+    }
+
+    /// Index of the custom line, which follows the replacements.
+    fn custom_idx(&self) -> usize {
+        self.suggestion.replacements.len()
+    }
+
+    /// Select the custom line.
+    pub fn select_custom(&mut self) {
+        self.pick_idx = self.custom_idx();
+        self.cursor_offset = self.custom_replacement.chars().count() as u16;
+    }
+
+    /// Checks if the currently selected line is the custom entry.
+    pub fn is_custom_entry(&self) -> bool {
+        self.pick_idx == self.custom_idx()
+    }
+
+    /// The extra choice on the currently selected line, if any.
+    pub fn extra(&self) -> Option<Extra> {
+        self.pick_idx
+            .checked_sub(self.custom_idx() + 1)
+            .and_then(|idx| self.extras.get(idx).copied())
+    }
+
+    /// Checks if `extra` is offered for the suggestion.
+    pub fn offers(&self, extra: Extra) -> bool {
+        self.extras.contains(&extra)
+    }
+
+    /// Convert the replacement to a `BandAid`
+    ///
+    /// Must not be called if an extra choice is selected.
+    pub fn to_bandaid(&self) -> BandAid {
+        if self.is_custom_entry() {
+            BandAid::from((self.custom_replacement.clone(), &self.suggestion.span))
+        } else {
+            let replacement = self
+                .suggestion
+                .replacements
+                .get(self.pick_idx)
+                .expect("User Pick index is out of bounds");
+            BandAid::from((replacement.to_owned(), &self.suggestion.span))
+        }
+    }
+
+    /// Byte offset of the char the cursor is positioned at.
+    fn cursor_byte_offset(&self) -> usize {
+        self.custom_replacement
+            .char_indices()
+            .nth(self.cursor_offset as usize)
+            .map(|(offset, _)| offset)
+            .unwrap_or_else(|| self.custom_replacement.len())
+    }
+
+    /// Edit the replacement that was not provided by the backend.
+    ///
+    /// Returns `true` if the user finished the entry.
+    fn enter_custom_replacement(&mut self, code: KeyCode) -> bool {
+        let length = self.custom_replacement.chars().count() as u16;
+        match code {
+            KeyCode::Left => self.cursor_offset = self.cursor_offset.saturating_sub(1),
+            KeyCode::Right => self.cursor_offset = (self.cursor_offset + 1).min(length),
+            KeyCode::Home => self.cursor_offset = 0,
+            KeyCode::End => self.cursor_offset = length,
+            KeyCode::Up => self.select_previous(),
+            KeyCode::Down => self.select_next(),
+            // leave the custom entry, but keep what was typed so far
+            KeyCode::Esc => {
+                if self.custom_idx() > 0 {
+                    self.pick_idx = 0;
+                } else if !self.extras.is_empty() {
+                    self.select_next();
+                }
+            }
+            KeyCode::Backspace => {
+                if self.cursor_offset > 0 {
+                    self.cursor_offset -= 1;
+                    let offset = self.cursor_byte_offset();
+                    self.custom_replacement.remove(offset);
+                }
+            }
+            KeyCode::Delete => {
+                if self.cursor_offset < length {
+                    let offset = self.cursor_byte_offset();
+                    self.custom_replacement.remove(offset);
+                }
+            }
+            KeyCode::Enter => return !self.custom_replacement.is_empty(),
+            KeyCode::Char(c) => {
+                let offset = self.cursor_byte_offset();
+                self.custom_replacement.insert(offset, c);
+                self.cursor_offset += 1;
+            }
+            _ => {}
+        }
+        false
+    }
+}
+
+/// The selection of used suggestion replacements.
+#[derive(Debug, Clone, Default)]
+pub struct UserPicked {
+    /// Associates the bandaids to a content origin, or path respectively.
+    pub bandaids: indexmap::IndexMap<ContentOrigin, Vec<BandAid>>,
+    /// Words to add to the dictionary of the user.
+    pub words: Vec<String>,
+}
+
+impl UserPicked {
+    /// Count the number of suggestions across all files in total.
+    pub fn total_count(&self) -> usize {
+        self.bandaids.iter().map(|(_origin, vec)| vec.len()).sum()
+    }
+
+    /// Apply a single `BandAid`
+    pub fn add_bandaid(&mut self, origin: &ContentOrigin, bandaid: BandAid) {
+        self.bandaids
+            .entry(origin.clone())
+            .or_insert_with(|| Vec::with_capacity(10))
+            .push(bandaid);
+    }
+
+    pub(super) fn select_interactive<'s>(
+        suggestions_per_path: SuggestionSet<'s>,
+        config: &Config,
+    ) -> Result<(Self, UserSelection)> {
+        let dictionary = config
+            .hunspell
+            .as_ref()
+            .and_then(|hunspell| hunspell.extra_dictionaries().first());
+        let mut session = Session::new(&suggestions_per_path, dictionary.map(PathBuf::as_path));
+        if session.is_empty() {
+            trace!("No suggestions, nothing to pick from");
+            return Ok((UserPicked::default(), UserSelection::Nop));
+        }
+
+        trace!("Select the ones to actully use");
+
+        let _guard = ScopedRaw::new()?;
+        let mut buffer = Vec::with_capacity(16 << 10);
+        loop {
+            buffer.clear();
+            let size = terminal::size()?;
+            render::render(&session, size, &mut buffer)?;
+            stdout().write_all(&buffer)?;
+            stdout().flush()?;
+
+            let event = match crossterm::event::read()
+                .map_err(|e| anyhow::anyhow!("Something unexpected happened on the CLI: {}", e))?
+            {
+                Event::Key(event) => event,
+                Event::Resize(..) => continue,
+                sth => {
+                    trace!("read() something other than a key: {:?}", sth);
+                    continue;
+                }
+            };
+            trace!("registered event: {:?}", &event);
+
+            match session.handle(event) {
+                UserSelection::Nop => continue,
+                UserSelection::Abort => return Ok((UserPicked::default(), UserSelection::Abort)),
+                UserSelection::Quit => return Ok((session.into_picked(), UserSelection::Quit)),
+            }
+        }
+    }
+}
@@ -0,0 +1,571 @@
+//! Draw a review session onto the full terminal screen.
+//!
+//! ```raw
+//!  cargo spellcheck fix                         2/14 decided, 1 to apply
+//!  src/lib.rs         3/9 │ + 1  12:4   teh → the
+//!  README.md          0/5 │ - 2  14:10  recieve
+//!                         │ » 3  17:22  wrod
+//!                         │──────────────────────────────────────────
+//!                         │ Hunspell
+//!                         │    16 │ /// Some context
+//!                         │    17 │ /// Maybe the wrod is wrong.
+//!                         │
+//!                         │  » word
+//!                         │    ...
+//!  y apply  n skip  ←/→ previous/next  g go to  u undo  q review  ? help
+//! ```
+
+use super::session::{Decision, Item, Mode, Session};
+use super::{Extra, HELP};
+use crate::ContentOrigin;
+
+use anyhow::Result;
+use crossterm::{
+    cursor,
+    style::{Attribute, Color, ContentStyle, PrintStyledContent, StyledContent},
+    terminal, QueueableCommand,
+};
+use std::io::Write;
+
+/// Number of lines shown before and after the line of a mistake.
+const CONTEXT: usize = 3;
+
+/// Clip `s` to at most `width` chars, tabs are replaced by a single space
+/// so every char occupies one column.
+fn clip(s: &str, width: usize) -> String {
+    s.chars()
+        .map(|c| if c == '\t' { ' ' } else { c })
+        .take(width)
+        .collect()
+}
+
+/// Clip `s` from the left, so the end (i.e. the file name) stays visible.
+fn clip_left(s: &str, width: usize) -> String {
+    let len = s.chars().count();
+    if len <= width {
+        s.to_owned()
+    } else if width == 0 {
+        String::new()
+    } else {
+        let tail = s.chars().skip(len - width + 1).collect::<String>();
+        format!("…{}", tail)
+    }
+}
+
+/// First visible index of a list of `len` entries, so `selected` is visible.
+fn scroll(selected: usize, len: usize, height: usize) -> usize {
+    if len <= height || height == 0 {
+        0
+    } else {
+        selected
+            .saturating_sub(height / 2)
+            .min(len.saturating_sub(height))
+    }
+}
+
+/// A rectangular area of the screen.
+#[derive(Debug, Clone, Copy)]
+struct Area {
+    x: u16,
+    y: u16,
+    width: u16,
+    height: u16,
+}
+
+/// Queues styled text, clipped to the screen.
+struct Canvas<'w, W: Write> {
+    out: &'w mut W,
+}
+
+impl<W: Write> Canvas<'_, W> {
+    fn text(&mut self, x: u16, y: u16, width: u16, style: ContentStyle, s: &str) -> Result<()> {
+        self.out
+            .queue(cursor::MoveTo(x, y))?
+            .queue(PrintStyledContent(StyledContent::new(
+                style,
+                clip(s, width as usize),
+            )))?;
+        Ok(())
+    }
+}
+
+fn plain() -> ContentStyle {
+    ContentStyle::new()
+}
+
+fn bold() -> ContentStyle {
+    ContentStyle::new().attribute(Attribute::Bold)
+}
+
+fn selected() -> ContentStyle {
+    ContentStyle::new()
+        .background(Color::DarkGrey)
+        .foreground(Color::White)
+        .attribute(Attribute::Bold)
+}
+
+fn mistake() -> ContentStyle {
+    ContentStyle::new()
+        .foreground(Color::Red)
+        .attribute(Attribute::Bold)
+}
+
+fn dim() -> ContentStyle {
+    ContentStyle::new().foreground(Color::Blue)
+}
+
+/// A short description of the decision.
+fn decision_label(item: &Item) -> (char, String) {
+    match &item.decision {
+        Some(Decision::Replace(bandaid)) => {
+            ('+', format!("{} → {}", item.flagged, bandaid.content))
+        }
+        Some(Decision::Skip) => ('-', item.flagged.clone()),
+        Some(Decision::AddToDictionary) => ('+', format!("{} → dictionary", item.flagged)),
+        Some(Decision::Suppress(_)) => ('+', format!("{} → ignored inline", item.flagged)),
+        None => (' ', item.flagged.clone()),
+    }
+}
+
+/// The path of a file, relative to the current working dir if possible.
+fn display_path(origin: &ContentOrigin) -> String {
+    let path = origin.as_path();
+    crate::traverse::cwd()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(|path| path.to_owned()))
+        .unwrap_or_else(|| path.to_owned())
+        .display()
+        .to_string()
+}
+
+/// Render the complete screen of `session` for a terminal of `size`.
+pub(super) fn render(session: &Session, size: (u16, u16), out: &mut impl Write) -> Result<()> {
+    let (width, height) = size;
+    out.queue(cursor::Hide)?
+        .queue(terminal::Clear(terminal::ClearType::All))?;
+    let mut canvas = Canvas { out };
+    if width < 40 || height < 12 {
+        canvas.text(0, 0, width, bold(), "Terminal too small")?;
+        canvas.out.flush()?;
+        return Ok(());
+    }
+
+    let pending = session.pending().count();
+    canvas.text(0, 0, width, bold(), " cargo spellcheck fix")?;
+    let progress = format!(
+        "{}/{} decided, {} to apply ",
+        session.decided(),
+        session.items.len(),
+        pending
+    );
+    let progress_len = progress.chars().count() as u16;
+    if progress_len + 22 < width {
+        canvas.text(width - progress_len, 0, progress_len, bold(), &progress)?;
+    }
+
+    let files_width = (width / 4).max(16).min(40);
+    let files = Area {
+        x: 1,
+        y: 1,
+        width: files_width - 2,
+        height: height - 2,
+    };
+    let main = Area {
+        x: files_width + 2,
+        y: 1,
+        width: width - files_width - 2,
+        height: height - 2,
+    };
+    for y in main.y..(main.y + main.height) {
+        canvas.text(files_width, y, 1, dim(), "│")?;
+    }
+
+    render_files(&mut canvas, session, files)?;
+    let cursor = match session.mode {
+        Mode::Review => {
+            render_review(&mut canvas, session, main)?;
+            None
+        }
+        Mode::Help => {
+            for (idx, line) in HELP.lines().take(main.height as usize).enumerate() {
+                canvas.text(main.x, main.y + idx as u16, main.width, plain(), line)?;
+            }
+            None
+        }
+        _ => render_suggestion(&mut canvas, session, main)?,
+    };
+
+    let status = match &session.mode {
+        Mode::Pick if session.current().state.is_custom_entry() => {
+            "type a replacement  Enter apply  ↑/↓ other choices  Esc back  Ctrl-C quit".to_owned()
+        }
+        Mode::Pick => {
+            "y apply  n skip  a/A apply all  s/S skip all  e edit  + add word  i ignore inline  ←/→ previous/next  j/k undecided  g go to  u undo  d skip file  q review  ? help"
+                .to_owned()
+        }
+        Mode::Goto(input) => format!(
+            "Go to suggestion (1-{}): {}",
+            session.items.len(),
+            input
+        ),
+        Mode::Help => "press any key to continue".to_owned(),
+        Mode::Review => "y write changes to disk  n, q back  u undo  Ctrl-C quit without writing".to_owned(),
+    };
+    canvas.text(1, height - 1, width - 1, dim(), &status)?;
+
+    match (&session.mode, cursor) {
+        (Mode::Goto(_), _) => {
+            let x = 1 + status.chars().count() as u16;
+            canvas
+                .out
+                .queue(cursor::MoveTo(x.min(width - 1), height - 1))?
+                .queue(cursor::Show)?;
+        }
+        (_, Some((x, y))) => {
+            canvas
+                .out
+                .queue(cursor::MoveTo(x.min(width - 1), y))?
+                .queue(cursor::Show)?;
+        }
+        _ => {}
+    }
+    canvas.out.flush()?;
+    Ok(())
+}
+
+/// The list of files with the number of decided suggestions.
+fn render_files<W: Write>(canvas: &mut Canvas<W>, session: &Session, area: Area) -> Result<()> {
+    let current = session.current().file;
+    let first = scroll(current, session.files.len(), area.height as usize);
+    for (row, (idx, file)) in session
+        .files
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(area.height as usize)
+        .enumerate()
+    {
+        let range = session.file_items(idx);
+        let decided = session.items[range.clone()]
+            .iter()
+            .filter(|item| item.decision.is_some())
+            .count();
+        let counter = format!(" {}/{}", decided, range.len());
+        let name_width = (area.width as usize).saturating_sub(counter.chars().count());
+        let name = clip_left(&display_path(file.origin), name_width);
+        let line = format!("{:<width$}{}", name, counter, width = name_width);
+        let style = if idx == current { selected() } else { plain() };
+        canvas.text(area.x, area.y + row as u16, area.width, style, &line)?;
+    }
+    Ok(())
+}
+
+/// The suggestions of the current file, the context of the current one and
+/// its replacements.
+///
+/// Returns the cursor position if a custom replacement is edited.
+fn render_suggestion<W: Write>(
+    canvas: &mut Canvas<W>,
+    session: &Session,
+    area: Area,
+) -> Result<Option<(u16, u16)>> {
+    let item = session.current();
+    let range = session.file_items(item.file);
+
+    let list_height = (area.height / 3).max(3);
+    let first = scroll(
+        session.cursor - range.start,
+        range.len(),
+        list_height as usize,
+    );
+    for (row, idx) in range
+        .clone()
+        .skip(first)
+        .take(list_height as usize)
+        .enumerate()
+    {
+        let other = &session.items[idx];
+        let (marker, label) = decision_label(other);
+        let marker = if idx == session.cursor { '»' } else { marker };
+        let start = other.state.suggestion.span.start;
+        let line = format!(
+            "{} {:>4} {:>5}:{:<4} {}",
+            marker,
+            idx + 1,
+            start.line,
+            start.column,
+            label
+        );
+        let style = if idx == session.cursor {
+            selected()
+        } else {
+            plain()
+        };
+        canvas.text(area.x, area.y + row as u16, area.width, style, &line)?;
+    }
+
+    let mut y = area.y + list_height;
+    canvas.text(
+        area.x,
+        y,
+        area.width,
+        dim(),
+        &"─".repeat(area.width as usize),
+    )?;
+    y += 1;
+
+    let suggestion = item.state.suggestion;
+    let heading = match &suggestion.description {
+        Some(description) => format!("{}: {}", suggestion.detector, description),
+        None => format!("{}", suggestion.detector),
+    };
+    canvas.text(area.x, y, area.width, bold(), &heading)?;
+    y += 1;
+
+    // the lines around the mistake, together with the marked columns per line
+    let span = suggestion.span;
+    let lines = match &session.files[item.file].lines {
+        Some(lines) if span.start.line >= 1 && span.start.line <= lines.len() => {
+            let first = span.start.line.saturating_sub(CONTEXT).max(1);
+            let last = (span.end.line + CONTEXT).min(lines.len());
+            (first..=last)
+                .map(|lineno| {
+                    let line = lines[lineno - 1].as_str();
+                    let marked = if lineno < span.start.line || lineno > span.end.line {
+                        None
+                    } else {
+                        let start = if lineno == span.start.line {
+                            span.start.column
+                        } else {
+                            0
+                        };
+                        let end = if lineno == span.end.line {
+                            span.end.column + 1
+                        } else {
+                            line.chars().count()
+                        };
+                        Some(start..end)
+                    };
+                    (lineno, line.to_owned(), marked)
+                })
+                .collect::<Vec<_>>()
+        }
+        _ => {
+            // the file is not available, fall back to the chunk
+            let content = suggestion.chunk.as_str();
+            let before = content
+                .chars()
+                .take(suggestion.range.start)
+                .collect::<String>();
+            let hit = before.matches('\n').count();
+            let column = before.chars().rev().take_while(|c| *c != '\n').count();
+            content
+                .lines()
+                .enumerate()
+                .skip(hit.saturating_sub(CONTEXT))
+                .take(2 * CONTEXT + 1)
+                .map(|(idx, line)| {
+                    let marked = if idx == hit {
+                        Some(column..(column + suggestion.range.len()))
+                    } else {
+                        None
+                    };
+                    (
+                        (span.start.line + idx).saturating_sub(hit),
+                        line.to_owned(),
+                        marked,
+                    )
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let gutter = 8u16;
+    for (lineno, line, marked) in lines {
+        if y >= area.y + area.height {
+            break;
+        }
+        canvas.text(area.x, y, gutter, dim(), &format!("{:>5} │", lineno))?;
+        let text_width = area.width.saturating_sub(gutter);
+        canvas.text(area.x + gutter, y, text_width, plain(), &line)?;
+        if let Some(marked) = marked {
+            let start = marked.start.min(text_width as usize);
+            let flagged = line
+                .chars()
+                .skip(start)
+                .take(marked.len())
+                .collect::<String>();
+            canvas.text(
+                area.x + gutter + start as u16,
+                y,
+                text_width - start as u16,
+                mistake(),
+                &flagged,
+            )?;
+        }
+        y += 1;
+    }
+    y += 1;
+
+    let state = &item.state;
+    let mut cursor = None;
+    for (idx, replacement) in suggestion.replacements.iter().enumerate() {
+        if y >= area.y + area.height {
+            break;
+        }
+        let (marker, style) = if idx == state.pick_idx {
+            ("»", selected())
+        } else {
+            (" ", plain())
+        };
+        canvas.text(area.x + 1, y, 1, bold(), marker)?;
+        canvas.text(area.x + 3, y, area.width - 3, style, replacement)?;
+        y += 1;
+    }
+    if y < area.y + area.height {
+        let custom = ContentStyle::new().foreground(Color::Yellow);
+        if state.is_custom_entry() {
+            canvas.text(area.x + 1, y, 1, bold(), "»")?;
+            canvas.text(
+                area.x + 3,
+                y,
+                area.width - 3,
+                custom,
+                &state.custom_replacement,
+            )?;
+            cursor = Some((area.x + 3 + state.cursor_offset, y));
+        } else {
+            let content = if state.custom_replacement.is_empty() {
+                "..."
+            } else {
+                state.custom_replacement.as_str()
+            };
+            canvas.text(area.x + 3, y, area.width - 3, custom, content)?;
+        }
+        y += 1;
+    }
+    for extra in &state.extras {
+        if y >= area.y + area.height {
+            break;
+        }
+        let label = match extra {
+            Extra::AddToDictionary => format!(
+                "add `{}` to {}",
+                item.flagged,
+                session
+                    .dictionary
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default()
+            ),
+            Extra::Suppress => format!("ignore `{}` with an inline comment", item.flagged),
+        };
+        let (marker, style) = if state.extra() == Some(*extra) {
+            ("»", selected())
+        } else {
+            (" ", dim())
+        };
+        canvas.text(area.x + 1, y, 1, bold(), marker)?;
+        canvas.text(area.x + 3, y, area.width - 3, style, &label)?;
+        y += 1;
+    }
+    Ok(cursor)
+}
+
+/// All pending changes, grouped by file, followed by the words added to the
+/// dictionary.
+fn render_review<W: Write>(canvas: &mut Canvas<W>, session: &Session, area: Area) -> Result<()> {
+    let pending = session.pending().collect::<Vec<_>>();
+    let words = session.dictionary_words();
+    let undecided = session.items.len() - session.decided();
+    let heading = format!(
+        "{} changes will be written to disk, {} suggestions are undecided",
+        pending.len() + words.len(),
+        undecided
+    );
+    canvas.text(area.x, area.y, area.width, bold(), &heading)?;
+
+    let mut lines = Vec::with_capacity(pending.len() * 2);
+    let mut last_file = None;
+    for (item, bandaid) in pending {
+        if last_file != Some(item.file) {
+            last_file = Some(item.file);
+            lines.push((bold(), display_path(session.files[item.file].origin)));
+        }
+        let start = item.state.suggestion.span.start;
+        let change = match item.decision {
+            Some(Decision::Suppress(_)) => "ignored inline".to_owned(),
+            _ => bandaid.content.clone(),
+        };
+        lines.push((
+            plain(),
+            format!(
+                "  {:>5}:{:<4} {} → {}",
+                start.line, start.column, item.flagged, change
+            ),
+        ));
+    }
+    if let (Some(dictionary), false) = (session.dictionary, words.is_empty()) {
+        lines.push((bold(), dictionary.display().to_string()));
+        lines.push((plain(), format!("  + {}", words.join(", "))));
+    }
+    for (row, (style, line)) in lines
+        .iter()
+        .take(area.height.saturating_sub(2) as usize)
+        .enumerate()
+    {
+        canvas.text(area.x, area.y + 2 + row as u16, area.width, *style, line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{dummy::DummyChecker, Checker};
+    use crate::{Documentation, SuggestionSet};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn clipping() {
+        assert_eq!(clip("a\tb", 2), "a ");
+        assert_eq!(clip_left("src/lib.rs", 6), "…ib.rs");
+        assert_eq!(clip_left("lib.rs", 6), "lib.rs");
+        assert_eq!(scroll(0, 10, 4), 0);
+        assert_eq!(scroll(9, 10, 4), 6);
+        assert_eq!(scroll(5, 10, 4), 3);
+    }
+
+    #[test]
+    fn screens() {
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityRust,
+            "/// Alpha beta\n/// gamma\nstruct X;",
+            false,
+        );
+        let mut set = SuggestionSet::new();
+        for (origin, chunks) in docs.iter() {
+            let suggestions = DummyChecker.check(origin, &chunks[..]).unwrap();
+            set.extend(origin.clone(), suggestions);
+        }
+        let mut session = Session::new(&set, None);
+
+        let mut out = Vec::new();
+        render(&session, (100, 30), &mut out).unwrap();
+        let screen = String::from_utf8(out).unwrap();
+        assert!(screen.contains("entity.rs"));
+        assert!(screen.contains("replacement_0"));
+        assert!(screen.contains("Alpha"));
+
+        session.handle(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+        session.handle(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+        let mut out = Vec::new();
+        render(&session, (100, 30), &mut out).unwrap();
+        let screen = String::from_utf8(out).unwrap();
+        assert!(screen.contains("1 changes will be written to disk"));
+        assert!(screen.contains("Alpha → replacement_0"));
+
+        // must not panic on tiny terminals
+        render(&session, (10, 3), &mut Vec::new()).unwrap();
+        render(&session, (40, 12), &mut Vec::new()).unwrap();
+    }
+}
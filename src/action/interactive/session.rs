@@ -0,0 +1,917 @@
+//! The state of a review session, independent of the terminal.
+//!
+//! Tracks the decisions for all suggestions, the one currently shown,
+//! and the history of decisions to undo them.
+
+use super::{BandAid, Extra, State, UserPicked, UserSelection};
+use crate::checker::suppress;
+use crate::documentation::CommentVariant;
+use crate::util::sub_chars;
+use crate::{ContentOrigin, Detector, LineColumn, Span, SuggestionSet};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use fs_err as fs;
+use std::path::Path;
+
+/// The decision for a single suggestion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Decision {
+    /// Cover the mistake with the bandaid.
+    Replace(BandAid),
+    /// Keep the content as is.
+    Skip,
+    /// Keep the content and add the flagged word to the dictionary.
+    AddToDictionary,
+    /// Keep the content and suppress the finding inline, the bandaid
+    /// inserting the suppression comment is only present for one of the
+    /// findings covered by it.
+    Suppress(Option<BandAid>),
+}
+
+/// What is shown and where key strokes go to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) enum Mode {
+    /// Pick a replacement for the current suggestion.
+    Pick,
+    /// Enter the number of the suggestion to go to.
+    Goto(String),
+    /// Show the help until any key is pressed.
+    Help,
+    /// List all pending changes before writing them to disk.
+    Review,
+}
+
+/// A file with suggestions.
+pub(super) struct File<'s> {
+    pub origin: &'s ContentOrigin,
+    /// Lines of the file, used to display the context of a suggestion.
+    ///
+    /// `None` if the file could not be read.
+    pub lines: Option<Vec<String>>,
+}
+
+/// A suggestion together with the pick state and decision.
+pub(super) struct Item<'s, 't> {
+    /// Index of the file in `Session::files`.
+    pub file: usize,
+    /// The content covered by the suggestion.
+    pub flagged: String,
+    pub state: State<'s, 't>,
+    pub decision: Option<Decision>,
+}
+
+/// Everything required to revert a single user action.
+struct Undo {
+    cursor: usize,
+    previous: Vec<(usize, Option<Decision>)>,
+}
+
+/// A review session over all suggestions, in order of files.
+pub(super) struct Session<'s, 't> {
+    pub files: Vec<File<'s>>,
+    /// All suggestions, the ones of a file are contiguous.
+    pub items: Vec<Item<'s, 't>>,
+    /// Index of the item currently shown.
+    pub cursor: usize,
+    pub mode: Mode,
+    /// The dictionary words are added to, if any.
+    pub dictionary: Option<&'s Path>,
+    history: Vec<Undo>,
+}
+
+impl<'s, 't> Session<'s, 't>
+where
+    't: 's,
+{
+    /// Create a new session.
+    ///
+    /// Adding words to the dictionary is only offered for findings of the
+    /// hunspell checker and if a `dictionary` is given, suppressing them
+    /// inline only in documentation, not in developer comments.
+    pub fn new(suggestions_per_path: &'s SuggestionSet<'t>, dictionary: Option<&'s Path>) -> Self {
+        let mut files = Vec::with_capacity(suggestions_per_path.len());
+        let mut items = Vec::with_capacity(suggestions_per_path.total_count());
+        for (origin, suggestions) in suggestions_per_path.iter() {
+            let file = files.len();
+            let before = items.len();
+            items.extend(suggestions.iter().map(|suggestion| {
+                let flagged = sub_chars(suggestion.chunk.as_str(), suggestion.range.clone());
+                // the suppression comment lists whitespace separated words,
+                // a finding of multiple words is suppressed if all are listed
+                let mut extras = Vec::with_capacity(2);
+                if !flagged.trim().is_empty() {
+                    if dictionary.is_some()
+                        && suggestion.detector == Detector::Hunspell
+                        && !flagged.contains(char::is_whitespace)
+                    {
+                        extras.push(Extra::AddToDictionary);
+                    }
+                    // the comment is html, which only stays invisible where
+                    // the content is rendered as markdown
+                    if !matches!(
+                        suggestion.chunk.variant(),
+                        CommentVariant::DoubleSlash | CommentVariant::SlashStar { .. }
+                    ) {
+                        extras.push(Extra::Suppress);
+                    }
+                }
+                Item {
+                    file,
+                    flagged,
+                    state: State::new(suggestion, extras),
+                    decision: None,
+                }
+            }));
+            if items.len() == before {
+                continue;
+            }
+            let lines = match origin {
+                ContentOrigin::RustSourceFile(path) | ContentOrigin::CommonMarkFile(path) => {
+                    fs::read_to_string(path)
+                        .ok()
+                        .map(|content| content.lines().map(ToOwned::to_owned).collect())
+                }
+                _ => None,
+            };
+            files.push(File { origin, lines });
+        }
+        Self {
+            files,
+            items,
+            cursor: 0,
+            mode: Mode::Pick,
+            dictionary,
+            history: Vec::new(),
+        }
+    }
+
+    /// Check if there is anything to pick from at all.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The item currently shown.
+    pub fn current(&self) -> &Item<'s, 't> {
+        &self.items[self.cursor]
+    }
+
+    /// Range of item indices belonging to the file with index `file`.
+    pub fn file_items(&self, file: usize) -> std::ops::Range<usize> {
+        let start = self.items.iter().position(|item| item.file == file);
+        match start {
+            Some(start) => {
+                let len = self.items[start..]
+                    .iter()
+                    .take_while(|item| item.file == file)
+                    .count();
+                start..(start + len)
+            }
+            None => 0..0,
+        }
+    }
+
+    /// Number of items with a decision.
+    pub fn decided(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.decision.is_some())
+            .count()
+    }
+
+    /// All bandaids that will be written to disk, in order.
+    ///
+    /// Findings of the identical span share their decision, the bandaid is
+    /// only listed for the first of them.
+    pub fn pending(&self) -> impl Iterator<Item = (&Item<'s, 't>, &BandAid)> {
+        let mut seen = Vec::<(usize, Span)>::new();
+        self.items
+            .iter()
+            .filter_map(move |item| match &item.decision {
+                Some(Decision::Replace(bandaid)) | Some(Decision::Suppress(Some(bandaid))) => {
+                    let key = (item.file, item.state.suggestion.span);
+                    if seen.contains(&key) {
+                        return None;
+                    }
+                    seen.push(key);
+                    Some((item, bandaid))
+                }
+                _ => None,
+            })
+    }
+
+    /// All words that will be added to the dictionary, without duplicates.
+    pub fn dictionary_words(&self) -> Vec<&str> {
+        let mut words = Vec::new();
+        for item in &self.items {
+            if item.decision == Some(Decision::AddToDictionary)
+                && !words.contains(&item.flagged.as_str())
+            {
+                words.push(item.flagged.as_str());
+            }
+        }
+        words
+    }
+
+    /// Move to the next item, if `undecided` is set, only undecided ones are
+    /// considered and the search wraps around at the end.
+    ///
+    /// Returns `false` if there is no such item.
+    fn next(&mut self, undecided: bool) -> bool {
+        let n = self.items.len();
+        let found = if undecided {
+            (1..n)
+                .map(|offset| (self.cursor + offset) % n)
+                .find(|&idx| self.items[idx].decision.is_none())
+        } else {
+            Some(self.cursor + 1).filter(|&idx| idx < n)
+        };
+        found.map(|idx| self.cursor = idx).is_some()
+    }
+
+    /// The counterpart of [`next`](Self::next).
+    fn previous(&mut self, undecided: bool) -> bool {
+        let n = self.items.len();
+        let found = if undecided {
+            (1..n)
+                .map(|offset| (self.cursor + n - offset) % n)
+                .find(|&idx| self.items[idx].decision.is_none())
+        } else {
+            self.cursor.checked_sub(1)
+        };
+        found.map(|idx| self.cursor = idx).is_some()
+    }
+
+    /// Move to the first item of the next or previous file.
+    fn switch_file(&mut self, forward: bool) {
+        let n = self.files.len();
+        let file = self.current().file;
+        let file = if forward {
+            (file + 1) % n
+        } else {
+            (file + n - 1) % n
+        };
+        self.cursor = self.file_items(file).start;
+    }
+
+    /// The current item and all undecided ones flagging the identical content
+    /// by the same detector, either within the current file or `everywhere`.
+    fn occurrences(&self, everywhere: bool) -> Vec<usize> {
+        let current = self.current();
+        let detector = current.state.suggestion.detector;
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(idx, item)| {
+                *idx == self.cursor
+                    || (item.decision.is_none()
+                        && (everywhere || item.file == current.file)
+                        && item.state.suggestion.detector == detector
+                        && item.flagged == current.flagged)
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Apply the picked replacement of the current item to all its occurrences.
+    fn replace_all(&mut self, everywhere: bool) {
+        if self.current().state.extra().is_some() {
+            return;
+        }
+        let content = self.current().state.to_bandaid().content;
+        let decisions = self
+            .occurrences(everywhere)
+            .into_iter()
+            .map(|idx| {
+                let span = &self.items[idx].state.suggestion.span;
+                (
+                    idx,
+                    Decision::Replace(BandAid::from((content.clone(), span))),
+                )
+            })
+            .collect();
+        self.decide(decisions);
+    }
+
+    /// Skip the current item and all its occurrences.
+    fn skip_all(&mut self, everywhere: bool) {
+        let decisions = self
+            .occurrences(everywhere)
+            .into_iter()
+            .map(|idx| (idx, Decision::Skip))
+            .collect();
+        self.decide(decisions);
+    }
+
+    /// Add the word of the current item to the dictionary, which covers all
+    /// its occurrences.
+    fn add_to_dictionary(&mut self) {
+        if !self.current().state.offers(Extra::AddToDictionary) {
+            return;
+        }
+        let decisions = self
+            .occurrences(true)
+            .into_iter()
+            .map(|idx| (idx, Decision::AddToDictionary))
+            .collect();
+        self.decide(decisions);
+    }
+
+    /// Suppress the current item with a comment inserted right after it.
+    ///
+    /// The comment covers the whole chunk, so all undecided findings of the
+    /// identical content within the chunk are suppressed as well, regardless
+    /// of the detector.
+    fn suppress(&mut self) {
+        let current = self.current();
+        if !current.state.offers(Extra::Suppress) {
+            return;
+        }
+        let suggestion = current.state.suggestion;
+        let at = LineColumn {
+            line: suggestion.span.end.line,
+            column: suggestion.span.end.column + 1,
+        };
+        let comment = format!(" {}", suppress::directive(&[current.flagged.as_str()]));
+        let bandaid = BandAid::insertion(comment, at);
+        let decisions = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, item)| {
+                if idx == self.cursor {
+                    Some((idx, Decision::Suppress(Some(bandaid.clone()))))
+                } else if item.decision.is_none()
+                    && std::ptr::eq(item.state.suggestion.chunk, suggestion.chunk)
+                    && item.flagged == current.flagged
+                {
+                    Some((idx, Decision::Suppress(None)))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        self.decide(decisions);
+    }
+
+    /// Apply the selected line of the current item, which is either a
+    /// replacement or an extra choice.
+    fn apply_selected(&mut self) {
+        let cursor = self.cursor;
+        match self.items[cursor].state.extra() {
+            Some(Extra::AddToDictionary) => self.add_to_dictionary(),
+            Some(Extra::Suppress) => self.suppress(),
+            None => {
+                let bandaid = self.items[cursor].state.to_bandaid();
+                self.decide(vec![(cursor, Decision::Replace(bandaid))]);
+            }
+        }
+    }
+
+    /// Indices of the other items of the same file, which flag the identical
+    /// span, i.e. findings of different detectors for the same word.
+    fn siblings(&self, idx: usize) -> impl Iterator<Item = usize> + '_ {
+        let item = &self.items[idx];
+        self.file_items(item.file).filter(move |&other| {
+            other != idx && self.items[other].state.suggestion.span == item.state.suggestion.span
+        })
+    }
+
+    /// Record the decision for the given items, then move on to the
+    /// next undecided item or to the final review if there is none.
+    ///
+    /// Findings of the identical span are decided together, replacing
+    /// the same span twice would produce overlapping changes.
+    fn decide(&mut self, mut decisions: Vec<(usize, Decision)>) {
+        if decisions.is_empty() {
+            return;
+        }
+        for i in 0..decisions.len() {
+            let (idx, decision) = decisions[i].clone();
+            let decision = match decision {
+                // the suppression comment is only inserted once
+                Decision::Suppress(_) => Decision::Suppress(None),
+                decision => decision,
+            };
+            let siblings = self
+                .siblings(idx)
+                .filter(|sibling| decisions.iter().all(|(other, _)| other != sibling))
+                .collect::<Vec<_>>();
+            decisions.extend(
+                siblings
+                    .into_iter()
+                    .map(|sibling| (sibling, decision.clone())),
+            );
+        }
+        let previous = decisions
+            .into_iter()
+            .map(|(idx, decision)| (idx, self.items[idx].decision.replace(decision)))
+            .collect();
+        self.history.push(Undo {
+            cursor: self.cursor,
+            previous,
+        });
+        if self.current().decision.is_some() && !self.next(true) {
+            self.mode = Mode::Review;
+        }
+    }
+
+    /// Revert the last decision and show the affected item again.
+    fn undo(&mut self) {
+        if let Some(Undo { cursor, previous }) = self.history.pop() {
+            for (idx, decision) in previous {
+                self.items[idx].decision = decision;
+            }
+            self.cursor = cursor;
+        }
+        self.mode = Mode::Pick;
+    }
+
+    /// Process a key stroke.
+    pub fn handle(&mut self, event: KeyEvent) -> UserSelection {
+        let KeyEvent { code, modifiers } = event;
+        if code == KeyCode::Char('c') && modifiers == KeyModifiers::CONTROL {
+            return UserSelection::Abort;
+        }
+        match self.mode {
+            Mode::Pick if self.current().state.is_custom_entry() => self.handle_custom(code),
+            Mode::Pick => return self.handle_pick(code),
+            Mode::Goto(_) => self.handle_goto(code),
+            Mode::Help => self.mode = Mode::Pick,
+            Mode::Review => return self.handle_review(code),
+        }
+        UserSelection::Nop
+    }
+
+    fn handle_custom(&mut self, code: KeyCode) {
+        let cursor = self.cursor;
+        match code {
+            KeyCode::Tab => return self.switch_file(true),
+            KeyCode::BackTab => return self.switch_file(false),
+            _ => {}
+        }
+        let state = &mut self.items[cursor].state;
+        if state.enter_custom_replacement(code) {
+            let bandaid = state.to_bandaid();
+            self.decide(vec![(cursor, Decision::Replace(bandaid))]);
+        } else if code == KeyCode::Esc && state.is_custom_entry() {
+            // nothing else to select, leave the suggestion undecided
+            if !self.next(true) {
+                self.mode = Mode::Review;
+            }
+        }
+    }
+
+    fn handle_pick(&mut self, code: KeyCode) -> UserSelection {
+        let cursor = self.cursor;
+        match code {
+            KeyCode::Up => self.items[cursor].state.select_previous(),
+            KeyCode::Down => self.items[cursor].state.select_next(),
+            KeyCode::Enter | KeyCode::Char('y') => self.apply_selected(),
+            KeyCode::Char('n') => self.decide(vec![(cursor, Decision::Skip)]),
+            KeyCode::Char('a') => self.replace_all(false),
+            KeyCode::Char('A') => self.replace_all(true),
+            KeyCode::Char('s') => self.skip_all(false),
+            KeyCode::Char('S') => self.skip_all(true),
+            KeyCode::Char('e') => self.items[cursor].state.select_custom(),
+            KeyCode::Char('+') => self.add_to_dictionary(),
+            KeyCode::Char('i') => self.suppress(),
+            KeyCode::Right | KeyCode::Char('J') => {
+                self.next(false);
+            }
+            KeyCode::Left | KeyCode::Char('K') => {
+                self.previous(false);
+            }
+            KeyCode::Char('j') => {
+                self.next(true);
+            }
+            KeyCode::Char('k') => {
+                self.previous(true);
+            }
+            KeyCode::Tab => self.switch_file(true),
+            KeyCode::BackTab => self.switch_file(false),
+            KeyCode::Char('g') => self.mode = Mode::Goto(String::new()),
+            KeyCode::Char('d') => {
+                let skip = self
+                    .file_items(self.current().file)
+                    .filter(|&idx| self.items[idx].decision.is_none())
+                    .map(|idx| (idx, Decision::Skip))
+                    .collect();
+                self.decide(skip);
+            }
+            KeyCode::Char('u') => self.undo(),
+            KeyCode::Char('q') | KeyCode::Esc => self.mode = Mode::Review,
+            KeyCode::Char('?') => self.mode = Mode::Help,
+            x => log::trace!("Unexpected input {:?}", x),
+        }
+        UserSelection::Nop
+    }
+
+    fn handle_goto(&mut self, code: KeyCode) {
+        let n = self.items.len();
+        if let Mode::Goto(ref mut input) = self.mode {
+            match code {
+                KeyCode::Char(c) if c.is_ascii_digit() => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                KeyCode::Enter => {
+                    match input.parse::<usize>() {
+                        Ok(nth) if nth >= 1 && nth <= n => self.cursor = nth - 1,
+                        _ => log::trace!("Invalid suggestion number {}", input),
+                    }
+                    self.mode = Mode::Pick;
+                }
+                KeyCode::Esc => self.mode = Mode::Pick,
+                _ => {}
+            }
+        }
+    }
+
+    fn handle_review(&mut self, code: KeyCode) -> UserSelection {
+        match code {
+            KeyCode::Enter | KeyCode::Char('y') => return UserSelection::Quit,
+            // discarding all decisions is left to Ctrl-C, a second `q` must
+            // not lose the session
+            KeyCode::Char('n') | KeyCode::Char('b') | KeyCode::Char('q') | KeyCode::Esc => {
+                self.mode = Mode::Pick
+            }
+            KeyCode::Char('u') => self.undo(),
+            _ => {}
+        }
+        UserSelection::Nop
+    }
+
+    /// Collect the bandaids to apply and the words to add to the dictionary.
+    pub fn into_picked(self) -> UserPicked {
+        let mut picked = UserPicked::default();
+        picked.words = self
+            .dictionary_words()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect();
+        for (item, bandaid) in self.pending() {
+            picked.add_bandaid(self.files[item.file].origin, bandaid.clone());
+        }
+        // patches are applied in order of their position
+        for bandaids in picked.bandaids.values_mut() {
+            bandaids.sort_by_key(|bandaid| (bandaid.span.start.line, bandaid.span.start.column));
+        }
+        picked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{dummy::DummyChecker, Checker};
+    use crate::Documentation;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn keys(session: &mut Session, codes: &[KeyCode]) -> UserSelection {
+        codes
+            .iter()
+            .map(|code| session.handle(key(*code)))
+            .last()
+            .unwrap_or(UserSelection::Nop)
+    }
+
+    lazy_static::lazy_static! {
+        static ref DOCS: Vec<Documentation> = vec![
+            Documentation::load_from_str(
+                ContentOrigin::TestEntityRust,
+                "/// Alpha beta gamma\nstruct X;",
+                false,
+            ),
+            Documentation::load_from_str(
+                ContentOrigin::TestEntityCommonMark,
+                "Delta epsilon",
+                false,
+            ),
+        ];
+    }
+
+    fn suggestions() -> SuggestionSet<'static> {
+        let mut set = SuggestionSet::new();
+        for docs in DOCS.iter() {
+            for (origin, chunks) in docs.iter() {
+                let suggestions = DummyChecker.check(origin, &chunks[..]).unwrap();
+                set.extend(origin.clone(), suggestions);
+            }
+        }
+        set
+    }
+
+    #[test]
+    fn navigate_and_revisit() {
+        let set = suggestions();
+        let mut session = Session::new(&set, None);
+        assert_eq!(session.items.len(), 5);
+        assert_eq!(session.files.len(), 2);
+        assert_eq!(session.file_items(1), 3..5);
+        assert_eq!(session.current().flagged, "Alpha");
+
+        // apply, skip, then go back and change the first decision
+        keys(&mut session, &[KeyCode::Char('y'), KeyCode::Char('n')]);
+        assert_eq!(session.cursor, 2);
+        keys(&mut session, &[KeyCode::Left, KeyCode::Left]);
+        assert_eq!(session.cursor, 0);
+        keys(&mut session, &[KeyCode::Char('n')]);
+        assert_eq!(session.items[0].decision, Some(Decision::Skip));
+        // continues with the next undecided one
+        assert_eq!(session.cursor, 2);
+
+        keys(&mut session, &[KeyCode::Tab]);
+        assert_eq!(session.current().flagged, "Delta");
+        keys(&mut session, &[KeyCode::BackTab, KeyCode::Char('j')]);
+        assert_eq!(session.cursor, 2);
+        keys(&mut session, &[KeyCode::Char('k')]);
+        assert_eq!(session.cursor, 4);
+    }
+
+    #[test]
+    fn goto_undo_and_review() {
+        let set = suggestions();
+        let mut session = Session::new(&set, None);
+
+        keys(
+            &mut session,
+            &[KeyCode::Char('g'), KeyCode::Char('4'), KeyCode::Enter],
+        );
+        assert_eq!(session.mode, Mode::Pick);
+        assert_eq!(session.current().flagged, "Delta");
+
+        // skip the rest of the second file, then undo it
+        keys(&mut session, &[KeyCode::Char('d')]);
+        assert_eq!(session.decided(), 2);
+        assert_eq!(session.cursor, 0);
+        keys(&mut session, &[KeyCode::Char('u')]);
+        assert_eq!(session.decided(), 0);
+        assert_eq!(session.cursor, 3);
+
+        // a custom replacement
+        keys(&mut session, &[KeyCode::Char('e'), KeyCode::Char('D')]);
+        assert_eq!(
+            keys(&mut session, &[KeyCode::Char('x'), KeyCode::Enter]),
+            UserSelection::Nop
+        );
+        assert!(session.items[3].decision.is_some());
+
+        // deciding the last one leads to the review
+        keys(
+            &mut session,
+            &[
+                KeyCode::Char('y'),
+                KeyCode::Char('y'),
+                KeyCode::Char('n'),
+                KeyCode::Char('n'),
+            ],
+        );
+        assert_eq!(session.mode, Mode::Review);
+        assert_eq!(session.pending().count(), 3);
+        assert_eq!(keys(&mut session, &[KeyCode::Enter]), UserSelection::Quit);
+
+        let picked = session.into_picked();
+        assert_eq!(picked.total_count(), 3);
+        let contents = picked
+            .bandaids
+            .values()
+            .flatten()
+            .map(|bandaid| bandaid.content.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(contents, vec!["replacement_0", "Dx", "replacement_1"]);
+    }
+
+    #[test]
+    fn all_occurrences() {
+        lazy_static::lazy_static! {
+            static ref REPEATED: Vec<Documentation> = vec![
+                Documentation::load_from_str(
+                    ContentOrigin::TestEntityRust,
+                    "/// Teh cat and teh dog and Teh\nstruct X;",
+                    false,
+                ),
+                Documentation::load_from_str(
+                    ContentOrigin::TestEntityCommonMark,
+                    "Teh end",
+                    false,
+                ),
+            ];
+        }
+        let mut set = SuggestionSet::new();
+        for docs in REPEATED.iter() {
+            for (origin, chunks) in docs.iter() {
+                let suggestions = DummyChecker.check(origin, &chunks[..]).unwrap();
+                set.extend(origin.clone(), suggestions);
+            }
+        }
+
+        let mut session = Session::new(&set, None);
+        assert_eq!(session.occurrences(false), vec![0, 6]);
+        assert_eq!(session.occurrences(true), vec![0, 6, 7]);
+
+        // within the file, with the replacement picked for the first occurrence
+        keys(&mut session, &[KeyCode::Char('a')]);
+        assert_eq!(session.decided(), 2);
+        assert_eq!(
+            session.items[6].decision,
+            Some(Decision::Replace(BandAid::from((
+                "replacement_0".to_owned(),
+                &session.items[6].state.suggestion.span
+            ))))
+        );
+        assert_eq!(session.items[7].decision, None);
+        keys(&mut session, &[KeyCode::Char('u')]);
+        assert_eq!(session.decided(), 0);
+
+        // decided ones are left untouched
+        keys(
+            &mut session,
+            &[KeyCode::Char('g'), KeyCode::Char('7'), KeyCode::Enter],
+        );
+        keys(&mut session, &[KeyCode::Char('y')]);
+        keys(
+            &mut session,
+            &[KeyCode::Char('g'), KeyCode::Char('1'), KeyCode::Enter],
+        );
+        keys(&mut session, &[KeyCode::Char('S')]);
+        assert_eq!(session.items[0].decision, Some(Decision::Skip));
+        assert_ne!(session.items[6].decision, Some(Decision::Skip));
+        assert_eq!(session.items[7].decision, Some(Decision::Skip));
+        // the lower case `teh` is a different word
+        assert_eq!(session.items[3].decision, None);
+    }
+
+    #[test]
+    fn without_replacements() {
+        lazy_static::lazy_static! {
+            static ref UNKNOWN: Vec<Documentation> = vec![
+                Documentation::load_from_str(
+                    ContentOrigin::TestEntityRust,
+                    "/// Teh cat and Teh\nstruct X;",
+                    false,
+                ),
+                Documentation::load_from_str(
+                    ContentOrigin::TestEntityCommonMark,
+                    "Teh end",
+                    false,
+                ),
+            ];
+        }
+        let mut set = SuggestionSet::new();
+        for docs in UNKNOWN.iter() {
+            for (origin, chunks) in docs.iter() {
+                let suggestions = DummyChecker
+                    .check(origin, &chunks[..])
+                    .unwrap()
+                    .into_iter()
+                    .map(|mut suggestion| {
+                        suggestion.detector = Detector::Hunspell;
+                        suggestion.replacements.clear();
+                        suggestion
+                    })
+                    .collect::<Vec<_>>();
+                set.extend(origin.clone(), suggestions);
+            }
+        }
+
+        let mut session = Session::new(&set, Some(Path::new("extra.dic")));
+        assert_eq!(session.items.len(), 6);
+        // the custom entry is focused, typing works right away
+        assert!(session.current().state.is_custom_entry());
+        keys(&mut session, &[KeyCode::Char('T'), KeyCode::Char('x')]);
+        assert_eq!(session.current().state.custom_replacement, "Tx");
+
+        // add to the dictionary, which covers all occurrences
+        keys(&mut session, &[KeyCode::Down]);
+        assert_eq!(
+            session.current().state.extra(),
+            Some(Extra::AddToDictionary)
+        );
+        keys(&mut session, &[KeyCode::Enter]);
+        assert_eq!(session.decided(), 3);
+        assert_eq!(session.dictionary_words(), vec!["Teh"]);
+        assert_eq!(session.cursor, 1);
+        // the custom entry takes all chars, Esc leads to the other choices
+        keys(&mut session, &[KeyCode::Char('u')]);
+        assert_eq!(session.decided(), 3);
+        keys(&mut session, &[KeyCode::Esc, KeyCode::Char('u')]);
+        assert_eq!(session.decided(), 0);
+
+        // suppress inline, covering the occurrences within the chunk only
+        keys(&mut session, &[KeyCode::Char('i')]);
+        assert_eq!(session.decided(), 2);
+        assert_eq!(session.items[3].decision, Some(Decision::Suppress(None)));
+        assert_eq!(session.items[4].decision, None);
+
+        // leave the remaining ones undecided, `cat` still shows the choices
+        assert_eq!(session.cursor, 1);
+        assert!(!session.current().state.is_custom_entry());
+        keys(&mut session, &[KeyCode::Esc]);
+        assert_eq!(session.mode, Mode::Review);
+        let picked = session.into_picked();
+        assert!(picked.words.is_empty());
+        let bandaid = &picked.bandaids[&ContentOrigin::TestEntityRust][0];
+        assert_eq!(bandaid.content, " <!-- spellcheck:ignore Teh -->");
+        assert!(bandaid.insert);
+        assert_eq!(bandaid.span.start, LineColumn { line: 1, column: 7 });
+    }
+
+    #[test]
+    fn suppress_in_documentation_only() {
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityRust,
+            "/// Alpha\n// Beta\n/* Gamma */\nstruct X;",
+            true,
+        );
+        let mut set = SuggestionSet::new();
+        for (origin, chunks) in docs.iter() {
+            // the dummy checker only covers the first chunk
+            for chunk in chunks {
+                let suggestions = DummyChecker
+                    .check(origin, std::slice::from_ref(chunk))
+                    .unwrap();
+                set.extend(origin.clone(), suggestions);
+            }
+        }
+        let session = Session::new(&set, None);
+        let offered = session
+            .items
+            .iter()
+            .map(|item| (item.flagged.as_str(), item.state.offers(Extra::Suppress)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            offered,
+            vec![("Alpha", true), ("Beta", false), ("Gamma", false)]
+        );
+    }
+
+    #[test]
+    fn identical_spans() {
+        let mut set = SuggestionSet::new();
+        let (origin, chunks) = DOCS[0].iter().next().expect("Contains doc comments. qed");
+        for detector in [Detector::Hunspell, Detector::Typos] {
+            let suggestions = DummyChecker
+                .check(origin, &chunks[..])
+                .unwrap()
+                .into_iter()
+                .map(|mut suggestion| {
+                    suggestion.detector = detector;
+                    suggestion
+                });
+            set.extend(origin.clone(), suggestions);
+        }
+
+        let mut session = Session::new(&set, None);
+        assert_eq!(session.items.len(), 6);
+        // the finding of the other detector is decided along
+        keys(&mut session, &[KeyCode::Char('y')]);
+        assert_eq!(session.decided(), 2);
+        assert_eq!(session.items[0].decision, session.items[3].decision);
+        assert_eq!(session.cursor, 1);
+
+        // deciding it again changes both
+        keys(&mut session, &[KeyCode::Char('g'), KeyCode::Char('4')]);
+        keys(&mut session, &[KeyCode::Enter, KeyCode::Char('n')]);
+        assert_eq!(session.items[0].decision, Some(Decision::Skip));
+        keys(&mut session, &[KeyCode::Char('u')]);
+        assert!(matches!(
+            session.items[0].decision,
+            Some(Decision::Replace(_))
+        ));
+        assert_eq!(session.cursor, 3);
+
+        keys(
+            &mut session,
+            &[KeyCode::Char('y'), KeyCode::Char('y'), KeyCode::Char('y')],
+        );
+        assert_eq!(session.mode, Mode::Review);
+        assert_eq!(session.pending().count(), 3);
+        let picked = session.into_picked();
+        assert_eq!(picked.total_count(), 3);
+    }
+
+    #[test]
+    fn abort() {
+        let set = suggestions();
+        let mut session = Session::new(&set, None);
+        assert_eq!(
+            session.handle(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            UserSelection::Abort
+        );
+        keys(&mut session, &[KeyCode::Char('q')]);
+        assert_eq!(session.mode, Mode::Review);
+        assert_eq!(
+            keys(&mut session, &[KeyCode::Char('q')]),
+            UserSelection::Nop
+        );
+        assert_eq!(session.mode, Mode::Pick);
+        keys(&mut session, &[KeyCode::Char('q')]);
+        assert_eq!(
+            session.handle(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)),
+            UserSelection::Abort
+        );
+    }
+}
@@ -4,15 +4,17 @@ use super::*;
 use anyhow::{anyhow, Result};
 use log::{debug, trace};
 
-use std::fs::{self, OpenOptions};
-use std::io::{Read, Write};
+use std::io::Write;
 
 use std::path::PathBuf;
 
 pub mod bandaid;
 pub mod interactive;
+mod patch;
+mod write;
 
 pub(crate) use bandaid::*;
+pub(crate) use patch::*;
 
 /// State of conclusion.
 #[derive(Debug, Clone, Copy)]
@@ -34,24 +36,6 @@ impl Finish {
     }
 }
 
-/// A patch to be stitched on-top of another string.
-///
-/// Has intentionally no awareness of any rust or cmark/markdown semantics.
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) enum Patch {
-    /// Replace the area spanned by `replace` with `replacement`.
-    /// Since `Span` is inclusive, `Replace` will always replace a character in the original sources.
-    Replace {
-        replace_span: Span,
-        replacement: String,
-    },
-    /// Location where to insert.
-    Insert {
-        insert_at: LineColumn,
-        content: String,
-    },
-}
-
 impl<'a> From<&'a BandAid> for Patch {
     fn from(bandaid: &'a BandAid) -> Self {
         // TODO XXX
@@ -61,152 +45,18 @@ impl<'a> From<&'a BandAid> for Patch {
 
 impl From<BandAid> for Patch {
     fn from(bandaid: BandAid) -> Self {
-        match bandaid {
-            bandaid if bandaid.span.start == bandaid.span.end => Self::Insert {
+        if bandaid.insert {
+            Self::Insert {
                 insert_at: bandaid.span.start,
                 content: bandaid.content,
-            },
-            _ => Self::Replace {
-                replace_span: bandaid.span,
-                replacement: bandaid.content,
-            },
-        }
-    }
-}
-
-/// Correct lines by applying patches.
-///
-/// Assumes all `BandAids` do not overlap when replacing.
-/// Inserting multiple times at a particular `LineColumn` is OK,
-/// but replacing overlapping `Span`s of the original source is not.
-///
-/// This function is not concerned with _any_ semantics or comments or
-/// whatsoever at all, it blindly replaces what is given to it.
-pub(crate) fn apply_patches<'s, II, I>(
-    patches: II,
-    source_buffer: &str,
-    mut sink: impl Write,
-) -> Result<()>
-where
-    II: IntoIterator<IntoIter = I, Item = Patch>,
-    I: Iterator<Item = Patch>,
-{
-    let mut patches = patches.into_iter().peekable();
-
-    let mut source_iter =
-        iter_with_line_column_from(source_buffer, LineColumn { line: 1, column: 0 }).peekable();
-
-    const TARGET: &str = "patch";
-    let mut write_to_sink = |topic: &str, data: &str| -> Result<()> {
-        log::trace!(target: TARGET, "w<{}>: {}", topic, data.escape_debug());
-        sink.write(data.as_bytes())?;
-        Ok(())
-    };
-
-    let mut cc_end_byte_offset = 0;
-
-    let mut current = None;
-    let mut byte_cursor = 0usize;
-    loop {
-        let cc_start_byte_offset = if let Some(ref current) = current {
-            let (cc_start, data, insertion) = match current {
-                Patch::Replace {
-                    replace_span,
-                    replacement,
-                } => (replace_span.end, replacement.as_str(), false),
-                Patch::Insert { insert_at, content } => (insert_at.clone(), content.as_str(), true),
-            };
-
-            write_to_sink("new", data)?;
-
-            if insertion {
-                // do not advance anythin on insertion
-                byte_cursor
-            } else {
-                // skip the range of chars based on the line column
-                // so the cursor continues after the "replaced" characters
-                let mut cc_start_byte_offset = byte_cursor;
-                'skip: while let Some((c, byte_offset, _idx, linecol)) = source_iter.peek() {
-                    let byte_offset = *byte_offset;
-                    let linecol = *linecol;
-
-                    cc_start_byte_offset = byte_offset + c.len_utf8();
-
-                    if linecol >= cc_start {
-                        log::trace!(
-                            target: TARGET,
-                            "skip buffer: >{}<",
-                            &source_buffer[cc_end_byte_offset..cc_start_byte_offset].escape_debug()
-                        );
-
-                        break 'skip;
-                    }
-
-                    log::trace!(target: TARGET, "skip[{}]: >{}<", _idx, c.escape_debug());
-
-                    let _ = source_iter.next();
-                }
-                cc_start_byte_offset
             }
         } else {
-            byte_cursor
-        };
-        debug_assert!(byte_cursor <= cc_start_byte_offset);
-        byte_cursor = cc_start_byte_offset;
-
-        cc_end_byte_offset = if let Some(upcoming) = patches.peek() {
-            let cc_end = match upcoming {
-                Patch::Replace { replace_span, .. } => replace_span.start,
-                Patch::Insert { insert_at, .. } => insert_at.clone(),
-            };
-
-            // do not write anything
-
-            // carbon copy until this byte offset
-            let mut cc_end_byte_offset = byte_cursor;
-            'cc: while let Some((c, byte_offset, _idx, linecol)) = source_iter.peek() {
-                let byte_offset = *byte_offset;
-                let linecol = *linecol;
-
-                if linecol >= cc_end {
-                    log::trace!(
-                        target: TARGET,
-                        "copy buffer: >{}<",
-                        &source_buffer[cc_start_byte_offset..cc_end_byte_offset].escape_debug()
-                    );
-                    break 'cc;
-                }
-
-                cc_end_byte_offset = byte_offset + c.len_utf8();
-
-                log::trace!(target: TARGET, "copy[{}]: >{}<", _idx, c.escape_debug());
-
-                let _ = source_iter.next();
-                // we need to drag this one behind, since...
+            Self::Replace {
+                replace_span: bandaid.span,
+                replacement: bandaid.content,
             }
-            // in the case we reach EOF here the `cc_end_byte_offset` could never be updated correctly
-            std::cmp::min(cc_end_byte_offset, source_buffer.len())
-        } else {
-            source_buffer.len()
-        };
-        debug_assert!(byte_cursor <= cc_end_byte_offset);
-
-        byte_cursor = cc_end_byte_offset;
-
-        let cc_range = cc_start_byte_offset..cc_end_byte_offset;
-
-        write_to_sink("cc", &source_buffer[cc_range])?;
-
-        // move on to the next
-        current = patches.next();
-
-        if current.is_none() {
-            // we already made sure earlier to write out everything
-            break;
         }
     }
-
-    Ok(())
 }
 
 /// Mode in which `cargo-spellcheck` operates.
@@ -224,87 +74,55 @@ pub enum Action {
     /// Reflow doc comments, so they adhere to a given maximum column width.
     #[serde(alias = "reflow")]
     Reflow,
-
-    /// Print help and exit.
-    #[serde(alias = "help")]
-    Help,
-
-    /// Print the version info and exit.
-    #[serde(alias = "version")]
-    Version,
+    /// Report doc comments which would be changed by a reflow, without
+    /// writing anything.
+    #[serde(alias = "reflow-check")]
+    ReflowCheck,
 
     /// Print the config being in use, default config if none.
     #[serde(alias = "config")]
     Config,
+
+    /// Diagnose dictionaries, configuration and compiled in checkers.
+    #[serde(alias = "doctor")]
+    Doctor,
+
+    /// Print the shell completions.
+    #[serde(alias = "completions")]
+    Completions,
+
+    /// Print the man page.
+    #[serde(alias = "manpage")]
+    Manpage,
 }
 
 impl Action {
     /// Apply bandaids to the file represented by content origin.
+    ///
+    /// Nothing is written if the file was modified since it was added
+    /// to `documentation`.
     pub fn write_changes_to_disk(
         &self,
         origin: ContentOrigin,
         bandaids: impl IntoIterator<Item = BandAid>,
+        documentation: &Documentation,
+        config: &Config,
     ) -> Result<()> {
-        match origin {
-            ContentOrigin::CommonMarkFile(path) => self.correct_file(path, bandaids),
-            ContentOrigin::RustSourceFile(path) => self.correct_file(path, bandaids),
-            ContentOrigin::RustDocTest(path, _span) => self.correct_file(path, bandaids),
+        let path = match origin {
+            ContentOrigin::CommonMarkFile(path) => path,
+            ContentOrigin::RustSourceFile(path) => path,
+            ContentOrigin::RustDocTest(path, _span) => path,
             #[cfg(test)]
             ContentOrigin::TestEntityRust => unreachable!("Use a proper file"),
             #[cfg(test)]
             ContentOrigin::TestEntityCommonMark => unreachable!("Use a proper file"),
-        }
-    }
-
-    /// assumes suggestions are sorted by line number and column number and must be non overlapping
-    fn correct_file(
-        &self,
-        path: PathBuf,
-        bandaids: impl IntoIterator<Item = BandAid>,
-    ) -> Result<()> {
-        let path = path
-            .as_path()
-            .canonicalize()
-            .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
-        let path = path.as_path();
-        trace!("Attempting to open {} as read", path.display());
-        let ro = std::fs::OpenOptions::new()
-            .read(true)
-            .open(path)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
-
-        let mut reader = std::io::BufReader::new(ro);
-
-        const TEMPORARY: &'static str = ".spellcheck.tmp";
-
-        let tmp = std::env::current_dir()
-            .expect("Must have cwd")
-            .join(TEMPORARY);
-        // let tmp = tmp.canonicalize().map_err(|e| { anyhow!("Failed to canonicalize {}", tmp.display() ).context(e) })?;
-        //trace!("Attempting to open {} as read", tmp.display());
-        let wr = OpenOptions::new()
-            .write(true)
-            .truncate(true)
-            .create(true)
-            .open(&tmp)
-            .map_err(|e| anyhow!("Failed to open {}", path.display()).context(e))?;
-
-        let mut writer = std::io::BufWriter::with_capacity(1024, wr);
-
-        let mut content = String::with_capacity(2e6 as usize);
-        reader.get_mut().read_to_string(&mut content)?;
-
-        apply_patches(
-            bandaids.into_iter().map(|x| Patch::from(x)),
-            content.as_str(), // FIXME for efficiency, correct_lines should integrate with `BufRead` instead of a `String` buffer
-            &mut writer,
-        )?;
-
-        writer.flush()?;
-
-        fs::rename(tmp, path)?;
-
-        Ok(())
+        };
+        write::patch_file(
+            &path,
+            bandaids.into_iter().map(Patch::from),
+            documentation.digest(&path),
+            config.backup,
+        )
     }
 
     /// Consumingly apply the user picked changes to a file.
@@ -314,12 +132,22 @@ impl Action {
     pub fn write_user_pick_changes_to_disk(
         &self,
         userpicked: interactive::UserPicked,
-        _config: &Config,
+        documentation: &Documentation,
+        config: &Config,
     ) -> Result<()> {
+        if !userpicked.words.is_empty() {
+            let dictionary = config
+                .hunspell
+                .as_ref()
+                .and_then(|hunspell| hunspell.extra_dictionaries().first())
+                .ok_or_else(|| anyhow!("No extra dictionary configured to add words to"))?;
+            let added = crate::config::extend_hunspell_dic_path(dictionary, &userpicked.words)?;
+            debug!("Added {} words to {}", added, dictionary.display());
+        }
         if userpicked.total_count() > 0 {
             debug!("Writing changes back to disk");
             for (origin, bandaids) in userpicked.bandaids.into_iter() {
-                self.write_changes_to_disk(origin, bandaids.into_iter())?;
+                self.write_changes_to_disk(origin, bandaids, documentation, config)?;
             }
         } else {
             debug!("No band aids to apply");
@@ -340,13 +168,21 @@ impl Action {
     }
 
     /// Run the requested action.
-    pub fn run(self, suggestions: SuggestionSet, config: &Config) -> Result<Finish> {
+    ///
+    /// `suggestions` must originate from `documentation`.
+    pub fn run(
+        self,
+        suggestions: SuggestionSet,
+        documentation: &Documentation,
+        config: &Config,
+    ) -> Result<Finish> {
         match self {
-            Self::Check => self.check(suggestions, config),
+            Self::Check | Self::ReflowCheck => self.check(suggestions, config),
             Self::Reflow => {
-                let n = suggestions.len();
+                let mut n = 0;
 
                 for (origin, suggestions) in suggestions {
+                    // lines which are too long, yet cannot be reflowed, come without replacement
                     let bandaids = suggestions
                         .into_iter()
                         .filter_map(|suggestion| {
@@ -359,7 +195,12 @@ impl Action {
                             })
                         })
                         .collect::<Vec<_>>();
-                    self.write_changes_to_disk(origin, bandaids)?;
+                    if bandaids.is_empty() {
+                        trace!("Nothing to reflow in {}", origin.as_path().display());
+                        continue;
+                    }
+                    n += bandaids.len();
+                    self.write_changes_to_disk(origin, bandaids, documentation, config)?;
                 }
                 Ok(Finish::MistakeCount(n))
             }
@@ -369,12 +210,12 @@ impl Action {
                 if user_sel == interactive::UserSelection::Abort {
                     Ok(Finish::Abort)
                 } else {
-                    let n = picked.total_count();
-                    self.write_user_pick_changes_to_disk(picked, config)?;
+                    let n = picked.total_count() + picked.words.len();
+                    self.write_user_pick_changes_to_disk(picked, documentation, config)?;
                     Ok(Finish::MistakeCount(n))
                 }
             }
-            Self::Config | Self::Version | Self::Help => {
+            Self::Config | Self::Doctor | Self::Completions | Self::Manpage => {
                 unreachable!("Should have been handled way earlier")
             }
         }
@@ -392,7 +233,7 @@ mod tests {
 
             apply_patches(
                 $bandaids.into_iter().map(|bandaid| Patch::from(bandaid)),
-                $text,
+                $text.as_bytes(),
                 &mut sink,
             )
             .expect("Line correction must work in unit test!");
@@ -522,4 +363,107 @@ Icecream truck"#
         }];
         verify_correction!("A🐢C", patches, "A🐢CQ");
     }
+
+    #[test]
+    fn patch_streaming() {
+        let source = "untouched\nAlpha beta\ngamma delta\nuntouched\n";
+        // out of order and read in tiny pieces
+        let patches = vec![
+            Patch::Insert {
+                insert_at: LineColumn { line: 4, column: 0 },
+                content: "> ".to_owned(),
+            },
+            Patch::Replace {
+                replace_span: Span {
+                    start: LineColumn { line: 2, column: 6 },
+                    end: LineColumn { line: 3, column: 4 },
+                },
+                replacement: "omega".to_owned(),
+            },
+        ];
+        let mut sink = Vec::new();
+        apply_patches(
+            patches,
+            std::io::BufReader::with_capacity(3, source.as_bytes()),
+            &mut sink,
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(sink).unwrap(),
+            "untouched\nAlpha omega delta\n> untouched\n"
+        );
+    }
+
+    #[test]
+    fn patch_single_character() {
+        use crate::checker::{typos::TyposChecker, Checker};
+        use fs_err as fs;
+
+        // the span of `A` starts and ends at the same column
+        let content = "/// A iterator.\nstruct X;\n";
+        let origin = ContentOrigin::TestEntityRust;
+        let docs = Documentation::load_from_str(origin.clone(), content, false);
+        let (_origin, chunks) = docs.iter().next().unwrap();
+        let checker = TyposChecker::new(&Default::default()).unwrap();
+        let bandaids = checker
+            .check(&origin, &chunks[..])
+            .unwrap()
+            .into_iter()
+            .map(|suggestion| {
+                assert_eq!(suggestion.span.start, suggestion.span.end);
+                BandAid::from((suggestion.replacements[0].clone(), &suggestion.span))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bandaids.len(), 1);
+
+        verify_correction!(
+            "/// A iterator.\nstruct X;\n",
+            bandaids.clone(),
+            "/// An iterator.\nstruct X;\n"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        fs::write(&path, content).unwrap();
+        write::patch_file(&path, bandaids.into_iter().map(Patch::from), None, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// An iterator.\nstruct X;\n"
+        );
+    }
+
+    #[test]
+    fn reflow_skips_files_without_changes() {
+        use crate::checker::{dummy::DummyChecker, Checker};
+        use fs_err as fs;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let content = "/// Alpha beta gamma\nstruct X;\n";
+        fs::write(&path, content).unwrap();
+
+        let origin = ContentOrigin::RustSourceFile(path.clone());
+        let docs = Documentation::load_from_str(origin.clone(), content, false);
+        let (_origin, chunks) = docs.iter().next().unwrap();
+        // lines too long yet impossible to reflow come without replacement
+        let suggestions = DummyChecker
+            .check(&origin, &chunks[..])
+            .unwrap()
+            .into_iter()
+            .map(|mut suggestion| {
+                suggestion.replacements.clear();
+                suggestion
+            });
+        let mut set = SuggestionSet::new();
+        set.extend(origin, suggestions);
+
+        let config = Config {
+            backup: true,
+            ..Default::default()
+        };
+        let finish = Action::Reflow.run(set, &docs, &config).unwrap();
+        assert!(!finish.found_any());
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
 }
@@ -0,0 +1,138 @@
+//! Stitching patches on top of a source, line by line.
+//!
+//! Only depends on `LineColumn` and `Span`, so it can be used by the
+//! benchmarks as well, which include this file on its own and provide
+//! their own `Span`. Nothing else of the crate may be used here.
+
+use crate::{LineColumn, Span};
+
+use anyhow::Result;
+use std::io::{BufRead, Write};
+
+/// A patch to be stitched on-top of another string.
+///
+/// Has intentionally no awareness of any rust or cmark/markdown semantics.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Patch {
+    /// Replace the area spanned by `replace` with `replacement`.
+    /// Since `Span` is inclusive, `Replace` will always replace a character in the original sources.
+    Replace {
+        replace_span: Span,
+        replacement: String,
+    },
+    /// Location where to insert.
+    Insert {
+        insert_at: LineColumn,
+        content: String,
+    },
+}
+
+impl Patch {
+    /// Position of the first char of the source the patch applies to.
+    pub(crate) fn start(&self) -> LineColumn {
+        match self {
+            Self::Replace { replace_span, .. } => replace_span.start,
+            Self::Insert { insert_at, .. } => *insert_at,
+        }
+    }
+
+    /// Order in which patches are applied, by position, where insertions
+    /// go in front of a replacement at the same position.
+    pub(crate) fn order(&self) -> (LineColumn, bool) {
+        (self.start(), matches!(self, Self::Replace { .. }))
+    }
+
+    /// The content added by the patch.
+    fn content(&self) -> &str {
+        match self {
+            Self::Replace { replacement, .. } => replacement.as_str(),
+            Self::Insert { content, .. } => content.as_str(),
+        }
+    }
+}
+
+/// Correct lines by applying patches.
+///
+/// Assumes all `BandAids` do not overlap when replacing.
+/// Inserting multiple times at a particular `LineColumn` is OK,
+/// but replacing overlapping `Span`s of the original source is not.
+///
+/// The source is streamed line by line into the sink, lines not touched by
+/// any patch are copied as is, so the cost is linear in the size of the
+/// source. Patches at positions beyond the end of the source are appended.
+///
+/// This function is not concerned with _any_ semantics or comments or
+/// whatsoever at all, it blindly replaces what is given to it.
+pub(crate) fn apply_patches<II>(
+    patches: II,
+    mut source: impl BufRead,
+    mut sink: impl Write,
+) -> Result<()>
+where
+    II: IntoIterator<Item = Patch>,
+{
+    const TARGET: &str = "patch";
+
+    let mut patches = patches.into_iter().collect::<Vec<_>>();
+    patches.sort_by_key(Patch::order);
+    let mut patches = patches.into_iter().peekable();
+
+    // inclusive end of the area currently being replaced
+    let mut replacing: Option<LineColumn> = None;
+    let mut line = String::with_capacity(256);
+    let mut lineno = 0usize;
+    loop {
+        line.clear();
+        if source.read_line(&mut line)? == 0 {
+            break;
+        }
+        lineno += 1;
+
+        let touched = replacing.is_some()
+            || patches
+                .peek()
+                .map_or(false, |patch| patch.start().line <= lineno);
+        if !touched {
+            sink.write_all(line.as_bytes())?;
+            continue;
+        }
+
+        // start of the chars not written yet
+        let mut run = 0usize;
+        for (column, (offset, c)) in line.char_indices().enumerate() {
+            let at = LineColumn {
+                line: lineno,
+                column,
+            };
+            if patches.peek().map_or(false, |patch| patch.start() <= at) {
+                sink.write_all(&line.as_bytes()[run..offset])?;
+                run = offset;
+                while let Some(patch) = patches.next_if(|patch| patch.start() <= at) {
+                    log::trace!(target: TARGET, "{}:{} w<new>: {:?}", lineno, column, patch.content());
+                    sink.write_all(patch.content().as_bytes())?;
+                    if let Patch::Replace { replace_span, .. } = patch {
+                        replacing = Some(
+                            replacing.map_or(replace_span.end, |end| end.max(replace_span.end)),
+                        );
+                    }
+                }
+            }
+            match replacing {
+                Some(end) if at <= end => {
+                    // skip the replaced char
+                    sink.write_all(&line.as_bytes()[run..offset])?;
+                    run = offset + c.len_utf8();
+                }
+                Some(_) => replacing = None,
+                None => {}
+            }
+        }
+        sink.write_all(&line.as_bytes()[run..])?;
+    }
+
+    for patch in patches {
+        log::trace!(target: TARGET, "w<eof>: {:?}", patch.content());
+        sink.write_all(patch.content().as_bytes())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,274 @@
+//! Writing patched files back to disk.
+//!
+//! The patched content is written to a temporary file next to the
+//! target, which then replaces the target by renaming it. The file
+//! permissions and line endings of the target are preserved, and
+//! nothing is written if the target was modified since it was read
+//! for checking.
+
+use super::{apply_patches, Patch};
+use crate::util::reader_digest;
+
+use anyhow::{anyhow, bail, Result};
+use fs_err as fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Extension of backup files.
+const BACKUP_EXTENSION: &str = "orig";
+
+/// Sort the patches by position and make sure no two of them touch the
+/// same chars of the original content.
+///
+/// Inserting multiple times at the same position is fine, as is
+/// inserting right in front of a replaced area.
+pub(crate) fn sort_and_verify(patches: &mut [Patch]) -> Result<()> {
+    patches.sort_by_key(Patch::order);
+    for pair in patches.windows(2) {
+        let (previous, next) = (&pair[0], &pair[1]);
+        if let Patch::Replace { replace_span, .. } = previous {
+            if next.start() <= replace_span.end {
+                let end = match next {
+                    Patch::Replace { replace_span, .. } => replace_span.end,
+                    Patch::Insert { insert_at, .. } => *insert_at,
+                };
+                bail!(
+                    "Overlapping changes, {}:{}-{}:{} and {}:{}-{}:{} both modify the same content",
+                    replace_span.start.line,
+                    replace_span.start.column,
+                    replace_span.end.line,
+                    replace_span.end.column,
+                    next.start().line,
+                    next.start().column,
+                    end.line,
+                    end.column,
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Use the line endings of `content` for all newly added lines.
+fn adjust_line_endings(patch: Patch, crlf: bool) -> Patch {
+    if !crlf {
+        return patch;
+    }
+    let convert = |s: String| {
+        if s.contains('\n') {
+            s.replace("\r\n", "\n").replace('\n', "\r\n")
+        } else {
+            s
+        }
+    };
+    match patch {
+        Patch::Replace {
+            replace_span,
+            replacement,
+        } => Patch::Replace {
+            replace_span,
+            replacement: convert(replacement),
+        },
+        Patch::Insert { insert_at, content } => Patch::Insert {
+            insert_at,
+            content: convert(content),
+        },
+    }
+}
+
+/// A path next to `path`, with an additional extension.
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+/// Removes the temporary file unless it was moved in place.
+struct Temporary(Option<PathBuf>);
+
+impl Drop for Temporary {
+    fn drop(&mut self) {
+        if let Some(path) = self.0.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Apply `patches` to the file at `path`.
+///
+/// If `expected` is given, the content must still have this digest, so
+/// the patches, which are relative to the content that was checked,
+/// apply. A copy of the original content is kept with an additional
+/// `.orig` extension if `backup` is set.
+pub(crate) fn patch_file(
+    path: &Path,
+    patches: impl IntoIterator<Item = Patch>,
+    expected: Option<u64>,
+    backup: bool,
+) -> Result<()> {
+    let path = path
+        .canonicalize()
+        .map_err(|e| anyhow!("Failed to canonicalize {}", path.display()).context(e))?;
+    let path = path.as_path();
+    log::trace!("Attempting to read {}", path.display());
+    let digest = reader_digest(fs::File::open(path)?)?;
+    if expected.map_or(false, |expected| expected != digest) {
+        bail!(
+            "{} was modified since it was checked, not applying any changes",
+            path.display()
+        );
+    }
+
+    // the line ending of the first line is used for all new lines
+    let mut first = Vec::with_capacity(256);
+    BufReader::new(fs::File::open(path)?).read_until(b'\n', &mut first)?;
+    let crlf = first.ends_with(b"\r\n");
+    let mut patches = patches
+        .into_iter()
+        .map(|patch| adjust_line_endings(patch, crlf))
+        .collect::<Vec<_>>();
+    sort_and_verify(&mut patches)
+        .map_err(|e| e.context(format!("Refusing to modify {}", path.display())))?;
+
+    let permissions = fs::metadata(path)?.permissions();
+
+    // a unique name per process, so parallel runs do not interfere
+    let tmp = sibling(path, &format!("spellcheck.{}.tmp", std::process::id()));
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&tmp)?;
+    let mut guard = Temporary(Some(tmp.clone()));
+
+    let mut writer = std::io::BufWriter::new(file);
+    apply_patches(patches, BufReader::new(fs::File::open(path)?), &mut writer)?;
+    let file = writer
+        .into_inner()
+        .map_err(|e| anyhow!("Failed to write {}", tmp.display()).context(e.into_error()))?;
+    file.sync_all()?;
+    drop(file);
+    fs::set_permissions(&tmp, permissions)?;
+
+    // the file might have been modified while writing the patched one
+    if reader_digest(fs::File::open(path)?)? != digest {
+        bail!(
+            "{} was modified while applying changes, not applying any changes",
+            path.display()
+        );
+    }
+    if backup {
+        let backup = sibling(path, BACKUP_EXTENSION);
+        log::debug!("Keeping a copy of the original in {}", backup.display());
+        fs::copy(path, &backup)?;
+    }
+    fs::rename(&tmp, path)?;
+    guard.0 = None;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::content_digest;
+    use crate::{LineColumn, Span};
+
+    fn replace(start: (usize, usize), end: (usize, usize), replacement: &str) -> Patch {
+        Patch::Replace {
+            replace_span: Span {
+                start: LineColumn {
+                    line: start.0,
+                    column: start.1,
+                },
+                end: LineColumn {
+                    line: end.0,
+                    column: end.1,
+                },
+            },
+            replacement: replacement.to_owned(),
+        }
+    }
+
+    fn insert(at: (usize, usize), content: &str) -> Patch {
+        Patch::Insert {
+            insert_at: LineColumn {
+                line: at.0,
+                column: at.1,
+            },
+            content: content.to_owned(),
+        }
+    }
+
+    #[test]
+    fn overlapping() {
+        let mut patches = vec![replace((1, 4), (1, 6), "x"), replace((1, 0), (1, 2), "y")];
+        assert!(sort_and_verify(&mut patches).is_ok());
+        assert_eq!(patches[0], replace((1, 0), (1, 2), "y"));
+
+        let mut patches = vec![replace((1, 0), (1, 4), "x"), replace((1, 4), (1, 6), "y")];
+        assert!(sort_and_verify(&mut patches).is_err());
+
+        let mut patches = vec![replace((1, 0), (2, 1), "x"), insert((1, 7), "y")];
+        assert!(sort_and_verify(&mut patches).is_err());
+
+        let mut patches = vec![
+            replace((1, 0), (1, 2), "z"),
+            insert((1, 0), "x"),
+            insert((1, 0), "y"),
+            insert((1, 3), "w"),
+        ];
+        assert!(sort_and_verify(&mut patches).is_ok());
+        assert_eq!(patches[1], insert((1, 0), "y"));
+    }
+
+    #[test]
+    fn patch_preserves_line_endings_and_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let original = "/// Teh cat\r\nstruct X;\r\n";
+        fs::write(&path, original).unwrap();
+        let digest = content_digest(original);
+
+        patch_file(
+            &path,
+            vec![replace((1, 4), (1, 6), "The\nbig")],
+            Some(digest),
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// The\r\nbig cat\r\nstruct X;\r\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.path().join("lib.rs.orig")).unwrap(),
+            original
+        );
+        // no leftovers besides the backup
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+
+        // the content changed since it was checked
+        let err = patch_file(&path, vec![insert((1, 0), "x")], Some(digest), false);
+        assert!(err.is_err());
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "/// The\r\nbig cat\r\nstruct X;\r\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn patch_preserves_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.rs");
+        fs::write(&path, "// Teh\n").unwrap();
+        fs::set_permissions(&path, std::fs::Permissions::from_mode(0o751)).unwrap();
+
+        patch_file(&path, vec![replace((1, 3), (1, 5), "The")], None, false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "// The\n");
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o751);
+    }
+}
@@ -0,0 +1,206 @@
+//! Project specific style and terminology rules.
+//!
+//! Every match of a configured regular expression in the plain text of a
+//! chunk is reported, with the expanded replacement template as fix.
+
+use super::{Checker, Detector, Suggestion};
+use crate::config::{CustomRule, CustomRulesConfig};
+use crate::documentation::PlainOverlay;
+use crate::util::byte_range_to_char_range;
+use crate::{CheckableChunk, ContentOrigin, RuleId};
+
+use anyhow::{anyhow, Result};
+use log::{debug, trace};
+
+pub(crate) struct CustomRulesChecker {
+    rules: Vec<CustomRule>,
+}
+
+impl Checker for CustomRulesChecker {
+    type Config = CustomRulesConfig;
+
+    fn detector() -> Detector {
+        Detector::CustomRules
+    }
+
+    fn new(config: &Self::Config) -> Result<Self> {
+        debug!("Using {} custom rules", config.rules.len());
+        Ok(Self {
+            rules: config.rules.clone(),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut acc = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let plain = chunk.erase_cmark();
+            trace!("{:?}", &plain);
+            for rule in &self.rules {
+                acc.extend(check_plain(origin, chunk, &plain, rule)?);
+            }
+        }
+        Ok(acc)
+    }
+}
+
+/// Report all matches of `rule` in `plain`, the plain text of `chunk`.
+fn check_plain<'a>(
+    origin: &ContentOrigin,
+    chunk: &'a CheckableChunk,
+    plain: &PlainOverlay,
+    rule: &CustomRule,
+) -> Result<Vec<Suggestion<'a>>> {
+    let txt = plain.as_str();
+    let id = RuleId {
+        category: rule.category.clone(),
+        id: rule.id.clone(),
+    };
+
+    let mut acc = Vec::new();
+    for captures in rule.pattern.captures_iter(txt) {
+        let captures = captures
+            .map_err(|e| anyhow!("Failed to match custom rule {} in {}", id, origin).context(e))?;
+        let whole = captures.get(0).expect("Group 0 is the whole match. qed");
+        if whole.start() == whole.end() {
+            continue;
+        }
+        let range = if let Some(range) = byte_range_to_char_range(txt, whole.start()..whole.end()) {
+            range
+        } else {
+            continue;
+        };
+        trace!("Custom rule {} matched {:?}", id, whole.as_str());
+
+        let expand = |template: &str| {
+            let mut expanded = String::new();
+            captures.expand(template, &mut expanded);
+            expanded
+        };
+        let message = expand(&rule.message);
+        let replacements = rule
+            .replacement
+            .as_deref()
+            .map(expand)
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        acc.extend(
+            plain
+                .find_spans(range)
+                .into_iter()
+                .map(|(range, span)| Suggestion {
+                    detector: Detector::CustomRules,
+                    range,
+                    span,
+                    origin: origin.clone(),
+                    replacements: replacements.clone(),
+                    chunk,
+                    description: Some(message.clone()),
+                    rule: Some(id.clone()),
+                    severity: rule.severity.unwrap_or_default(),
+                }),
+        );
+    }
+    Ok(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::tests::check_rust;
+    use crate::{Documentation, Severity};
+
+    const RULES: &str = r#"
+[[rule]]
+id = "EG"
+pattern = "\\beg\\."
+message = "Write `e.g.`"
+replacement = "e.g."
+
+[[rule]]
+id = "GITHUB"
+category = "TERMINOLOGY"
+pattern = "\\bGit(h)ub\\b"
+message = "Spell it `GitHub`, not `$0`"
+replacement = "GitHub"
+
+[[rule]]
+id = "SIMPLY"
+pattern = "(?i)\\bsimply\\s+"
+message = "Avoid `simply`"
+replacement = ""
+severity = "warning"
+
+[[rule]]
+id = "WASM"
+pattern = "\\b(?i:web)(?i:assembly)\\b"
+message = "Capitalize `WebAssembly`"
+replacement = "WebAssembly"
+"#;
+
+    fn check(source: &str) -> Vec<(String, String, Vec<String>, Severity)> {
+        let config: CustomRulesConfig = toml::from_str(RULES).unwrap();
+        check_rust(&CustomRulesChecker::new(&config).unwrap(), source)
+    }
+
+    #[test]
+    fn house_style() {
+        let found = check(
+            "/// Simply push eg. a webassembly module to Github.\n///\n/// Not `Github` in code, e.g. fine.\nstruct X;",
+        );
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "CUSTOM/EG".to_owned(),
+                    "eg.".to_owned(),
+                    vec!["e.g.".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "TERMINOLOGY/GITHUB".to_owned(),
+                    "Github".to_owned(),
+                    vec!["GitHub".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "CUSTOM/SIMPLY".to_owned(),
+                    "Simply ".to_owned(),
+                    vec!["".to_owned()],
+                    Severity::Warning
+                ),
+                (
+                    "CUSTOM/WASM".to_owned(),
+                    "webassembly".to_owned(),
+                    vec!["WebAssembly".to_owned()],
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn templates_refer_to_captures() {
+        let config: CustomRulesConfig = toml::from_str(RULES).unwrap();
+        let checker = CustomRulesChecker::new(&config).unwrap();
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityRust,
+            "/// On Github.\nstruct X;",
+            false,
+        );
+        let (origin, chunks) = docs.iter().next().unwrap();
+        let suggestions = checker.check(origin, chunks).unwrap();
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(
+            suggestions[0].description.as_deref(),
+            Some("Spell it `GitHub`, not `Github`")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+//! Checks of the individual words of a chunk against a dictionary
+//!
+//! Shared by the dictionary based checkers, tokenizes the plain text of
+//! the chunks, applies the configured quirks and reports every word the
+//! dictionary does not know, with its suggestions as replacements.
+
+use super::quirks::{
+    replacements_contain_dashed, replacements_contain_dashless, transform, Transformed,
+};
+use super::{tokenize, Detector, Suggestion};
+
+use crate::config::Quirks;
+use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
+use crate::util::sub_chars;
+use crate::{Range, Severity};
+
+use log::trace;
+
+/// A word list with affixes, which can check words and suggest replacements.
+pub(crate) trait Dictionary {
+    /// Check if `word` is spelled correctly.
+    fn check(&self, word: &str) -> bool;
+
+    /// Suggest replacements for the misspelled `word`, best first.
+    fn suggest(&self, word: &str) -> Vec<String>;
+}
+
+/// Check all words of `chunks`, reported as found by `detector`.
+pub(crate) fn check_words<'a, 's, D: Dictionary + ?Sized>(
+    dictionary: &D,
+    detector: Detector,
+    quirks: &Quirks,
+    origin: &ContentOrigin,
+    chunks: &'a [CheckableChunk],
+) -> Vec<Suggestion<'s>>
+where
+    'a: 's,
+{
+    let transform_regex = quirks.transform_regex();
+    let context = Context {
+        dictionary,
+        detector,
+        origin,
+        allow_concatenated: quirks.allow_concatenated(),
+        allow_dashed: quirks.allow_dashed(),
+    };
+
+    let mut acc = Vec::with_capacity(chunks.len());
+    for chunk in chunks {
+        let plain = chunk.erase_cmark();
+        trace!("{:?}", &plain);
+        let txt = plain.as_str();
+        for range in tokenize(txt) {
+            let word = sub_chars(txt, range.clone());
+            if transform_regex.is_empty() {
+                context.obtain_suggestions(&plain, chunk, word, range, &mut acc)
+            } else {
+                match transform(transform_regex, word.as_str(), range.clone()) {
+                    Transformed::Fragments(word_fragments) => {
+                        for (range, word_fragment) in word_fragments {
+                            context.obtain_suggestions(
+                                &plain,
+                                chunk,
+                                word_fragment.to_owned(),
+                                range,
+                                &mut acc,
+                            );
+                        }
+                    }
+                    Transformed::Atomic((range, word)) => {
+                        context.obtain_suggestions(&plain, chunk, word.to_owned(), range, &mut acc);
+                    }
+                    Transformed::Whitelisted(_) => {}
+                }
+            }
+        }
+    }
+    acc
+}
+
+/// Everything but the word, which stays the same for all checked words.
+struct Context<'d, D: ?Sized> {
+    dictionary: &'d D,
+    detector: Detector,
+    origin: &'d ContentOrigin,
+    allow_concatenated: bool,
+    allow_dashed: bool,
+}
+
+impl<'d, D: Dictionary + ?Sized> Context<'d, D> {
+    fn obtain_suggestions<'s>(
+        &self,
+        plain: &PlainOverlay,
+        chunk: &'s CheckableChunk,
+        word: String,
+        range: Range,
+        acc: &mut Vec<Suggestion<'s>>,
+    ) {
+        if self.dictionary.check(&word) {
+            trace!(
+                "Found a match for word (plain range: {:?}): >{}<",
+                &range,
+                word
+            );
+            return;
+        }
+        trace!("No match for word (plain range: {:?}): >{}<", &range, &word);
+        // get rid of single character suggestions
+        let replacements = self
+            .dictionary
+            .suggest(&word)
+            .into_iter()
+            .filter(|x| x.len() > 1) // single char suggestions tend to be useless
+            .collect::<Vec<_>>();
+
+        if self.allow_concatenated && replacements_contain_dashless(&word, replacements.as_slice())
+        {
+            trace!(target: "quirks", "Found dashless word in replacement suggestions, treating {} as ok", &word);
+            return;
+        }
+        if self.allow_dashed && replacements_contain_dashed(&word, replacements.as_slice()) {
+            trace!(target: "quirks", "Found dashed word in replacement suggestions, treating {} as ok", &word);
+            return;
+        }
+        for (range, span) in plain.find_spans(range.clone()) {
+            acc.push(Suggestion {
+                detector: self.detector,
+                range,
+                span,
+                origin: self.origin.clone(),
+                replacements: replacements.clone(),
+                chunk,
+                rule: None,
+                severity: Severity::Error,
+                description: Some("Possible spelling mistake found.".to_owned()),
+            })
+        }
+    }
+}
@@ -4,8 +4,8 @@
 
 use super::tokenize;
 use super::Checker;
-use crate::documentation::Documentation;
-use crate::suggestion::{Detector, Suggestion, SuggestionSet};
+use crate::documentation::{CheckableChunk, ContentOrigin};
+use crate::suggestion::{Detector, Severity, Suggestion};
 use crate::util::sub_chars;
 use anyhow::Result;
 use log::trace;
@@ -20,49 +20,52 @@ impl Checker for DummyChecker {
         Detector::Dummy
     }
 
-    fn check<'a, 's>(docu: &'a Documentation, _: &Self::Config) -> Result<SuggestionSet<'s>>
+    fn new(_config: &Self::Config) -> Result<Self> {
+        Ok(Self)
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
     where
         'a: 's,
     {
-        let suggestions = docu.iter().try_fold::<SuggestionSet, _, Result<_>>(
-            SuggestionSet::new(),
-            |mut acc, (origin, chunks)| {
-                let chunk = chunks
-                    .iter()
-                    .next()
-                    .expect("DummyChecker expects at least one chunk");
-                let plain = chunk.erase_cmark();
-                for (index, range) in dbg!(tokenize(plain.as_str())).into_iter().enumerate() {
+        let mut acc = Vec::with_capacity(8);
+        // only the first chunk is relevant for the extraction tests
+        if let Some(chunk) = chunks.first() {
+            let plain = chunk.erase_cmark();
+            for (index, range) in dbg!(tokenize(plain.as_str())).into_iter().enumerate() {
+                trace!(
+                    "****Token[{}]: >{}<",
+                    index,
+                    sub_chars(plain.as_str(), range.clone())
+                );
+                let detector = Detector::Dummy;
+                let range2span = plain.find_spans(range.clone());
+                for (range, span) in range2span {
                     trace!(
-                        "****Token[{}]: >{}<",
-                        index,
-                        sub_chars(plain.as_str(), range.clone())
+                        "Suggestion for {:?} -> {}",
+                        range,
+                        chunk.display(range.clone())
                     );
-                    let detector = Detector::Dummy;
-                    let range2span = plain.find_spans(range.clone());
-                    for (range, span) in range2span {
-                        trace!(
-                            "Suggestion for {:?} -> {}",
-                            range,
-                            chunk.display(range.clone())
-                        );
-                        let replacements = vec![format!("replacement_{}", index)];
-                        let suggestion = Suggestion {
-                            detector,
-                            span,
-                            range,
-                            origin: origin.clone(),
-                            replacements,
-                            chunk,
-                            description: None,
-                        };
-                        acc.add(origin.clone(), suggestion);
-                    }
+                    let replacements = vec![format!("replacement_{}", index)];
+                    let suggestion = Suggestion {
+                        detector,
+                        span,
+                        range,
+                        origin: origin.clone(),
+                        replacements,
+                        chunk,
+                        rule: None,
+                        severity: Severity::Error,
+                        description: None,
+                    };
+                    acc.push(suggestion);
                 }
-                Ok(acc)
-            },
-        )?;
-
-        Ok(suggestions)
+            }
+        }
+        Ok(acc)
     }
 }
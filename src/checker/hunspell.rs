@@ -5,84 +5,53 @@
 //! the defined affixes.
 //! Can handle multiple dictionaries.
 
-use super::{tokenize, Checker, Detector, Documentation, Suggestion, SuggestionSet};
+use super::dictionary::{check_words, Dictionary};
+use super::{Checker, Detector, Suggestion};
 
-use crate::documentation::{CheckableChunk, ContentOrigin, PlainOverlay};
-use crate::util::sub_chars;
-use crate::Range;
+use crate::config::{is_valid_hunspell_dic_path, Quirks};
+use crate::documentation::{CheckableChunk, ContentOrigin};
 
-use fs_err as fs;
-use log::{debug, trace};
-use rayon::prelude::*;
-use std::io::{self, BufRead};
-use std::path::{Path, PathBuf};
+use log::{debug, info};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use hunspell_rs::Hunspell;
 
-use anyhow::{anyhow, bail, Result};
-
-use super::quirks::{
-    replacements_contain_dashed, replacements_contain_dashless, transform, Transformed,
-};
+use anyhow::{bail, Result};
 
 pub struct HunspellWrapper(pub Arc<Hunspell>);
 
 unsafe impl Send for HunspellWrapper {}
 unsafe impl Sync for HunspellWrapper {}
 
-pub struct HunspellChecker;
+pub struct HunspellChecker {
+    hunspell: HunspellWrapper,
+    quirks: Quirks,
+}
 
 impl HunspellChecker {
     fn inner_init(config: &<Self as Checker>::Config) -> Result<HunspellWrapper> {
-        let search_dirs = config.search_dirs();
-
         let lang = config.lang();
 
         // lookup paths are really just an attempt to provide a dictionary, so be more forgiving
         // when encountering errors here
-        let (dic, aff): (PathBuf, PathBuf) = search_dirs
-            .into_iter()
-            .filter(|search_dir| {
-                let keep = search_dir.is_dir();
-                if !keep {
-                    // search_dir also contains the default paths, so just silently ignore these
-                    debug!(
-                        "Dictionary search path is not a directory {}",
-                        search_dir.display()
-                    );
-                } else {
-                    debug!(
-                        "Found dictionary search path {}",
-                        search_dir.display()
-                    );
-                }
-                keep
-            })
-            .find_map(|search_dir| {
-                let dic = search_dir.join(lang).with_extension("dic");
-                if !dic.is_file() {
-                    debug!(
-                        "Dictionary path dervied from search dir is not a file {}",
-                        dic.display()
-                    );
-                    return None;
-                }
-                let aff = search_dir.join(lang).with_extension("aff");
-                if !aff.is_file() {
-                    debug!(
-                        "Affixes path dervied from search dir is not a file {}",
-                        aff.display()
-                    );
-                    return None;
-                }
-                debug!("Using dic {} and aff {}", dic.display(), aff.display());
-                Some((dic, aff))
-            })
-            .ok_or_else(|| {
-                anyhow!("Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided",
-                lang = lang)
-            })?;
+        let (dic, aff): (PathBuf, PathBuf) = match config.lookup_dictionary().found {
+            Some(found) => found,
+            #[cfg(feature = "bundled-dictionary")]
+            None if config.has_bundled_fallback() => {
+                info!("No {lang}.dic / {lang}.aff in any search dir", lang = lang);
+                extract_bundled()?
+            }
+            None => bail!(
+                "Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided, `--checkers=typos` checks without any dictionary",
+                lang = lang
+            ),
+        };
+        info!(
+            "Using dictionary {} with affixes {}",
+            dic.display(),
+            aff.display()
+        );
 
         let dic = dic.to_str().unwrap();
         let aff = aff.to_str().unwrap();
@@ -125,6 +94,61 @@ impl HunspellChecker {
     }
 }
 
+/// The bundled dictionary, `.dic` and `.aff`, xz compressed.
+#[cfg(feature = "bundled-dictionary")]
+static BUNDLED: [(&str, &[u8]); 2] = [
+    (
+        "dic",
+        include_bytes!(concat!(env!("OUT_DIR"), "/en_US.dic.xz")),
+    ),
+    (
+        "aff",
+        include_bytes!(concat!(env!("OUT_DIR"), "/en_US.aff.xz")),
+    ),
+];
+
+/// Extract the bundled dictionary into the cache dir, unless it was before.
+///
+/// `libhunspell` only loads dictionaries from files. The directory is named
+/// after the version and a checksum of the content, so a dictionary extracted
+/// by a different build is never picked up.
+#[cfg(feature = "bundled-dictionary")]
+fn extract_bundled() -> Result<(PathBuf, PathBuf)> {
+    use crate::config::BUNDLED_LANG;
+    use fs_err as fs;
+    use std::io::Write;
+
+    let mut checksum = crc32fast::Hasher::new();
+    for (_extension, compressed) in BUNDLED.iter() {
+        checksum.update(compressed);
+    }
+    let dir = crate::Config::cache_dir()?.join("dictionary").join(format!(
+        "{}-{:08x}",
+        env!("CARGO_PKG_VERSION"),
+        checksum.finalize()
+    ));
+    let path = |extension: &str| dir.join(BUNDLED_LANG).with_extension(extension);
+
+    for (extension, compressed) in BUNDLED.iter() {
+        let target = path(extension);
+        if target.is_file() {
+            continue;
+        }
+        debug!("Extracting the bundled dictionary to {}", target.display());
+        fs::create_dir_all(&dir)?;
+        let mut content = Vec::with_capacity(compressed.len() * 4);
+        std::io::copy(&mut xz2::read::XzDecoder::new(*compressed), &mut content).map_err(|e| {
+            anyhow::anyhow!("Failed to decompress the bundled {}", extension).context(e)
+        })?;
+        // concurrent runs must never see a partially written file
+        let partial = target.with_extension(format!("{}.{}", extension, std::process::id()));
+        fs::File::create(&partial)?.write_all(&content)?;
+        fs::rename(&partial, &target)?;
+    }
+    info!("Using the bundled {} dictionary", BUNDLED_LANG);
+    Ok((path("dic"), path("aff")))
+}
+
 impl Checker for HunspellChecker {
     type Config = crate::config::HunspellConfig;
 
@@ -132,215 +156,47 @@ impl Checker for HunspellChecker {
         Detector::Hunspell
     }
 
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    fn new(config: &Self::Config) -> Result<Self> {
+        let hunspell = Self::inner_init(config)?;
+        Ok(Self {
+            hunspell,
+            quirks: config.quirks.clone(),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
     where
         'a: 's,
     {
-        let hunspell = Self::inner_init(config)?;
-
-        let (transform_regex, allow_concatenated, allow_dashed) = {
-            let quirks = &config.quirks;
-            {
-                (
-                    quirks.transform_regex(),
-                    quirks.allow_concatenated(),
-                    quirks.allow_dashed(),
-                )
-            }
-        };
-
-        let suggestions = docu
-            .par_iter()
-            .try_fold::<SuggestionSet, Result<_>, _, _>(
-                || SuggestionSet::new(),
-                move |mut acc, (origin, chunks)| {
-                    debug!("Processing {}", origin.as_path().display());
-
-                    for chunk in chunks {
-                        let plain = chunk.erase_cmark();
-                        trace!("{:?}", &plain);
-                        let txt = plain.as_str();
-                        let hunspell = &*hunspell.0;
-                        for range in tokenize(txt) {
-                            let word = sub_chars(txt, range.clone());
-                            if transform_regex.is_empty() {
-                                obtain_suggestions(
-                                    &plain,
-                                    chunk,
-                                    &hunspell,
-                                    origin,
-                                    word,
-                                    range,
-                                    allow_concatenated,
-                                    allow_dashed,
-                                    &mut acc,
-                                )
-                            } else {
-                                match transform(&transform_regex[..], word.as_str(), range.clone())
-                                {
-                                    Transformed::Fragments(word_fragments) => {
-                                        for (range, word_fragment) in word_fragments {
-                                            obtain_suggestions(
-                                                &plain,
-                                                chunk,
-                                                &hunspell,
-                                                origin,
-                                                word_fragment.to_owned(),
-                                                range,
-                                                allow_concatenated,
-                                                allow_dashed,
-                                                &mut acc,
-                                            );
-                                        }
-                                    }
-                                    Transformed::Atomic((range, word)) => {
-                                        obtain_suggestions(
-                                            &plain,
-                                            chunk,
-                                            &hunspell,
-                                            origin,
-                                            word.to_owned(),
-                                            range,
-                                            allow_concatenated,
-                                            allow_dashed,
-                                            &mut acc,
-                                        );
-                                    }
-                                    Transformed::Whitelisted(_) => {}
-                                }
-                            }
-                        }
-                    }
-                    Ok(acc)
-                },
-            )
-            .try_reduce(
-                || SuggestionSet::new(),
-                |mut a, b| {
-                    a.join(b);
-                    Ok(a)
-                },
-            )?;
-
-        // TODO sort spans by file and line + column
-        Ok(suggestions)
+        Ok(check_words(
+            &*self.hunspell.0,
+            Detector::Hunspell,
+            &self.quirks,
+            origin,
+            chunks,
+        ))
     }
 }
 
-fn obtain_suggestions<'s>(
-    plain: &PlainOverlay,
-    chunk: &'s CheckableChunk,
-    hunspell: &Hunspell,
-    origin: &ContentOrigin,
-    word: String,
-    range: Range,
-    allow_concatenated: bool,
-    allow_dashed: bool,
-    acc: &mut SuggestionSet<'s>,
-) {
-    if !hunspell.check(&word) {
-        trace!("No match for word (plain range: {:?}): >{}<", &range, &word);
-        // get rid of single character suggestions
-        let replacements = hunspell
-            .suggest(&word)
-            .into_iter()
-            .filter(|x| x.len() > 1) // single char suggestions tend to be useless
-            .collect::<Vec<_>>();
-
-        if allow_concatenated && replacements_contain_dashless(&word, replacements.as_slice()) {
-            trace!(target: "quirks", "Found dashless word in replacement suggestions, treating {} as ok", &word);
-            return;
-        }
-        if allow_dashed && replacements_contain_dashed(&word, replacements.as_slice()) {
-            trace!(target: "quirks", "Found dashed word in replacement suggestions, treating {} as ok", &word);
-            return;
-        }
-        for (range, span) in plain.find_spans(range.clone()) {
-            acc.add(
-                origin.clone(),
-                Suggestion {
-                    detector: Detector::Hunspell,
-                    range,
-                    span,
-                    origin: origin.clone(),
-                    replacements: replacements.clone(),
-                    chunk,
-                    description: Some("Possible spelling mistake found.".to_owned()),
-                },
-            )
-        }
-    } else {
-        trace!(
-            "Found a match for word (plain range: {:?}): >{}<",
-            &range,
-            word
-        );
+impl Dictionary for Hunspell {
+    fn check(&self, word: &str) -> bool {
+        Hunspell::check(self, word)
     }
-}
-
-/// Check if provided path has valid dictionary format.
-///
-/// This is a YOLO check.
-fn is_valid_hunspell_dic_path(path: impl AsRef<Path>) -> Result<()> {
-    let reader = io::BufReader::new(fs::File::open(path.as_ref())?);
-    is_valid_hunspell_dic(reader)
-}
 
-/// Check a reader for correct hunspell format.
-fn is_valid_hunspell_dic(reader: impl BufRead) -> Result<()> {
-    let mut iter = reader.lines().enumerate();
-    if let Some((_lineno, first)) = iter.next() {
-        let first = first?;
-        let _ = first.parse::<u64>().map_err(|e| {
-            anyhow!(
-                "First line of extra dictionary must a number, but is: >{}<",
-                first
-            )
-            .context(e)
-        })?;
-    }
-    // Just check the first 10 lines, don't waste much time here
-    // the first two are the most important ones.
-    for (lineno, line) in iter.take(10) {
-        // All lines after must be format x.
-        if let Ok(num) = line?.parse::<i64>() {
-            bail!(
-                "Line {} of extra dictionary must not be a number, but is: >{}<",
-                lineno,
-                num
-            )
-        };
+    fn suggest(&self, word: &str) -> Vec<String> {
+        Hunspell::suggest(self, word)
     }
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    #[test]
-    fn hunspell_dic_format() {
-        const GOOD: &str = "2
-whitespazes
-catsndogs
-";
-        const BAD_1: &str = "foo
-12349
-bar
-";
-        const BAD_2: &str = "2
-12349
-bar
-";
-        const BAD_3: &str = "foo
-xxx
-bar
-";
-        assert!(is_valid_hunspell_dic(&mut GOOD.as_bytes()).is_ok());
-        assert!(is_valid_hunspell_dic(&mut BAD_1.as_bytes()).is_err());
-        assert!(is_valid_hunspell_dic(&mut BAD_2.as_bytes()).is_err());
-        assert!(is_valid_hunspell_dic(&mut BAD_3.as_bytes()).is_err());
-    }
+    use fs_err as fs;
+    use std::io::{self, BufRead};
 
     #[test]
     fn hunspell_binding_is_sane() {
@@ -394,4 +250,20 @@ bar
             }
         }
     }
+
+    #[cfg(feature = "bundled-dictionary")]
+    #[test]
+    fn bundled_dictionary_is_sane() {
+        let (dic, aff) = extract_bundled().unwrap();
+        assert!(is_valid_hunspell_dic_path(&dic).is_ok());
+
+        let hunspell = Hunspell::new(
+            aff.display().to_string().as_str(),
+            dic.display().to_string().as_str(),
+        );
+        assert!(hunspell.check("iterator"));
+        assert!(hunspell.check("The"));
+        assert!(!hunspell.check("iteratr"));
+        assert!(hunspell.suggest("iteratr").contains(&"iterator".to_owned()));
+    }
 }
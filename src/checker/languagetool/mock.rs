@@ -0,0 +1,212 @@
+//! A stand-in for the `v2/check` endpoint of a languagetool server.
+//!
+//! Reports every match of the regular expressions of its canned rules, so the
+//! checker can be tested without a running languagetool instance. Responses
+//! can be made to fail or to be delayed, to cover the error handling.
+
+use regex::Regex;
+use serde_json::json;
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// A canned rule, every match of `pattern` is reported.
+pub(crate) struct MockRule {
+    id: &'static str,
+    category: &'static str,
+    pattern: Regex,
+    message: &'static str,
+    /// Replacement templates, which may refer to capture groups as `$1`.
+    replacements: Vec<&'static str>,
+}
+
+impl MockRule {
+    pub(crate) fn new(
+        id: &'static str,
+        category: &'static str,
+        pattern: &str,
+        message: &'static str,
+        replacements: &[&'static str],
+    ) -> Self {
+        Self {
+            id,
+            category,
+            pattern: Regex::new(pattern).expect("Mock rule patterns are valid. qed"),
+            message,
+            replacements: replacements.to_vec(),
+        }
+    }
+}
+
+/// Deviation from a regular response to a single request.
+#[derive(Debug, Clone)]
+pub(crate) enum Failure {
+    /// Respond with the status code and an optional `Retry-After` in seconds.
+    Status(u16, Option<u64>),
+    /// Respond regularly, but only after the delay.
+    Delay(Duration),
+}
+
+/// The state shared with the thread serving the requests.
+struct Shared {
+    rules: Vec<MockRule>,
+    /// Applied to the upcoming requests in order, afterwards all requests are
+    /// answered regularly.
+    failures: Mutex<VecDeque<Failure>>,
+    /// The form parameters of all received requests.
+    requests: Mutex<Vec<HashMap<String, String>>>,
+}
+
+/// A mock languagetool server listening on a random local port, stopped
+/// when dropped.
+pub(crate) struct MockServer {
+    server: Arc<tiny_http::Server>,
+    shared: Arc<Shared>,
+    url: url::Url,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl MockServer {
+    /// Start serving the `rules`, with the `failures` for the first requests.
+    pub(crate) fn start(rules: Vec<MockRule>, failures: Vec<Failure>) -> Self {
+        let server = Arc::new(
+            tiny_http::Server::http("127.0.0.1:0").expect("Binding to a local port works. qed"),
+        );
+        let addr = server
+            .server_addr()
+            .to_ip()
+            .expect("Listens on an ip address. qed");
+        let url = url::Url::parse(&format!("http://{}/", addr)).expect("Valid url. qed");
+        let shared = Arc::new(Shared {
+            rules,
+            failures: Mutex::new(failures.into()),
+            requests: Mutex::new(Vec::new()),
+        });
+        let handle = {
+            let server = Arc::clone(&server);
+            let shared = Arc::clone(&shared);
+            std::thread::spawn(move || {
+                for request in server.incoming_requests() {
+                    // delayed responses must not hold up subsequent requests
+                    let shared = Arc::clone(&shared);
+                    std::thread::spawn(move || shared.serve(request));
+                }
+            })
+        };
+        Self {
+            server,
+            shared,
+            url,
+            handle: Some(handle),
+        }
+    }
+
+    /// The base url of the server.
+    pub(crate) fn url(&self) -> &url::Url {
+        &self.url
+    }
+
+    /// The form parameters of all requests received so far.
+    pub(crate) fn requests(&self) -> Vec<HashMap<String, String>> {
+        self.shared.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.server.unblock();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Shared {
+    fn serve(&self, mut request: tiny_http::Request) {
+        let mut body = String::new();
+        if request.as_reader().read_to_string(&mut body).is_err() {
+            return;
+        }
+        let form = url::form_urlencoded::parse(body.as_bytes())
+            .into_owned()
+            .collect::<HashMap<_, _>>();
+        self.requests.lock().unwrap().push(form.clone());
+
+        if request.method() != &tiny_http::Method::Post || request.url() != "/v2/check" {
+            let _ = request.respond(tiny_http::Response::empty(404));
+            return;
+        }
+
+        let failure = self.failures.lock().unwrap().pop_front();
+        match failure {
+            Some(Failure::Status(status, retry_after)) => {
+                let mut response =
+                    tiny_http::Response::from_string("Mock failure").with_status_code(status);
+                if let Some(secs) = retry_after {
+                    response = response.with_header(
+                        tiny_http::Header::from_bytes("Retry-After", secs.to_string())
+                            .expect("Valid header. qed"),
+                    );
+                }
+                let _ = request.respond(response);
+                return;
+            }
+            Some(Failure::Delay(delay)) => std::thread::sleep(delay),
+            None => {}
+        }
+
+        let text = form.get("text").map(String::as_str).unwrap_or_default();
+        let list = |key: &str| {
+            form.get(key)
+                .map(|ids| ids.split(',').collect::<Vec<_>>())
+                .unwrap_or_default()
+        };
+        let disabled_rules = list("disabledRules");
+        let disabled_categories = list("disabledCategories");
+        let body = json!({
+            "software": { "name": "LanguageTool mock" },
+            "matches": self.matches(text, |rule| {
+                !disabled_rules.contains(&rule.id) && !disabled_categories.contains(&rule.category)
+            }),
+        });
+        let response = tiny_http::Response::from_string(body.to_string()).with_header(
+            tiny_http::Header::from_bytes("Content-Type", "application/json")
+                .expect("Valid header. qed"),
+        );
+        let _ = request.respond(response);
+    }
+
+    /// All matches of the enabled rules in `text`, with offsets in UTF-16
+    /// code units, like languagetool.
+    fn matches(&self, text: &str, enabled: impl Fn(&MockRule) -> bool) -> Vec<serde_json::Value> {
+        let utf16_len = |s: &str| s.chars().map(char::len_utf16).sum::<usize>();
+        let mut matches = Vec::new();
+        for rule in self.rules.iter().filter(|rule| enabled(rule)) {
+            for captures in rule.pattern.captures_iter(text) {
+                let whole = captures.get(0).expect("Group 0 is the whole match. qed");
+                let replacements = rule
+                    .replacements
+                    .iter()
+                    .map(|template| {
+                        let mut replacement = String::new();
+                        captures.expand(template, &mut replacement);
+                        json!({ "value": replacement })
+                    })
+                    .collect::<Vec<_>>();
+                matches.push(json!({
+                    "message": rule.message,
+                    "offset": utf16_len(&text[..whole.start()]),
+                    "length": utf16_len(whole.as_str()),
+                    "replacements": replacements,
+                    "rule": {
+                        "id": rule.id,
+                        "category": { "id": rule.category },
+                    },
+                }));
+            }
+        }
+        matches
+    }
+}
@@ -0,0 +1,341 @@
+//! Check spelling and grammar by utilizing a languagetool server
+//!
+//! Can either be local or hosted. Chunks are batched into as few requests as
+//! the configured maximum length allows, requests are sent no faster than the
+//! configured rate and retried with an exponential backoff on temporary
+//! failures.
+
+use super::*;
+
+use crate::config::LanguageToolConfig;
+use crate::documentation::PlainOverlay;
+use crate::{RuleId, Severity};
+
+use anyhow::{anyhow, bail};
+use log::trace;
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Separates the chunks within a batch, so no match spans two of them.
+const SEPARATOR: &str = "\n\n";
+
+pub struct LanguageToolChecker {
+    config: LanguageToolConfig,
+    check_url: url::Url,
+    agent: ureq::Agent,
+    /// Time of the most recent request, to adhere to the rate limit.
+    last_request: Mutex<Option<Instant>>,
+}
+
+/// The relevant subset of a `v2/check` response.
+#[derive(Debug, Deserialize)]
+struct CheckResponse {
+    #[serde(default)]
+    matches: Vec<Match>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Match {
+    message: String,
+    /// Offset in UTF-16 code units.
+    offset: usize,
+    /// Length in UTF-16 code units.
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+    rule: Option<Rule>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Replacement {
+    value: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rule {
+    id: String,
+    category: Option<Category>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Category {
+    id: String,
+}
+
+/// A chunk as part of a batch.
+struct Part<'a> {
+    chunk: &'a CheckableChunk,
+    plain: PlainOverlay<'a>,
+    /// Offset of the plain text within the batch text in UTF-16 code units.
+    offset: usize,
+    /// Length of the plain text in UTF-16 code units.
+    length: usize,
+}
+
+/// Chunks which are checked with a single request.
+struct Batch<'a> {
+    text: String,
+    parts: Vec<Part<'a>>,
+}
+
+impl<'a> Batch<'a> {
+    fn new() -> Self {
+        Self {
+            text: String::new(),
+            parts: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, chunk: &'a CheckableChunk, plain: PlainOverlay<'a>) {
+        if !self.parts.is_empty() {
+            self.text.push_str(SEPARATOR);
+        }
+        let offset = utf16_len(&self.text);
+        self.text.push_str(plain.as_str());
+        self.parts.push(Part {
+            chunk,
+            offset,
+            length: utf16_len(plain.as_str()),
+            plain,
+        });
+    }
+
+    /// The part containing the UTF-16 range `offset..offset+length` of the
+    /// batch text, and the range relative to the part in characters.
+    fn locate(&self, offset: usize, length: usize) -> Option<(&Part<'a>, Range)> {
+        let part = self
+            .parts
+            .iter()
+            .find(|part| part.offset <= offset && offset + length <= part.offset + part.length)?;
+        let s = part.plain.as_str();
+        let start = utf16_to_char_offset(s, offset - part.offset);
+        let end = utf16_to_char_offset(s, offset - part.offset + length);
+        Some((part, start..end))
+    }
+}
+
+/// Split the chunks into batches of at most `max_length` characters, a chunk
+/// exceeding the limit on its own forms a batch of its own.
+fn batches(chunks: &[CheckableChunk], max_length: usize) -> Vec<Batch<'_>> {
+    let mut batches = Vec::new();
+    let mut batch = Batch::new();
+    let mut length = 0;
+    for chunk in chunks {
+        let plain = chunk.erase_cmark();
+        trace!("markdown erasure: {:?}", &plain);
+        let chunk_length = plain.as_str().chars().count();
+        if chunk_length == 0 {
+            continue;
+        }
+        if !batch.parts.is_empty() {
+            if length + SEPARATOR.len() + chunk_length > max_length {
+                batches.push(std::mem::replace(&mut batch, Batch::new()));
+                length = 0;
+            } else {
+                length += SEPARATOR.len();
+            }
+        }
+        length += chunk_length;
+        batch.push(chunk, plain);
+    }
+    if !batch.parts.is_empty() {
+        batches.push(batch);
+    }
+    batches
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.chars().map(char::len_utf16).sum()
+}
+
+/// Convert an offset in UTF-16 code units, as used by languagetool, to an
+/// offset in characters.
+fn utf16_to_char_offset(s: &str, utf16_offset: usize) -> usize {
+    let mut acc = 0;
+    s.chars()
+        .take_while(|c| {
+            acc += c.len_utf16();
+            acc <= utf16_offset
+        })
+        .count()
+}
+
+impl LanguageToolChecker {
+    /// Block until sending another request adheres to the rate limit.
+    fn throttle(&self) {
+        let interval = if let Some(interval) = self.config.request_interval() {
+            interval
+        } else {
+            return;
+        };
+        let mut last_request = self
+            .last_request
+            .lock()
+            .expect("Lock is never held while panicking. qed");
+        if let Some(last) = *last_request {
+            let next = last + interval;
+            let now = Instant::now();
+            if next > now {
+                std::thread::sleep(next - now);
+            }
+        }
+        *last_request = Some(Instant::now());
+    }
+
+    /// Send the text of a batch, retries on network errors and on responses
+    /// hinting at a temporary failure.
+    fn request(&self, text: &str) -> Result<CheckResponse> {
+        let config = &self.config;
+        let disabled_rules = config.effective_disabled_rules().join(",");
+        let enabled_rules = config.enabled_rules.join(",");
+        let enabled_categories = config.enabled_categories.join(",");
+        let disabled_categories = config.disabled_categories.join(",");
+
+        let mut form = vec![("text", text), ("language", config.language.as_str())];
+        if let Some(mother_tongue) = &config.mother_tongue {
+            form.push(("motherTongue", mother_tongue.as_str()));
+        }
+        for (key, value) in [
+            ("enabledRules", &enabled_rules),
+            ("disabledRules", &disabled_rules),
+            ("enabledCategories", &enabled_categories),
+            ("disabledCategories", &disabled_categories),
+        ]
+        .iter()
+        {
+            if !value.is_empty() {
+                form.push((key, value.as_str()));
+            }
+        }
+        if config.enabled_only {
+            form.push(("enabledOnly", "true"));
+        }
+        if config.picky {
+            form.push(("level", "picky"));
+        }
+
+        let mut backoff = Duration::from_millis(config.backoff_ms);
+        let mut attempt = 0;
+        loop {
+            self.throttle();
+            let retry_after = match self.agent.post(self.check_url.as_str()).send_form(&form) {
+                Ok(response) => {
+                    return response.into_json::<CheckResponse>().map_err(|e| {
+                        anyhow!("Failed to parse response of {}", self.check_url).context(e)
+                    });
+                }
+                Err(ureq::Error::Status(status, response)) if status == 429 || status >= 500 => {
+                    log::debug!("Request failed with status {}", status);
+                    response
+                        .header("Retry-After")
+                        .and_then(|secs| secs.trim().parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                }
+                Err(ureq::Error::Status(status, response)) => {
+                    bail!(
+                        "Request to {} failed with status {}: {}",
+                        self.check_url,
+                        status,
+                        response.into_string().unwrap_or_default()
+                    );
+                }
+                Err(ureq::Error::Transport(e)) if attempt < config.retries => {
+                    log::debug!("Request failed: {}", e);
+                    None
+                }
+                Err(e) => {
+                    return Err(anyhow!("Request to {} failed", self.check_url).context(e));
+                }
+            };
+            if attempt >= config.retries {
+                bail!(
+                    "Request to {} failed after {} retries",
+                    self.check_url,
+                    attempt
+                );
+            }
+            attempt += 1;
+            let delay = retry_after.map_or(backoff, |retry_after| retry_after.max(backoff));
+            log::debug!("Retrying in {:?}, attempt {}", delay, attempt);
+            std::thread::sleep(delay);
+            backoff *= 2;
+        }
+    }
+}
+
+impl Checker for LanguageToolChecker {
+    type Config = LanguageToolConfig;
+
+    fn detector() -> Detector {
+        Detector::LanguageTool
+    }
+
+    fn new(config: &Self::Config) -> Result<Self> {
+        let check_url = config
+            .check_url()
+            .map_err(|e| anyhow!("Invalid languagetool url {}", config.url).context(e))?;
+        Ok(Self {
+            config: config.clone(),
+            check_url,
+            agent: ureq::AgentBuilder::new()
+                .timeout(Duration::from_millis(config.timeout_ms))
+                .build(),
+            last_request: Mutex::new(None),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut acc = Vec::with_capacity(chunks.len());
+        for batch in batches(chunks, self.config.max_batch_length) {
+            let resp = self.request(&batch.text)?;
+            for item in resp.matches {
+                trace!("item: {:?}", item);
+                let (part, range) = if let Some(located) = batch.locate(item.offset, item.length) {
+                    located
+                } else {
+                    log::debug!("Dropping match spanning multiple chunks: {:?}", item);
+                    continue;
+                };
+                let rule = item.rule.map(|rule| RuleId {
+                    category: rule
+                        .category
+                        .map(|category| category.id)
+                        .unwrap_or_default(),
+                    id: rule.id,
+                });
+                for (range, span) in part.plain.find_spans(range) {
+                    acc.push(Suggestion {
+                        detector: Detector::LanguageTool,
+                        range,
+                        span,
+                        origin: origin.clone(),
+                        replacements: item
+                            .replacements
+                            .iter()
+                            .filter_map(|x| x.value.clone())
+                            .collect(),
+                        chunk: part.chunk,
+                        description: Some(item.message.clone()),
+                        rule: rule.clone(),
+                        severity: Severity::Error,
+                    });
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod mock;
+
+#[cfg(test)]
+mod tests;
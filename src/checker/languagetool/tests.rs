@@ -0,0 +1,261 @@
+use super::mock::{Failure, MockRule, MockServer};
+use super::*;
+use crate::util::sub_chars;
+use crate::{CommentVariant, Documentation, LineColumn, Span};
+use indexmap::IndexMap;
+
+fn chunk(s: &str) -> CheckableChunk {
+    CheckableChunk::from_str(
+        s,
+        IndexMap::<Range, Span>::new(),
+        CommentVariant::CommonMark,
+    )
+}
+
+#[test]
+fn utf16_offsets() {
+    assert_eq!(utf16_to_char_offset("abc", 2), 2);
+    // the emoji is a surrogate pair
+    assert_eq!(utf16_to_char_offset("a🐱 b", 3), 2);
+    assert_eq!(utf16_to_char_offset("a🐱 b", 5), 4);
+}
+
+#[test]
+fn batching() {
+    let chunks = vec![chunk("Alpha beta."), chunk("🐱 gamma."), chunk("Delta.")];
+    let batches = batches(&chunks, 21);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].text, "Alpha beta.\n\n🐱 gamma.");
+    assert_eq!(batches[1].text, "Delta.");
+
+    let batch = &batches[0];
+    // `gamma` behind the emoji, which is two UTF-16 code units
+    let (part, range) = batch.locate(16, 5).expect("Within the second chunk. qed");
+    assert!(std::ptr::eq(part.chunk, &chunks[1]));
+    assert_eq!(range, 2..7);
+    // spans the separator
+    assert!(batch.locate(10, 4).is_none());
+}
+
+#[test]
+fn oversized_chunk() {
+    let chunks = vec![chunk("Alpha beta."), chunk("Gamma delta.")];
+    let batches = batches(&chunks, 5);
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[1].text, "Gamma delta.");
+}
+
+fn rules() -> Vec<MockRule> {
+    vec![
+        MockRule::new("TEH", "TYPOS", r"\bteh\b", "Possible typo", &["the"]),
+        MockRule::new(
+            "ENGLISH_WORD_REPEAT_RULE",
+            "MISC",
+            r"(?i)\b(the)\s+the\b",
+            "Possible typo: you repeated a word",
+            &["$1"],
+        ),
+        MockRule::new("EN_QUOTES", "TYPOGRAPHY", "\"", "Use a smart quote", &["“"]),
+    ]
+}
+
+fn mock_config(server: &MockServer) -> LanguageToolConfig {
+    LanguageToolConfig {
+        backoff_ms: 1,
+        ..LanguageToolConfig::from_url(server.url().clone())
+    }
+}
+
+/// Check the rust `source` with the server and return the flagged content
+/// along with the span of each suggestion.
+fn check(config: &LanguageToolConfig, source: &str) -> Result<Vec<(String, Span)>> {
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, source, false);
+    let (origin, chunks) = docs.iter().next().expect("Contains doc comments. qed");
+    let checker = LanguageToolChecker::new(config)?;
+    let suggestions = checker.check(origin, chunks)?;
+    Ok(suggestions
+        .iter()
+        .map(|suggestion| {
+            assert_eq!(suggestion.detector, Detector::LanguageTool);
+            (
+                sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                suggestion.span,
+            )
+        })
+        .collect())
+}
+
+fn span(line: usize, start: usize, end: usize) -> Span {
+    Span {
+        start: LineColumn {
+            line,
+            column: start,
+        },
+        end: LineColumn { line, column: end },
+    }
+}
+
+#[test]
+fn offsets_behind_wide_chars() {
+    let server = MockServer::start(rules(), vec![]);
+    let flagged = check(
+        &mock_config(&server),
+        "/// A 🐱 and teh \"dog\".\nstruct X;",
+    )
+    .unwrap();
+    assert_eq!(flagged, vec![("teh".to_owned(), span(1, 12, 14))]);
+}
+
+#[test]
+fn multi_line_span() {
+    let server = MockServer::start(rules(), vec![]);
+    let flagged = check(
+        &mock_config(&server),
+        "/// Everything in the\n/// the documentation.\nstruct X;",
+    )
+    .unwrap();
+    // one span for each line of the match
+    assert_eq!(
+        flagged,
+        vec![
+            ("the".to_owned(), span(1, 18, 20)),
+            ("the".to_owned(), span(2, 4, 6)),
+        ]
+    );
+}
+
+#[test]
+fn batched_chunks() {
+    let server = MockServer::start(rules(), vec![]);
+    let flagged = check(
+        &mock_config(&server),
+        r#"
+/// Fine.
+struct X;
+
+/// Not teh best.
+struct Y;
+
+/// Again teh same.
+struct Z;
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        flagged,
+        vec![
+            ("teh".to_owned(), span(5, 8, 10)),
+            ("teh".to_owned(), span(8, 10, 12)),
+        ]
+    );
+    assert_eq!(server.requests().len(), 1);
+
+    let server = MockServer::start(rules(), vec![]);
+    let config = LanguageToolConfig {
+        max_batch_length: 20,
+        ..mock_config(&server)
+    };
+    let flagged = check(
+        &config,
+        "/// Not teh best.\nstruct Y;\n\n/// Again teh same.\nstruct Z;",
+    )
+    .unwrap();
+    assert_eq!(flagged.len(), 2);
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn request_parameters() {
+    let server = MockServer::start(rules(), vec![]);
+    let config = LanguageToolConfig {
+        language: "en-GB".to_owned(),
+        mother_tongue: Some("de-DE".to_owned()),
+        disabled_rules: vec!["TEH".to_owned()],
+        disabled_categories: vec!["MISC".to_owned()],
+        picky: true,
+        ..mock_config(&server)
+    };
+    let flagged = check(&config, "/// Quote \"teh\".\nstruct X;").unwrap();
+    assert!(flagged.is_empty());
+
+    let requests = server.requests();
+    let form = &requests[0];
+    assert_eq!(form["text"], "Quote \"teh\".");
+    assert_eq!(form["language"], "en-GB");
+    assert_eq!(form["motherTongue"], "de-DE");
+    assert_eq!(form["disabledRules"], "EN_QUOTES,TEH");
+    assert_eq!(form["disabledCategories"], "MISC");
+    assert_eq!(form["level"], "picky");
+    assert!(!form.contains_key("enabledOnly"));
+}
+
+#[test]
+fn retry_server_errors() {
+    let server = MockServer::start(
+        rules(),
+        vec![Failure::Status(503, None), Failure::Status(429, Some(0))],
+    );
+    let flagged = check(&mock_config(&server), "/// Not teh best.\nstruct X;").unwrap();
+    assert_eq!(flagged.len(), 1);
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn retries_exhausted() {
+    let server = MockServer::start(
+        rules(),
+        vec![Failure::Status(500, None), Failure::Status(502, None)],
+    );
+    let config = LanguageToolConfig {
+        retries: 1,
+        ..mock_config(&server)
+    };
+    assert!(check(&config, "/// Not teh best.\nstruct X;").is_err());
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn client_errors_are_not_retried() {
+    let server = MockServer::start(rules(), vec![Failure::Status(400, None)]);
+    assert!(check(&mock_config(&server), "/// Not teh best.\nstruct X;").is_err());
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn timeout() {
+    let delay = Failure::Delay(Duration::from_millis(500));
+    let server = MockServer::start(rules(), vec![delay.clone()]);
+    let config = LanguageToolConfig {
+        timeout_ms: 50,
+        retries: 0,
+        ..mock_config(&server)
+    };
+    assert!(check(&config, "/// Not teh best.\nstruct X;").is_err());
+
+    let server = MockServer::start(rules(), vec![delay]);
+    let config = LanguageToolConfig {
+        timeout_ms: 50,
+        retries: 1,
+        ..mock_config(&server)
+    };
+    let flagged = check(&config, "/// Not teh best.\nstruct X;").unwrap();
+    assert_eq!(flagged.len(), 1);
+}
+
+#[test]
+fn rate_limit() {
+    let server = MockServer::start(rules(), vec![]);
+    let config = LanguageToolConfig {
+        max_batch_length: 1,
+        requests_per_second: Some(20.),
+        ..mock_config(&server)
+    };
+    let start = Instant::now();
+    check(
+        &config,
+        "/// A.\nstruct X;\n/// B.\nstruct Y;\n/// C.\nstruct Z;",
+    )
+    .unwrap();
+    assert_eq!(server.requests().len(), 3);
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
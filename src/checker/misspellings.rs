@@ -0,0 +1,424 @@
+//! Common misspellings of English words and their corrections, in the spirit
+//! of the list bundled with `codespell`.
+
+/// Sorted by the misspelling, which is all lowercase.
+pub(super) const MISSPELLINGS: &[(&str, &str)] = &[
+    ("abandonned", "abandoned"),
+    ("aberation", "aberration"),
+    ("abilty", "ability"),
+    ("abondon", "abandon"),
+    ("abscence", "absence"),
+    ("absense", "absence"),
+    ("absolutly", "absolutely"),
+    ("acceptible", "acceptable"),
+    ("accesible", "accessible"),
+    ("accessable", "accessible"),
+    ("accidentaly", "accidentally"),
+    ("accomodate", "accommodate"),
+    ("accomodation", "accommodation"),
+    ("accompanyed", "accompanied"),
+    ("accross", "across"),
+    ("acheive", "achieve"),
+    ("acheived", "achieved"),
+    ("acknowlege", "acknowledge"),
+    ("acording", "according"),
+    ("acquaintence", "acquaintance"),
+    ("acquiantance", "acquaintance"),
+    ("actualy", "actually"),
+    ("adddress", "address"),
+    ("additionaly", "additionally"),
+    ("addres", "address"),
+    ("addresss", "address"),
+    ("adress", "address"),
+    ("adressed", "addressed"),
+    ("adresses", "addresses"),
+    ("agressive", "aggressive"),
+    ("alege", "allege"),
+    ("algorithim", "algorithm"),
+    ("algoritm", "algorithm"),
+    ("alignement", "alignment"),
+    ("allign", "align"),
+    ("alligned", "aligned"),
+    ("allready", "already"),
+    ("allways", "always"),
+    ("alot", "a lot"),
+    ("alreay", "already"),
+    ("alredy", "already"),
+    ("alwasy", "always"),
+    ("amature", "amateur"),
+    ("ammount", "amount"),
+    ("analagous", "analogous"),
+    ("anbd", "and"),
+    ("anwser", "answer"),
+    ("apparant", "apparent"),
+    ("apparantly", "apparently"),
+    ("appearence", "appearance"),
+    ("applicaiton", "application"),
+    ("appropiate", "appropriate"),
+    ("aproximate", "approximate"),
+    ("aquire", "acquire"),
+    ("aquired", "acquired"),
+    ("arbitary", "arbitrary"),
+    ("arbitrarly", "arbitrarily"),
+    ("archtecture", "architecture"),
+    ("arguement", "argument"),
+    ("arguements", "arguments"),
+    ("arround", "around"),
+    ("artifical", "artificial"),
+    ("asign", "assign"),
+    ("assertation", "assertion"),
+    ("asssert", "assert"),
+    ("asume", "assume"),
+    ("asynchronus", "asynchronous"),
+    ("atleast", "at least"),
+    ("atribute", "attribute"),
+    ("atributes", "attributes"),
+    ("attemp", "attempt"),
+    ("attemps", "attempts"),
+    ("auxilary", "auxiliary"),
+    ("availabe", "available"),
+    ("availabilty", "availability"),
+    ("availble", "available"),
+    ("avaliable", "available"),
+    ("backwords", "backwards"),
+    ("basicly", "basically"),
+    ("becasue", "because"),
+    ("becaues", "because"),
+    ("beeing", "being"),
+    ("befor", "before"),
+    ("beggining", "beginning"),
+    ("begining", "beginning"),
+    ("beleive", "believe"),
+    ("belive", "believe"),
+    ("benificial", "beneficial"),
+    ("bettter", "better"),
+    ("boundry", "boundary"),
+    ("brakpoint", "breakpoint"),
+    ("buisness", "business"),
+    ("calender", "calendar"),
+    ("cancelation", "cancellation"),
+    ("capabilty", "capability"),
+    ("catagory", "category"),
+    ("certian", "certain"),
+    ("chaning", "changing"),
+    ("charachter", "character"),
+    ("charater", "character"),
+    ("charecter", "character"),
+    ("choosen", "chosen"),
+    ("chracter", "character"),
+    ("cleint", "client"),
+    ("coeficient", "coefficient"),
+    ("collegue", "colleague"),
+    ("comand", "command"),
+    ("comming", "coming"),
+    ("commited", "committed"),
+    ("commiting", "committing"),
+    ("committment", "commitment"),
+    ("comparision", "comparison"),
+    ("compatability", "compatibility"),
+    ("compatable", "compatible"),
+    ("compatiblity", "compatibility"),
+    ("competance", "competence"),
+    ("completly", "completely"),
+    ("comsume", "consume"),
+    ("concious", "conscious"),
+    ("condidtion", "condition"),
+    ("conditon", "condition"),
+    ("configuation", "configuration"),
+    ("configuraiton", "configuration"),
+    ("connecion", "connection"),
+    ("conscencious", "conscientious"),
+    ("consistant", "consistent"),
+    ("constatn", "constant"),
+    ("containg", "containing"),
+    ("contigious", "contiguous"),
+    ("continous", "continuous"),
+    ("controll", "control"),
+    ("contruct", "construct"),
+    ("convertion", "conversion"),
+    ("convienient", "convenient"),
+    ("corectly", "correctly"),
+    ("corrct", "correct"),
+    ("correspondance", "correspondence"),
+    ("coudl", "could"),
+    ("critereon", "criterion"),
+    ("curent", "current"),
+    ("currenly", "currently"),
+    ("dafault", "default"),
+    ("deafult", "default"),
+    ("decleration", "declaration"),
+    ("defaut", "default"),
+    ("defered", "deferred"),
+    ("definately", "definitely"),
+    ("definitly", "definitely"),
+    ("defintion", "definition"),
+    ("delimeter", "delimiter"),
+    ("dependancy", "dependency"),
+    ("dependecy", "dependency"),
+    ("depricated", "deprecated"),
+    ("derefence", "dereference"),
+    ("desireable", "desirable"),
+    ("destory", "destroy"),
+    ("detatch", "detach"),
+    ("determin", "determine"),
+    ("develeoper", "developer"),
+    ("develope", "develop"),
+    ("developement", "development"),
+    ("diffrent", "different"),
+    ("dimention", "dimension"),
+    ("directoy", "directory"),
+    ("disapear", "disappear"),
+    ("disapointed", "disappointed"),
+    ("discription", "description"),
+    ("dissapear", "disappear"),
+    ("documantation", "documentation"),
+    ("documenation", "documentation"),
+    ("doesnt", "doesn't"),
+    ("dont", "don't"),
+    ("drammatic", "dramatic"),
+    ("durring", "during"),
+    ("easilly", "easily"),
+    ("effeciency", "efficiency"),
+    ("effecient", "efficient"),
+    ("efficency", "efficiency"),
+    ("eigth", "eighth"),
+    ("elemnt", "element"),
+    ("embarass", "embarrass"),
+    ("enviroment", "environment"),
+    ("environement", "environment"),
+    ("equivalant", "equivalent"),
+    ("equivelent", "equivalent"),
+    ("eror", "error"),
+    ("errorneous", "erroneous"),
+    ("exagerate", "exaggerate"),
+    ("excecute", "execute"),
+    ("exceded", "exceeded"),
+    ("excelent", "excellent"),
+    ("existance", "existence"),
+    ("existant", "existent"),
+    ("exmaple", "example"),
+    ("expecially", "especially"),
+    ("experiance", "experience"),
+    ("explicitely", "explicitly"),
+    ("explicitily", "explicitly"),
+    ("expresion", "expression"),
+    ("extention", "extension"),
+    ("familar", "familiar"),
+    ("fasion", "fashion"),
+    ("feasable", "feasible"),
+    ("finaly", "finally"),
+    ("follwing", "following"),
+    ("folowing", "following"),
+    ("foward", "forward"),
+    ("fucntion", "function"),
+    ("fuction", "function"),
+    ("funcion", "function"),
+    ("functionaility", "functionality"),
+    ("futher", "further"),
+    ("garantee", "guarantee"),
+    ("gaurantee", "guarantee"),
+    ("generaly", "generally"),
+    ("goverment", "government"),
+    ("gramatically", "grammatically"),
+    ("grammer", "grammar"),
+    ("guarentee", "guarantee"),
+    ("guidence", "guidance"),
+    ("happend", "happened"),
+    ("harrass", "harass"),
+    ("hieght", "height"),
+    ("hierachy", "hierarchy"),
+    ("hte", "the"),
+    ("identifer", "identifier"),
+    ("ignorning", "ignoring"),
+    ("immediatly", "immediately"),
+    ("implemantation", "implementation"),
+    ("implementaion", "implementation"),
+    ("implemention", "implementation"),
+    ("implict", "implicit"),
+    ("incldue", "include"),
+    ("incompatable", "incompatible"),
+    ("inconsistant", "inconsistent"),
+    ("indentical", "identical"),
+    ("independant", "independent"),
+    ("indicies", "indices"),
+    ("infomation", "information"),
+    ("informaton", "information"),
+    ("inital", "initial"),
+    ("initalize", "initialize"),
+    ("initialse", "initialise"),
+    ("instace", "instance"),
+    ("instaed", "instead"),
+    ("intead", "instead"),
+    ("interupt", "interrupt"),
+    ("intial", "initial"),
+    ("intialize", "initialize"),
+    ("invaild", "invalid"),
+    ("irrelavant", "irrelevant"),
+    ("isntance", "instance"),
+    ("iterater", "iterator"),
+    ("itslef", "itself"),
+    ("knowlege", "knowledge"),
+    ("langauge", "language"),
+    ("lenght", "length"),
+    ("libary", "library"),
+    ("lightweigth", "lightweight"),
+    ("likelyhood", "likelihood"),
+    ("maintainance", "maintenance"),
+    ("maintenence", "maintenance"),
+    ("managment", "management"),
+    ("manualy", "manually"),
+    ("mathmatical", "mathematical"),
+    ("maximium", "maximum"),
+    ("meaningfull", "meaningful"),
+    ("mesage", "message"),
+    ("messsage", "message"),
+    ("milennium", "millennium"),
+    ("minumum", "minimum"),
+    ("mispell", "misspell"),
+    ("mispelled", "misspelled"),
+    ("mispelling", "misspelling"),
+    ("missmatch", "mismatch"),
+    ("modfiy", "modify"),
+    ("modifed", "modified"),
+    ("mulitple", "multiple"),
+    ("multipe", "multiple"),
+    ("mutiple", "multiple"),
+    ("neccessary", "necessary"),
+    ("necesary", "necessary"),
+    ("nessecary", "necessary"),
+    ("noticable", "noticeable"),
+    ("occassion", "occasion"),
+    ("occurance", "occurrence"),
+    ("occured", "occurred"),
+    ("occurence", "occurrence"),
+    ("occuring", "occurring"),
+    ("offical", "official"),
+    ("ommited", "omitted"),
+    ("ommiting", "omitting"),
+    ("ommitted", "omitted"),
+    ("oppurtunity", "opportunity"),
+    ("optimisaton", "optimisation"),
+    ("optinal", "optional"),
+    ("orignal", "original"),
+    ("otehr", "other"),
+    ("overriden", "overridden"),
+    ("paramater", "parameter"),
+    ("parameteres", "parameters"),
+    ("paramter", "parameter"),
+    ("parrallel", "parallel"),
+    ("particulary", "particularly"),
+    ("peformance", "performance"),
+    ("perfomance", "performance"),
+    ("permanant", "permanent"),
+    ("persistant", "persistent"),
+    ("plattform", "platform"),
+    ("posession", "possession"),
+    ("possibilty", "possibility"),
+    ("potentialy", "potentially"),
+    ("preceeding", "preceding"),
+    ("precendence", "precedence"),
+    ("prefered", "preferred"),
+    ("preferrably", "preferably"),
+    ("prescence", "presence"),
+    ("previosly", "previously"),
+    ("primative", "primitive"),
+    ("priviledge", "privilege"),
+    ("privilige", "privilege"),
+    ("probabilty", "probability"),
+    ("probaly", "probably"),
+    ("proccess", "process"),
+    ("proceedure", "procedure"),
+    ("programatically", "programmatically"),
+    ("propogate", "propagate"),
+    ("publically", "publicly"),
+    ("pursuade", "persuade"),
+    ("quering", "querying"),
+    ("readible", "readable"),
+    ("realy", "really"),
+    ("recieve", "receive"),
+    ("recieved", "received"),
+    ("reciever", "receiver"),
+    ("recomend", "recommend"),
+    ("recursivly", "recursively"),
+    ("redundent", "redundant"),
+    ("refered", "referred"),
+    ("refering", "referring"),
+    ("referrence", "reference"),
+    ("relevent", "relevant"),
+    ("remaing", "remaining"),
+    ("repetion", "repetition"),
+    ("replacment", "replacement"),
+    ("reponse", "response"),
+    ("represenation", "representation"),
+    ("requried", "required"),
+    ("resouce", "resource"),
+    ("responsability", "responsibility"),
+    ("retreive", "retrieve"),
+    ("retrive", "retrieve"),
+    ("returing", "returning"),
+    ("seperate", "separate"),
+    ("seperated", "separated"),
+    ("seperately", "separately"),
+    ("seperator", "separator"),
+    ("sequencial", "sequential"),
+    ("shoudl", "should"),
+    ("sigature", "signature"),
+    ("similiar", "similar"),
+    ("simpliest", "simplest"),
+    ("sincerly", "sincerely"),
+    ("somthing", "something"),
+    ("specifed", "specified"),
+    ("specifiy", "specify"),
+    ("speficied", "specified"),
+    ("statment", "statement"),
+    ("strenght", "strength"),
+    ("stucture", "structure"),
+    ("succesful", "successful"),
+    ("succesfully", "successfully"),
+    ("successfull", "successful"),
+    ("sucess", "success"),
+    ("sucessful", "successful"),
+    ("suffcient", "sufficient"),
+    ("supress", "suppress"),
+    ("suprise", "surprise"),
+    ("surpress", "suppress"),
+    ("synchonous", "synchronous"),
+    ("syncronous", "synchronous"),
+    ("sytem", "system"),
+    ("taht", "that"),
+    ("teh", "the"),
+    ("tempory", "temporary"),
+    ("tendancy", "tendency"),
+    ("therefor", "therefore"),
+    ("thier", "their"),
+    ("threshhold", "threshold"),
+    ("throught", "through"),
+    ("tommorow", "tomorrow"),
+    ("tranform", "transform"),
+    ("transfered", "transferred"),
+    ("truely", "truly"),
+    ("twelth", "twelfth"),
+    ("udpate", "update"),
+    ("unecessary", "unnecessary"),
+    ("unfortunatly", "unfortunately"),
+    ("uniqe", "unique"),
+    ("unkown", "unknown"),
+    ("unneccessary", "unnecessary"),
+    ("untill", "until"),
+    ("upadte", "update"),
+    ("usefull", "useful"),
+    ("usualy", "usually"),
+    ("utilites", "utilities"),
+    ("varaible", "variable"),
+    ("vaule", "value"),
+    ("verison", "version"),
+    ("visable", "visible"),
+    ("whcih", "which"),
+    ("wheter", "whether"),
+    ("wich", "which"),
+    ("wierd", "weird"),
+    ("wihch", "which"),
+    ("wiht", "with"),
+    ("wnat", "want"),
+    ("writting", "writing"),
+];
@@ -4,12 +4,16 @@
 //! Contains also helpers to avoid re-implementing generic
 //! algorithms again and again, i.e. tokenization.
 
-use crate::{Config, Detector, Documentation, Suggestion, SuggestionSet};
+use crate::config::VariantCheckers;
+use crate::documentation::{CheckableChunk, PlainOverlay};
+use crate::{Config, ContentOrigin, Detector, Documentation, Suggestion, SuggestionSet};
 
 use anyhow::Result;
 
+use crate::util::sub_chars;
 use crate::Range;
 use log::debug;
+use rayon::prelude::*;
 
 #[cfg(feature = "hunspell")]
 mod hunspell;
@@ -19,16 +23,35 @@ mod languagetool;
 #[cfg(feature = "nlprules")]
 mod nlprules;
 
-#[cfg(any(feature = "languagetool", feature = "hunspell"))]
 mod quirks;
 
+mod custom_rules;
+mod dictionary;
+mod misspellings;
+mod native_hunspell;
+mod rustdoc;
+mod terminology;
+pub(crate) mod typos;
+
+pub(crate) mod suppress;
+
 /// Implementation for a checker
 pub(crate) trait Checker {
     type Config;
 
     fn detector() -> Detector;
 
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    /// Create a new instance, loads all required resources upfront.
+    fn new(config: &Self::Config) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Check all `chunks`, which must originate from `origin`.
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
     where
         'a: 's;
 }
@@ -82,77 +105,299 @@ fn tokenize(s: &str) -> Vec<Range> {
     bananasplit
 }
 
-fn invoke_checker_inner<'a, 's, T>(
+/// Check if `range` of the plain text is contiguous in the chunk, and not
+/// made up of words around erased content, i.e. inline code.
+fn is_contiguous(plain: &PlainOverlay, chunk: &CheckableChunk, range: Range) -> bool {
+    let ranges = plain
+        .find_spans(range)
+        .into_iter()
+        .map(|(range, _span)| range)
+        .collect::<Vec<_>>();
+    !ranges.is_empty()
+        && ranges.windows(2).all(|pair| {
+            sub_chars(chunk.as_str(), pair[0].end..pair[1].start)
+                .chars()
+                .all(char::is_whitespace)
+        })
+}
+
+/// Run a single checker on all chunks of the documentation, which are
+/// selected for it by their comment variant.
+pub(crate) fn check_with<'a, 's, T>(
+    checker: &T,
     documentation: &'a Documentation,
-    config: Option<&T::Config>,
-    collective: &mut SuggestionSet<'s>,
-) -> Result<()>
+    selection: &VariantCheckers,
+) -> Result<SuggestionSet<'s>>
 where
     'a: 's,
-    T: Checker,
+    T: Checker + Sync,
 {
-    let config = config
-        .as_ref()
-        .expect("Must be Some(Config) if is_enabled returns true");
-
-    let suggestions = T::check(documentation, *config)?;
-    collective.join(suggestions);
-    Ok(())
+    let mut suggestions = documentation
+        .par_iter()
+        .try_fold::<SuggestionSet, Result<_>, _, _>(
+            || SuggestionSet::new(),
+            |mut acc, (origin, chunks)| {
+                debug!("Processing {}", origin.as_path().display());
+                let mut collective = Vec::new();
+                invoke_checker(Some(checker), origin, chunks, selection, &mut collective)?;
+                acc.extend(origin.clone(), collective);
+                Ok(acc)
+            },
+        )
+        .try_reduce(
+            || SuggestionSet::new(),
+            |mut a, b| {
+                a.join(b);
+                Ok(a)
+            },
+        )?;
+    suggestions.sort();
+    Ok(suggestions)
 }
 
-macro_rules! invoke_checker {
-    ($feature:literal, $checker:ty, $documentation:ident, $config:expr, $config_inner:expr, $collective:expr) => {
-        if !cfg!(feature = $feature) {
-            debug!("Feature {} is disabled by compilation.", $feature);
+/// Create a checker, if it is enabled by configuration.
+macro_rules! create_checker {
+    ($checker:ty, $config:expr, $config_inner:expr) => {{
+        let detector = <$checker>::detector();
+        if $config.is_enabled(detector) {
+            debug!("Creating {} checker.", detector);
+            let config_inner =
+                $config_inner.expect("Must be Some(Config) if is_enabled returns true");
+            Some(<$checker>::new(config_inner)?)
         } else {
-            #[cfg(feature = $feature)]
-            {
-                let detector = <$checker>::detector();
-                let config = $config;
-                if config.is_enabled(detector) {
-                    debug!("Running {} checks.", detector);
-                    invoke_checker_inner::<$checker>($documentation, $config_inner, $collective)?;
-                } else {
-                    debug!("Checker {} is disabled by configuration.", detector);
-                }
+            debug!("Checker {} is disabled by configuration.", detector);
+            None
+        }
+    }};
+}
+
+/// All checkers which are enabled by compilation and configuration.
+struct Checkers {
+    #[cfg(feature = "languagetool")]
+    languagetool: Option<self::languagetool::LanguageToolChecker>,
+    #[cfg(feature = "nlprules")]
+    nlprules: Option<self::nlprules::NlpRulesChecker>,
+    #[cfg(feature = "hunspell")]
+    hunspell: Option<self::hunspell::HunspellChecker>,
+    native_hunspell: Option<self::native_hunspell::NativeHunspellChecker>,
+    custom_rules: Option<self::custom_rules::CustomRulesChecker>,
+    rustdoc: Option<self::rustdoc::RustdocChecker>,
+    typos: Option<self::typos::TyposChecker>,
+    /// Checks all documentation at once, not per origin.
+    terminology: Option<self::terminology::TerminologyChecker>,
+    #[cfg(test)]
+    dummy: Option<self::dummy::DummyChecker>,
+}
+
+impl Checkers {
+    fn new(config: &Config) -> Result<Self> {
+        for (detector, compiled) in Detector::list_available() {
+            if !compiled {
+                debug!("Feature {} is disabled by compilation.", detector);
+            }
+        }
+        Ok(Self {
+            #[cfg(feature = "languagetool")]
+            languagetool: create_checker!(
+                self::languagetool::LanguageToolChecker,
+                config,
+                config.languagetool.as_ref()
+            ),
+            #[cfg(feature = "nlprules")]
+            nlprules: create_checker!(
+                self::nlprules::NlpRulesChecker,
+                config,
+                config.nlprules.as_ref()
+            ),
+            #[cfg(feature = "hunspell")]
+            hunspell: create_checker!(
+                self::hunspell::HunspellChecker,
+                config,
+                config.hunspell.as_ref()
+            ),
+            native_hunspell: create_checker!(
+                self::native_hunspell::NativeHunspellChecker,
+                config,
+                config.native_hunspell.as_ref()
+            ),
+            custom_rules: create_checker!(
+                self::custom_rules::CustomRulesChecker,
+                config,
+                config.custom_rules.as_ref()
+            ),
+            rustdoc: create_checker!(
+                self::rustdoc::RustdocChecker,
+                config,
+                config.rustdoc.as_ref()
+            ),
+            typos: create_checker!(self::typos::TyposChecker, config, config.typos.as_ref()),
+            terminology: create_checker!(
+                self::terminology::TerminologyChecker,
+                config,
+                config.terminology.as_ref()
+            ),
+            #[cfg(test)]
+            dummy: create_checker!(self::dummy::DummyChecker, config, Some(&())),
+        })
+    }
+
+    /// Check the chunks of a single origin with all checkers selected for
+    /// their comment variant.
+    ///
+    /// Findings suppressed inline within their chunk are dropped.
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+        selection: &VariantCheckers,
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut collective = Vec::new();
+
+        #[cfg(feature = "languagetool")]
+        invoke_checker(
+            self.languagetool.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        #[cfg(feature = "nlprules")]
+        invoke_checker(
+            self.nlprules.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        #[cfg(feature = "hunspell")]
+        invoke_checker(
+            self.hunspell.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        invoke_checker(
+            self.native_hunspell.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        invoke_checker(
+            self.custom_rules.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        invoke_checker(
+            self.rustdoc.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        invoke_checker(
+            self.typos.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        #[cfg(test)]
+        invoke_checker(
+            self.dummy.as_ref(),
+            origin,
+            chunks,
+            selection,
+            &mut collective,
+        )?;
+
+        for chunk in chunks {
+            suppress::retain_unsuppressed(chunk, &mut collective);
+        }
+        Ok(collective)
+    }
+}
+
+/// Run the checker on the chunks, whose comment variant its detector is selected for.
+///
+/// Consecutive selected chunks are passed at once, so checkers can process
+/// them together.
+fn invoke_checker<'a, 's, T>(
+    checker: Option<&T>,
+    origin: &ContentOrigin,
+    chunks: &'a [CheckableChunk],
+    selection: &VariantCheckers,
+    collective: &mut Vec<Suggestion<'s>>,
+) -> Result<()>
+where
+    'a: 's,
+    T: Checker,
+{
+    if let Some(checker) = checker {
+        let unselected = |chunk: &CheckableChunk| {
+            let variant = chunk.variant();
+            let unselected = !selection.is_selected(&variant, T::detector());
+            if unselected {
+                log::trace!(
+                    "Checker {} is not selected for {:?}",
+                    T::detector(),
+                    variant
+                );
             }
+            unselected
+        };
+        for run in chunks.split(unselected).filter(|run| !run.is_empty()) {
+            collective.extend(checker.check(origin, run)?);
         }
-    };
+    }
+    Ok(())
 }
 
 /// Check a full document for violations using the tools we have.
+///
+/// The checkers are dispatched per chunk, based on the configured
+/// selection for the comment variant of the chunk.
 pub fn check<'a, 's>(documentation: &'a Documentation, config: &Config) -> Result<SuggestionSet<'s>>
 where
     'a: 's,
 {
-    let mut collective = SuggestionSet::<'s>::new();
-
-    invoke_checker!(
-        "languagetool",
-        self::languagetool::LanguageToolChecker,
-        documentation,
-        config,
-        config.languagetool.as_ref(),
-        &mut collective
-    );
-
-    invoke_checker!(
-        "nlprules",
-        self::nlprules::NlpRulesChecker,
-        documentation,
-        config,
-        config.nlprules.as_ref(),
-        &mut collective
-    );
-
-    invoke_checker!(
-        "hunspell",
-        self::hunspell::HunspellChecker,
-        documentation,
-        config,
-        config.hunspell.as_ref(),
-        &mut collective
-    );
+    let checkers = Checkers::new(config)?;
+    let selection = &config.variant_checkers;
+
+    let mut collective = documentation
+        .par_iter()
+        .try_fold::<SuggestionSet, Result<_>, _, _>(
+            || SuggestionSet::new(),
+            |mut acc, (origin, chunks)| {
+                debug!("Processing {}", origin.as_path().display());
+                acc.extend(origin.clone(), checkers.check(origin, chunks, selection)?);
+                Ok(acc)
+            },
+        )
+        .try_reduce(
+            || SuggestionSet::new(),
+            |mut a, b| {
+                a.join(b);
+                Ok(a)
+            },
+        )?;
+
+    if let Some(terminology) = &checkers.terminology {
+        collective.join(terminology.check(documentation, selection)?);
+    }
 
     collective.sort();
 
@@ -166,8 +411,10 @@ pub mod dummy;
 pub mod tests {
     use super::*;
     use crate::span::Span;
+    use crate::CommentVariant;
     use crate::ContentOrigin;
     use crate::LineColumn;
+    use crate::Severity;
 
     use crate::fluff_up;
 
@@ -197,8 +444,8 @@ pub mod tests {
             .try_init();
         let dev_comments = false;
         let d = Documentation::load_from_str(ContentOrigin::TestEntityRust, content, dev_comments);
-        let suggestion_set =
-            dummy::DummyChecker::check(&d, &()).expect("Dummy extraction must never fail");
+        let suggestion_set = check_with(&dummy::DummyChecker, &d, &Default::default())
+            .expect("Dummy extraction must never fail");
 
         // one file
         assert_eq!(suggestion_set.len(), 1);
@@ -220,6 +467,35 @@ pub mod tests {
         }
     }
 
+    /// Check the doc comments of the rust `source` with `checker`.
+    ///
+    /// Each finding is the rule as `CATEGORY/ID`, empty without any,
+    /// the flagged content, the replacements and the severity.
+    pub(crate) fn check_rust<T: Checker>(
+        checker: &T,
+        source: &str,
+    ) -> Vec<(String, String, Vec<String>, Severity)> {
+        let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, source, false);
+        let (origin, chunks) = docs.iter().next().expect("Contains doc comments. qed");
+        checker
+            .check(origin, chunks)
+            .unwrap()
+            .into_iter()
+            .map(|suggestion| {
+                (
+                    suggestion
+                        .rule
+                        .as_ref()
+                        .map(ToString::to_string)
+                        .unwrap_or_default(),
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.clone(),
+                    suggestion.severity,
+                )
+            })
+            .collect()
+    }
+
     #[test]
     fn extract_suggestions_simple() {
         const SIMPLE: &'static str = fluff_up!("two literals");
@@ -288,4 +564,58 @@ pub mod tests {
         ];
         extraction_test_body(dbg!(SIMPLE), EXPECTED_SPANS);
     }
+
+    #[test]
+    fn variant_selection() {
+        const CONTENT: &str = r#"
+/// Doc.
+struct Q;
+
+// Dev.
+struct X;
+"#;
+        let d = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true);
+        assert_eq!(d.iter().next().unwrap().1.len(), 2);
+
+        let mut config = Config {
+            hunspell: None,
+            nlprules: None,
+            languagetool: None,
+            reflow: None,
+            ..Default::default()
+        };
+        config.variant_checkers.dev = Some(vec![]);
+
+        let suggestion_set = check(&d, &config).expect("Dummy check must never fail");
+        assert_eq!(suggestion_set.total_count(), 1);
+        let (_, suggestions) = suggestion_set.iter().next().unwrap();
+        assert_eq!(suggestions[0].chunk.variant(), CommentVariant::TripleSlash);
+    }
+
+    #[test]
+    fn variant_selection_reflow() {
+        const CONTENT: &str = r#"
+/// Alpha beta gamma delta epsilon zeta eta theta.
+struct Q;
+
+// Alpha beta gamma delta epsilon zeta eta theta.
+struct X;
+"#;
+        let d = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true);
+        let reflow = crate::reflow::Reflow::new(&crate::config::ReflowConfig {
+            max_line_length: 20,
+            dev_comments: true,
+            ..Default::default()
+        })
+        .unwrap();
+        let selection = VariantCheckers {
+            dev: Some(vec![Detector::Hunspell]),
+            ..Default::default()
+        };
+
+        let suggestion_set = check_with(&reflow, &d, &selection).unwrap();
+        assert_eq!(suggestion_set.total_count(), 1);
+        let (_, suggestions) = suggestion_set.iter().next().unwrap();
+        assert_eq!(suggestions[0].chunk.variant(), CommentVariant::TripleSlash);
+    }
 }
@@ -0,0 +1,630 @@
+//! Parsing of hunspell `.aff` files.
+//!
+//! Covers the subset needed for checking and suggesting: flag formats and
+//! aliases, prefixes and suffixes, compounding by flags and `COMPOUNDRULE`,
+//! the special flags, and the `TRY`, `REP`, `MAP` and `ICONV` tables. Refer
+//! to `man 5 hunspell` for the format.
+
+use anyhow::{anyhow, bail, Result};
+use std::collections::HashMap;
+
+/// A flag, a single or a pair of characters or a number, depending on the
+/// `FLAG` format of the affix file.
+pub(super) type Flag = u32;
+
+/// The format of flags, as declared by `FLAG`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FlagFormat {
+    /// A single character per flag, the default.
+    Char,
+    /// Two characters per flag.
+    Long,
+    /// Decimal numbers, separated by commas.
+    Num,
+}
+
+impl FlagFormat {
+    /// Parse all flags of `s`.
+    pub(super) fn parse(self, s: &str) -> Result<Vec<Flag>> {
+        match self {
+            Self::Char => Ok(s.chars().map(|c| c as Flag).collect()),
+            Self::Long => {
+                let chars = s.chars().collect::<Vec<_>>();
+                if chars.len() % 2 != 0 {
+                    bail!("Long flags `{}` must consist of pairs of characters", s);
+                }
+                Ok(chars
+                    .chunks(2)
+                    .map(|pair| (pair[0] as Flag) << 16 | pair[1] as Flag)
+                    .collect())
+            }
+            Self::Num => s
+                .split(',')
+                .filter(|flag| !flag.trim().is_empty())
+                .map(|flag| {
+                    flag.trim()
+                        .parse::<Flag>()
+                        .map_err(|e| anyhow!("Numeric flag `{}` is invalid", flag).context(e))
+                })
+                .collect(),
+        }
+    }
+
+    /// Parse a single flag, as used by the options which declare a special flag.
+    fn parse_one(self, s: &str) -> Result<Flag> {
+        self.parse(s)?
+            .first()
+            .copied()
+            .ok_or_else(|| anyhow!("Missing flag"))
+    }
+}
+
+/// One character of the condition of an affix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Matcher {
+    /// `.`
+    Any,
+    Char(char),
+    /// `[abc]` or `[^abc]`
+    Set {
+        chars: Vec<char>,
+        negated: bool,
+    },
+}
+
+impl Matcher {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Char(expected) => *expected == c,
+            Self::Set { chars, negated } => chars.contains(&c) != *negated,
+        }
+    }
+}
+
+/// The condition the root must satisfy for an affix to apply, at its start for
+/// prefixes and at its end for suffixes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub(super) struct Condition(Vec<Matcher>);
+
+impl Condition {
+    fn parse(s: &str) -> Result<Self> {
+        let mut matchers = Vec::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            matchers.push(match c {
+                '.' => Matcher::Any,
+                '[' => {
+                    let mut set = Vec::new();
+                    let mut negated = false;
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some('^') if set.is_empty() && !negated => negated = true,
+                            Some(c) => set.push(c),
+                            None => bail!("Unterminated `[` in condition `{}`", s),
+                        }
+                    }
+                    Matcher::Set {
+                        chars: set,
+                        negated,
+                    }
+                }
+                c => Matcher::Char(c),
+            });
+        }
+        // `.` alone is the condition without any restriction
+        if matchers == [Matcher::Any] {
+            matchers.clear();
+        }
+        Ok(Self(matchers))
+    }
+
+    pub(super) fn matches_start(&self, root: &str) -> bool {
+        let mut chars = root.chars();
+        self.0
+            .iter()
+            .all(|matcher| chars.next().map_or(false, |c| matcher.matches(c)))
+    }
+
+    pub(super) fn matches_end(&self, root: &str) -> bool {
+        let mut chars = root.chars().rev();
+        self.0
+            .iter()
+            .rev()
+            .all(|matcher| chars.next().map_or(false, |c| matcher.matches(c)))
+    }
+}
+
+/// A single prefix or suffix rule.
+#[derive(Debug, Clone)]
+pub(super) struct Affix {
+    pub(super) flag: Flag,
+    /// Can be combined with an affix of the other kind.
+    pub(super) cross_product: bool,
+    /// Removed from the root before adding.
+    pub(super) strip: String,
+    pub(super) add: String,
+    /// Flags of the affixed word, which allow further affixes.
+    pub(super) continuation: Vec<Flag>,
+    pub(super) condition: Condition,
+}
+
+/// An element of a `COMPOUNDRULE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Quantifier {
+    One,
+    /// `?`
+    Optional,
+    /// `*`
+    Any,
+}
+
+/// Settings for words made up of multiple dictionary words.
+#[derive(Debug, Clone)]
+pub(super) struct Compounding {
+    /// `COMPOUNDFLAG`, the word may appear anywhere in a compound.
+    pub(super) flag: Option<Flag>,
+    pub(super) begin: Option<Flag>,
+    pub(super) middle: Option<Flag>,
+    pub(super) end: Option<Flag>,
+    /// The word is only valid within compounds.
+    pub(super) only_in_compound: Option<Flag>,
+    /// Minimal length of each part in characters.
+    pub(super) min: usize,
+    /// Maximal number of parts.
+    pub(super) max_words: Option<usize>,
+    pub(super) rules: Vec<Vec<(Flag, Quantifier)>>,
+}
+
+impl Default for Compounding {
+    fn default() -> Self {
+        Self {
+            flag: None,
+            begin: None,
+            middle: None,
+            end: None,
+            only_in_compound: None,
+            min: 3,
+            max_words: None,
+            rules: Vec::new(),
+        }
+    }
+}
+
+impl Compounding {
+    pub(super) fn is_enabled(&self) -> bool {
+        self.flag.is_some()
+            || self.begin.is_some()
+            || self.middle.is_some()
+            || self.end.is_some()
+            || !self.rules.is_empty()
+    }
+}
+
+/// The parsed affix file.
+#[derive(Debug, Clone)]
+pub(super) struct Aff {
+    pub(super) flag_format: FlagFormat,
+    /// Flag sets referred to by number with `AF`, starting at `1`.
+    pub(super) aliases: Vec<Vec<Flag>>,
+    pub(super) prefixes: Vec<Affix>,
+    pub(super) suffixes: Vec<Affix>,
+    pub(super) compounding: Compounding,
+    pub(super) forbidden: Option<Flag>,
+    pub(super) need_affix: Option<Flag>,
+    pub(super) no_suggest: Option<Flag>,
+    pub(super) keep_case: Option<Flag>,
+    /// Characters to try for suggestions, ordered by frequency.
+    pub(super) try_chars: Vec<char>,
+    /// Typical misspellings and their replacement, `_` is a space.
+    pub(super) replacements: Vec<(String, String)>,
+    /// Groups of related characters, which may be mistaken for each other.
+    pub(super) related: Vec<Vec<String>>,
+    /// Conversions of the input, i.e. typographic apostrophes.
+    pub(super) input_conversions: Vec<(String, String)>,
+}
+
+impl Default for Aff {
+    fn default() -> Self {
+        Self {
+            flag_format: FlagFormat::Char,
+            aliases: Vec::new(),
+            prefixes: Vec::new(),
+            suffixes: Vec::new(),
+            compounding: Compounding::default(),
+            forbidden: None,
+            need_affix: None,
+            no_suggest: None,
+            keep_case: None,
+            try_chars: Vec::new(),
+            replacements: Vec::new(),
+            related: Vec::new(),
+            input_conversions: Vec::new(),
+        }
+    }
+}
+
+/// Decode the content of a dictionary or affix file, in the encoding declared
+/// by `SET` in the affix file.
+pub(super) fn decode(bytes: &[u8], encoding: &str) -> Result<String> {
+    /// Differences of ISO 8859-15 to ISO 8859-1.
+    const LATIN9: &[(u8, char)] = &[
+        (0xA4, '€'),
+        (0xA6, 'Š'),
+        (0xA8, 'š'),
+        (0xB4, 'Ž'),
+        (0xB8, 'ž'),
+        (0xBC, 'Œ'),
+        (0xBD, 'œ'),
+        (0xBE, 'Ÿ'),
+    ];
+    match encoding.to_uppercase().as_str() {
+        "UTF-8" | "UTF8" => String::from_utf8(bytes.to_vec())
+            .map_err(|e| anyhow!("Content is not valid UTF-8").context(e)),
+        "ISO8859-1" | "ISO-8859-1" => Ok(bytes.iter().map(|&b| b as char).collect()),
+        "ISO8859-15" | "ISO-8859-15" => Ok(bytes
+            .iter()
+            .map(|&b| {
+                LATIN9
+                    .iter()
+                    .find(|(byte, _)| *byte == b)
+                    .map_or(b as char, |(_, c)| *c)
+            })
+            .collect()),
+        _ => bail!("Unsupported encoding {}", encoding),
+    }
+}
+
+/// Find the encoding declared by `SET`, ISO 8859-1 if there is none.
+pub(super) fn encoding(bytes: &[u8]) -> String {
+    // the declaration itself is ASCII
+    bytes
+        .split(|&b| b == b'\n')
+        .filter_map(|line| std::str::from_utf8(line).ok())
+        .find_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next() == Some("SET") {
+                fields.next().map(str::to_owned)
+            } else {
+                None
+            }
+        })
+        .unwrap_or_else(|| "ISO8859-1".to_owned())
+}
+
+/// Split `word/flags` at the first unescaped `/`, and unescape `\/`.
+pub(super) fn split_flags(s: &str) -> (String, Option<&str>) {
+    let mut word = String::with_capacity(s.len());
+    let mut chars = s.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' if chars.peek().map(|(_, next)| *next) == Some('/') => {
+                word.push('/');
+                chars.next();
+            }
+            '/' => return (word, Some(&s[idx + 1..])),
+            c => word.push(c),
+        }
+    }
+    (word, None)
+}
+
+/// Context of a line within the affix file.
+#[derive(Debug, Default)]
+struct ParseState {
+    /// Cross product and remaining rule lines of each affix, by kind and flag.
+    affixes: HashMap<(bool, Flag), (bool, usize)>,
+    /// `AF` lines after the count are aliases.
+    aliases_declared: bool,
+}
+
+impl Aff {
+    /// Parse the decoded content of an affix file.
+    pub(super) fn parse(content: &str) -> Result<Self> {
+        let mut aff = Self::default();
+        let mut state = ParseState::default();
+        for (lineno, line) in content.lines().enumerate() {
+            aff.parse_line(line, &mut state).map_err(|e| {
+                e.context(format!("Failed to parse line {} of affix file", lineno + 1))
+            })?;
+        }
+        Ok(aff)
+    }
+
+    fn parse_line(&mut self, line: &str, state: &mut ParseState) -> Result<()> {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        let (keyword, args) = match fields.split_first() {
+            Some((keyword, args)) if !keyword.starts_with('#') => (*keyword, args),
+            _ => return Ok(()),
+        };
+        let arg = |idx: usize| {
+            args.get(idx)
+                .copied()
+                .ok_or_else(|| anyhow!("{} misses an argument", keyword))
+        };
+        let format = self.flag_format;
+        match keyword {
+            "FLAG" => {
+                self.flag_format = match arg(0)? {
+                    "long" => FlagFormat::Long,
+                    "num" => FlagFormat::Num,
+                    // the content is decoded already, so characters are characters
+                    "UTF-8" | "char" => FlagFormat::Char,
+                    other => bail!("Unknown flag format {}", other),
+                }
+            }
+            "AF" => {
+                // the first is the count of aliases
+                if state.aliases_declared {
+                    self.aliases.push(format.parse(arg(0)?)?);
+                } else {
+                    state.aliases_declared = true;
+                }
+            }
+            "TRY" => self.try_chars = arg(0)?.chars().collect(),
+            "REP" => {
+                if args.len() >= 2 {
+                    self.replacements
+                        .push((arg(0)?.to_owned(), arg(1)?.replace('_', " ")));
+                }
+            }
+            "MAP" => {
+                if args.len() == 1 && arg(0)?.parse::<usize>().is_err() {
+                    self.related.push(parse_map(arg(0)?)?);
+                }
+            }
+            "ICONV" => {
+                if args.len() >= 2 {
+                    self.input_conversions
+                        .push((arg(0)?.to_owned(), arg(1)?.to_owned()));
+                }
+            }
+            "COMPOUNDFLAG" => self.compounding.flag = Some(format.parse_one(arg(0)?)?),
+            "COMPOUNDBEGIN" => self.compounding.begin = Some(format.parse_one(arg(0)?)?),
+            "COMPOUNDMIDDLE" => self.compounding.middle = Some(format.parse_one(arg(0)?)?),
+            "COMPOUNDEND" | "COMPOUNDLAST" => {
+                self.compounding.end = Some(format.parse_one(arg(0)?)?)
+            }
+            "ONLYINCOMPOUND" => {
+                self.compounding.only_in_compound = Some(format.parse_one(arg(0)?)?)
+            }
+            "COMPOUNDMIN" => {
+                // hunspell treats values below one as one
+                self.compounding.min = std::cmp::max(1, arg(0)?.parse()?);
+            }
+            "COMPOUNDWORDMAX" => self.compounding.max_words = Some(arg(0)?.parse()?),
+            "COMPOUNDRULE" => {
+                if arg(0)?.parse::<usize>().is_err() {
+                    self.compounding
+                        .rules
+                        .push(parse_compound_rule(arg(0)?, format)?);
+                }
+            }
+            "FORBIDDENWORD" => self.forbidden = Some(format.parse_one(arg(0)?)?),
+            "NEEDAFFIX" | "PSEUDOROOT" => self.need_affix = Some(format.parse_one(arg(0)?)?),
+            "NOSUGGEST" => self.no_suggest = Some(format.parse_one(arg(0)?)?),
+            "KEEPCASE" => self.keep_case = Some(format.parse_one(arg(0)?)?),
+            "PFX" | "SFX" => {
+                let is_prefix = keyword == "PFX";
+                let flag = format.parse_one(arg(0)?)?;
+                match state.affixes.get_mut(&(is_prefix, flag)) {
+                    Some((cross_product, remaining)) if *remaining > 0 => {
+                        *remaining -= 1;
+                        let affix = parse_affix(flag, *cross_product, &args[1..], format)?;
+                        if is_prefix {
+                            self.prefixes.push(affix);
+                        } else {
+                            self.suffixes.push(affix);
+                        }
+                    }
+                    _ => {
+                        let cross_product = arg(1)? == "Y";
+                        let count = arg(2)?.parse::<usize>().map_err(|e| {
+                            anyhow!(
+                                "Invalid count of {} {}",
+                                keyword,
+                                arg(0).unwrap_or_default()
+                            )
+                            .context(e)
+                        })?;
+                        state
+                            .affixes
+                            .insert((is_prefix, flag), (cross_product, count));
+                    }
+                }
+            }
+            // everything else is irrelevant for checking and suggesting
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Parse a rule line of an affix, the fields after the flag:
+/// `strip add[/flags] condition`.
+fn parse_affix(
+    flag: Flag,
+    cross_product: bool,
+    fields: &[&str],
+    format: FlagFormat,
+) -> Result<Affix> {
+    let zero = |s: &str| {
+        if s == "0" {
+            String::new()
+        } else {
+            s.to_owned()
+        }
+    };
+    let strip = zero(
+        fields
+            .get(0)
+            .ok_or_else(|| anyhow!("Affix rule misses the stripping characters"))?,
+    );
+    let (add, continuation) = split_flags(
+        fields
+            .get(1)
+            .ok_or_else(|| anyhow!("Affix rule misses the affix"))?,
+    );
+    let continuation = match continuation {
+        Some(flags) => format.parse(flags)?,
+        None => Vec::new(),
+    };
+    let condition = Condition::parse(fields.get(2).copied().unwrap_or("."))?;
+    Ok(Affix {
+        flag,
+        cross_product,
+        strip,
+        add: zero(&add),
+        continuation,
+        condition,
+    })
+}
+
+/// Parse a `MAP` entry, characters or character sequences in parentheses.
+fn parse_map(s: &str) -> Result<Vec<String>> {
+    let mut group = Vec::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '(' {
+            let sequence = chars.by_ref().take_while(|c| *c != ')').collect::<String>();
+            if sequence.is_empty() {
+                bail!("Empty sequence in MAP {}", s);
+            }
+            group.push(sequence);
+        } else {
+            group.push(c.to_string());
+        }
+    }
+    Ok(group)
+}
+
+/// Parse a `COMPOUNDRULE`, flags followed by an optional `*` or `?`, multi
+/// character flags in parentheses.
+fn parse_compound_rule(s: &str, format: FlagFormat) -> Result<Vec<(Flag, Quantifier)>> {
+    let mut rule = Vec::<(Flag, Quantifier)>::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' => {
+                let last = rule
+                    .last_mut()
+                    .ok_or_else(|| anyhow!("Quantifier without flag in {}", s))?;
+                last.1 = if c == '*' {
+                    Quantifier::Any
+                } else {
+                    Quantifier::Optional
+                };
+            }
+            '(' => {
+                let flag = chars.by_ref().take_while(|c| *c != ')').collect::<String>();
+                rule.push((format.parse_one(&flag)?, Quantifier::One));
+            }
+            c => rule.push((c as Flag, Quantifier::One)),
+        }
+    }
+    Ok(rule)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags() {
+        assert_eq!(FlagFormat::Char.parse("Ab").unwrap(), vec![65, 98]);
+        assert_eq!(
+            FlagFormat::Long.parse("AaBb").unwrap(),
+            vec![65 << 16 | 97, 66 << 16 | 98]
+        );
+        assert!(FlagFormat::Long.parse("AaB").is_err());
+        assert_eq!(FlagFormat::Num.parse("1,22,333").unwrap(), vec![1, 22, 333]);
+    }
+
+    #[test]
+    fn conditions() {
+        let condition = Condition::parse("[^aeiou]y").unwrap();
+        assert!(condition.matches_end("try"));
+        assert!(!condition.matches_end("stay"));
+        assert!(!condition.matches_end("y"));
+        assert!(Condition::parse(".").unwrap().matches_end(""));
+        assert!(Condition::parse("[ab]c").unwrap().matches_start("bcd"));
+    }
+
+    #[test]
+    fn affix_file() {
+        let aff = Aff::parse(
+            r#"
+SET UTF-8
+TRY esianrtolc
+# a comment
+FLAG long
+COMPOUNDMIN 1
+ONLYINCOMPOUND cc
+COMPOUNDRULE 1
+COMPOUNDRULE (nn)*(11)(tt)
+
+REP 2
+REP f ph
+REP alot a_lot
+
+MAP 1
+MAP ß(ss)
+
+PFX Re Y 1
+PFX Re 0 re .
+
+SFX Ss N 2
+SFX Ss y ies [^aeiou]y
+SFX Ss 0 s/Xx [aeiou]y
+"#,
+        )
+        .unwrap();
+        let flag = |s: &str| FlagFormat::Long.parse_one(s).unwrap();
+        assert_eq!(aff.flag_format, FlagFormat::Long);
+        assert_eq!(aff.try_chars.len(), 10);
+        assert_eq!(aff.compounding.min, 1);
+        assert_eq!(aff.compounding.only_in_compound, Some(flag("cc")));
+        assert_eq!(
+            aff.compounding.rules,
+            vec![vec![
+                (flag("nn"), Quantifier::Any),
+                (flag("11"), Quantifier::One),
+                (flag("tt"), Quantifier::One)
+            ]]
+        );
+        assert_eq!(
+            aff.replacements,
+            vec![
+                ("f".to_owned(), "ph".to_owned()),
+                ("alot".to_owned(), "a lot".to_owned())
+            ]
+        );
+        assert_eq!(aff.related, vec![vec!["ß".to_owned(), "ss".to_owned()]]);
+        assert_eq!(aff.prefixes.len(), 1);
+        assert!(aff.prefixes[0].cross_product);
+        assert_eq!(aff.suffixes.len(), 2);
+        assert_eq!(aff.suffixes[0].strip, "y");
+        assert_eq!(aff.suffixes[0].add, "ies");
+        assert_eq!(aff.suffixes[1].strip, "");
+        assert_eq!(aff.suffixes[1].continuation, vec![flag("Xx")]);
+    }
+
+    #[test]
+    fn encodings() {
+        assert_eq!(encoding(b"# x\nSET UTF-8\n"), "UTF-8");
+        assert_eq!(encoding(b"TRY abc\n"), "ISO8859-1");
+        assert_eq!(decode(b"caf\xe9", "ISO8859-1").unwrap(), "café");
+        assert_eq!(decode(b"\xa4", "ISO8859-15").unwrap(), "€");
+        assert!(decode(b"x", "KOI8-R").is_err());
+    }
+
+    #[test]
+    fn escaped_flags() {
+        assert_eq!(
+            split_flags("and\\/or/AB"),
+            ("and/or".to_owned(), Some("AB"))
+        );
+        assert_eq!(split_flags("word"), ("word".to_owned(), None));
+    }
+}
@@ -0,0 +1,78 @@
+//! Parsing of hunspell `.dic` files.
+//!
+//! The first line is the approximate number of words, followed by one word
+//! per line with its optional flags, `word/flags`. Morphological fields after
+//! the word are ignored.
+
+use super::aff::{split_flags, Flag};
+use super::NativeDictionary;
+
+use anyhow::{anyhow, Result};
+
+impl NativeDictionary {
+    /// Add the words of the decoded content of a dictionary.
+    pub(super) fn add_words(&mut self, content: &str) -> Result<()> {
+        let mut lines = content.lines().enumerate().peekable();
+        // the count is only a hint for allocation
+        if let Some((_, count)) = lines.peek() {
+            if let Ok(count) = count.trim().parse::<usize>() {
+                self.words.reserve(count);
+                lines.next();
+            }
+        }
+        for (lineno, line) in lines {
+            // lines starting with a tab are comments
+            if line.starts_with('\t') {
+                continue;
+            }
+            let entry = match line.split_whitespace().next() {
+                Some(entry) => entry,
+                None => continue,
+            };
+            let (word, flags) = split_flags(entry);
+            let flags = match flags {
+                Some(flags) => self
+                    .parse_flags(flags)
+                    .map_err(|e| e.context(format!("Invalid flags in line {}", lineno + 1)))?,
+                None => Vec::new(),
+            };
+            self.words.entry(word).or_default().push(flags);
+        }
+        Ok(())
+    }
+
+    /// Parse the flags of a word, which refer to a flag set of `AF` if there
+    /// are any.
+    fn parse_flags(&self, flags: &str) -> Result<Vec<Flag>> {
+        let aliases = &self.aff.aliases;
+        if aliases.is_empty() {
+            return self.aff.flag_format.parse(flags);
+        }
+        flags
+            .parse::<usize>()
+            .ok()
+            .and_then(|idx| aliases.get(idx.checked_sub(1)?))
+            .cloned()
+            .ok_or_else(|| anyhow!("Flag alias {} is not defined", flags))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::aff::Aff;
+    use super::*;
+
+    #[test]
+    fn words() {
+        let aff = Aff::parse("FLAG num\nAF 2\nAF 1,2\nAF 3\n").unwrap();
+        let mut dictionary = NativeDictionary::new(aff, "UTF-8".to_owned());
+        dictionary
+            .add_words("3\nfoo/1\nfoo/2 po:noun\n\tcomment\nand\\/or\n\nbar\n")
+            .unwrap();
+        assert_eq!(dictionary.words["foo"], vec![vec![1, 2], vec![3]]);
+        assert_eq!(dictionary.words["and/or"], vec![Vec::<Flag>::new()]);
+        assert!(dictionary.words.contains_key("bar"));
+        assert_eq!(dictionary.words.len(), 3);
+        assert!(dictionary.add_words("1\nbaz/3\n").is_err());
+    }
+}
@@ -0,0 +1,302 @@
+//! Checking words against the dictionary.
+//!
+//! A word is correct if it is a root, a root with a prefix and/or up to two
+//! suffixes, or a compound of roots, in the case written or one of the case
+//! variants `libhunspell` accepts.
+
+use super::aff::{Affix, Flag, Quantifier};
+use super::{capitalize, Casing, NativeDictionary};
+
+impl NativeDictionary {
+    /// Check `word` and its accepted case variants.
+    pub(super) fn lookup(&self, word: &str) -> bool {
+        let word = self.convert_input(word);
+        if word.is_empty() || is_number(&word) {
+            return true;
+        }
+        if self.check_form(&word, false) {
+            return true;
+        }
+        match Casing::of(&word) {
+            Casing::Lower | Casing::Mixed => false,
+            Casing::Capitalized => self.check_form(&word.to_lowercase(), true),
+            Casing::Upper => {
+                let lowercase = word.to_lowercase();
+                self.check_form(&capitalize(&lowercase), true) || self.check_form(&lowercase, true)
+            }
+        }
+    }
+
+    /// Apply the `ICONV` conversions.
+    pub(super) fn convert_input(&self, word: &str) -> String {
+        self.aff
+            .input_conversions
+            .iter()
+            .fold(word.to_owned(), |word, (from, to)| word.replace(from, to))
+    }
+
+    /// Check the word in exactly this case, `recased` words must not be
+    /// flagged with `KEEPCASE`.
+    fn check_form(&self, word: &str, recased: bool) -> bool {
+        if self.is_forbidden(word) {
+            return false;
+        }
+        let need_affix = self.aff.need_affix;
+        self.homonyms(word).any(|flags| {
+            self.is_usable(flags, recased) && need_affix.map_or(true, |flag| !flags.contains(&flag))
+        }) || self.check_affixed(word, recased)
+            || self.check_compound(word)
+    }
+
+    /// Check if any homonym of the word is flagged with `FORBIDDENWORD`.
+    pub(super) fn is_forbidden(&self, word: &str) -> bool {
+        self.aff.forbidden.map_or(false, |forbidden| {
+            self.homonyms(word).any(|flags| flags.contains(&forbidden))
+        })
+    }
+
+    /// Check if a root with `flags` can be used outside of compounds.
+    fn is_usable(&self, flags: &[Flag], recased: bool) -> bool {
+        let is_set = |flag: Option<Flag>| flag.map_or(false, |flag| flags.contains(&flag));
+        !is_set(self.aff.forbidden)
+            && !is_set(self.aff.compounding.only_in_compound)
+            && !(recased && is_set(self.aff.keep_case))
+    }
+
+    /// Check if `root` is in the dictionary with all of the `required` flags.
+    fn has_root(&self, root: &str, recased: bool, required: &[Flag]) -> bool {
+        self.homonyms(root).any(|flags| {
+            self.is_usable(flags, recased) && required.iter().all(|flag| flags.contains(flag))
+        })
+    }
+
+    /// Check if the affix alone does not complete a word.
+    fn needs_affix(&self, affix: &Affix) -> bool {
+        self.aff
+            .need_affix
+            .map_or(false, |flag| affix.continuation.contains(&flag))
+    }
+
+    /// Check the word as a root with a prefix, one or two suffixes, or both.
+    fn check_affixed(&self, word: &str, recased: bool) -> bool {
+        for (suffix, root) in self.strip_suffixes(word) {
+            if !self.needs_affix(suffix) && self.has_root(&root, recased, &[suffix.flag]) {
+                return true;
+            }
+            // the inner suffix allows the outer one with its continuation flags
+            for (inner, inner_root) in self.strip_suffixes(&root) {
+                if inner.continuation.contains(&suffix.flag)
+                    && self.has_root(&inner_root, recased, &[inner.flag])
+                {
+                    return true;
+                }
+            }
+            if !suffix.cross_product {
+                continue;
+            }
+            for (prefix, prefix_root) in self.strip_prefixes(&root) {
+                if prefix.cross_product
+                    && self.has_root(&prefix_root, recased, &[prefix.flag, suffix.flag])
+                {
+                    return true;
+                }
+            }
+        }
+        self.strip_prefixes(word).into_iter().any(|(prefix, root)| {
+            !self.needs_affix(prefix) && self.has_root(&root, recased, &[prefix.flag])
+        })
+    }
+
+    /// All suffixes which could have been added to form `word`, with the root
+    /// they would have been added to.
+    fn strip_suffixes(&self, word: &str) -> Vec<(&Affix, String)> {
+        // the remaining stem must not be empty
+        let boundaries = word
+            .char_indices()
+            .skip(1)
+            .map(|(idx, _)| idx)
+            .chain(std::iter::once(word.len()));
+        let mut acc = Vec::new();
+        for idx in boundaries {
+            let (stem, add) = word.split_at(idx);
+            for affix in self.suffixes.get(add).into_iter().flatten() {
+                let affix = &self.aff.suffixes[*affix];
+                let root = format!("{}{}", stem, affix.strip);
+                if affix.condition.matches_end(&root) {
+                    acc.push((affix, root));
+                }
+            }
+        }
+        acc
+    }
+
+    /// All prefixes which could have been added to form `word`, with the root
+    /// they would have been added to.
+    fn strip_prefixes(&self, word: &str) -> Vec<(&Affix, String)> {
+        let mut acc = Vec::new();
+        for (idx, _) in word.char_indices() {
+            let (add, stem) = word.split_at(idx);
+            for affix in self.prefixes.get(add).into_iter().flatten() {
+                let affix = &self.aff.prefixes[*affix];
+                let root = format!("{}{}", affix.strip, stem);
+                if affix.condition.matches_start(&root) {
+                    acc.push((affix, root));
+                }
+            }
+        }
+        acc
+    }
+
+    /// Check if the word is made up of roots allowed in compounds.
+    fn check_compound(&self, word: &str) -> bool {
+        let compounding = &self.aff.compounding;
+        if !compounding.is_enabled() {
+            return false;
+        }
+        let chars = word.chars().collect::<Vec<_>>();
+        let has_flags = [
+            compounding.flag,
+            compounding.begin,
+            compounding.middle,
+            compounding.end,
+        ]
+        .iter()
+        .any(Option::is_some);
+        (has_flags && self.compound_by_flags(&chars, 0, 1))
+            || compounding
+                .rules
+                .iter()
+                .any(|rule| self.compound_by_rule(&chars, rule, 0, 0))
+    }
+
+    /// Check if a root of a compound has any of the flags.
+    fn is_compound_part(&self, part: &[char], allowed: &[Option<Flag>]) -> bool {
+        let part = part.iter().collect::<String>();
+        let need_affix = self.aff.need_affix;
+        self.homonyms(&part).any(|flags| {
+            let is_set = |flag: Option<Flag>| flag.map_or(false, |flag| flags.contains(&flag));
+            !is_set(self.aff.forbidden)
+                && !is_set(need_affix)
+                && allowed.iter().any(|flag| is_set(*flag))
+        })
+    }
+
+    /// Split the word into parts, which are flagged as allowed in their
+    /// position of the compound, starting with the `count`th part at `start`.
+    fn compound_by_flags(&self, chars: &[char], start: usize, count: usize) -> bool {
+        let compounding = &self.aff.compounding;
+        if compounding.max_words.map_or(false, |max| count > max) {
+            return false;
+        }
+        let min = compounding.min;
+        for end in (start + min)..=chars.len() {
+            let last = end == chars.len();
+            if start == 0 && last {
+                // not a compound at all
+                break;
+            }
+            if !last && chars.len() - end < min {
+                continue;
+            }
+            let position = if start == 0 {
+                compounding.begin
+            } else if last {
+                compounding.end
+            } else {
+                compounding.middle
+            };
+            if self.is_compound_part(&chars[start..end], &[compounding.flag, position])
+                && (last || self.compound_by_flags(chars, end, count + 1))
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Match the parts of the word against a `COMPOUNDRULE`, like a regular
+    /// expression with flags instead of characters.
+    fn compound_by_rule(
+        &self,
+        chars: &[char],
+        rule: &[(Flag, Quantifier)],
+        start: usize,
+        parts: usize,
+    ) -> bool {
+        let (&(flag, quantifier), rest) = match rule.split_first() {
+            Some(first) => first,
+            None => return start == chars.len() && parts > 1,
+        };
+        if quantifier != Quantifier::One && self.compound_by_rule(chars, rest, start, parts) {
+            return true;
+        }
+        let next = if quantifier == Quantifier::Any {
+            rule
+        } else {
+            rest
+        };
+        ((start + self.aff.compounding.min)..=chars.len()).any(|end| {
+            self.is_compound_part(&chars[start..end], &[Some(flag)])
+                && self.compound_by_rule(chars, next, end, parts + 1)
+        })
+    }
+}
+
+/// Numbers, optionally with separators, are always correct.
+fn is_number(word: &str) -> bool {
+    word.chars().any(|c| c.is_ascii_digit())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_digit() || c == '.' || c == ',' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::dictionary;
+
+    #[test]
+    fn affixes() {
+        let dictionary = dictionary();
+        for word in &[
+            "happy",
+            "unhappy",
+            "happiness",
+            "unhappiness",
+            "happinesses",
+            "hashes",
+            "tries",
+            "stays",
+            "stayed",
+            "cafes",
+            "unlocked",
+            "bad/good",
+        ] {
+            assert!(dictionary.lookup(word), "{} must be correct", word);
+        }
+        for word in &["hashs", "trys", "staies", "unhash", "unhappies", "happy's"] {
+            assert!(!dictionary.lookup(word), "{} must be wrong", word);
+        }
+    }
+
+    #[test]
+    fn casing() {
+        let dictionary = dictionary();
+        for word in &["Happy", "HAPPY", "Unhappiness", "Paris", "PARIS", "NASA"] {
+            assert!(dictionary.lookup(word), "{} must be correct", word);
+        }
+        for word in &["paris", "Nasa", "nasa", "hAppy"] {
+            assert!(!dictionary.lookup(word), "{} must be wrong", word);
+        }
+    }
+
+    #[test]
+    fn special_words() {
+        let dictionary = dictionary();
+        for word in &["21st", "3rd", "112th", "1,024", "sux"] {
+            assert!(dictionary.lookup(word), "{} must be correct", word);
+        }
+        for word in &["colour", "Colour", "st", "2st"] {
+            assert!(!dictionary.lookup(word), "{} must be wrong", word);
+        }
+    }
+}
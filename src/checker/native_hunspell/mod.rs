@@ -0,0 +1,331 @@
+//! A dictionary check with affixes, without `libhunspell`
+//!
+//! Reads the same `.dic` and `.aff` files as the hunspell checker and
+//! implements the commonly used subset of their format in Rust, so it is
+//! available where the C++ library can not be built or linked. Suggestions
+//! are derived from the `REP` and `MAP` tables of the affix file and single
+//! character edits, so they differ from those of `libhunspell`.
+
+use super::dictionary::{check_words, Dictionary};
+use super::{Checker, Detector, Suggestion};
+
+use crate::config::{is_valid_hunspell_dic_path, HunspellConfig, Quirks};
+use crate::documentation::{CheckableChunk, ContentOrigin};
+
+use anyhow::{anyhow, bail, Result};
+use fs_err as fs;
+use log::{debug, info};
+use std::collections::HashMap;
+use std::path::Path;
+
+mod aff;
+mod dic;
+mod lookup;
+mod suggest;
+
+use self::aff::{decode, encoding, Aff, Flag};
+
+pub(crate) struct NativeHunspellChecker {
+    dictionary: NativeDictionary,
+    quirks: Quirks,
+}
+
+impl Checker for NativeHunspellChecker {
+    type Config = HunspellConfig;
+
+    fn detector() -> Detector {
+        Detector::NativeHunspell
+    }
+
+    fn new(config: &Self::Config) -> Result<Self> {
+        let lang = config.lang();
+        let (dic, aff) = config.lookup_dictionary().found.ok_or_else(|| {
+            anyhow!(
+                "Failed to find any {lang}.dic / {lang}.aff in any search dir or no search provided, `--checkers=typos` checks without any dictionary",
+                lang = lang
+            )
+        })?;
+        info!(
+            "Using dictionary {} with affixes {}",
+            dic.display(),
+            aff.display()
+        );
+        let mut dictionary = NativeDictionary::load(&aff, &dic)?;
+
+        // be more strict about the extra dictionaries, they have to exist
+        for extra_dic in config.extra_dictionaries().iter() {
+            debug!("Adding extra dictionary {}", extra_dic.display());
+            if !extra_dic.is_file() {
+                bail!("Extra dictionary {} is not a file", extra_dic.display())
+            }
+            is_valid_hunspell_dic_path(extra_dic)?;
+            dictionary.add_dictionary(extra_dic)?;
+        }
+        debug!("Dictionary setup completed successfully.");
+
+        Ok(Self {
+            dictionary,
+            quirks: config.quirks.clone(),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        Ok(check_words(
+            &self.dictionary,
+            Self::detector(),
+            &self.quirks,
+            origin,
+            chunks,
+        ))
+    }
+}
+
+/// The words of one or more `.dic` files, with the affixes of an `.aff` file.
+pub(crate) struct NativeDictionary {
+    aff: Aff,
+    /// Encoding of all dictionary files, as declared by the affix file.
+    encoding: String,
+    /// The flags of every homonym of a word.
+    words: HashMap<String, Vec<Vec<Flag>>>,
+    /// Indices of the prefixes by what they add.
+    prefixes: HashMap<String, Vec<usize>>,
+    /// Indices of the suffixes by what they add.
+    suffixes: HashMap<String, Vec<usize>>,
+}
+
+impl NativeDictionary {
+    /// Load the affix file and the main dictionary.
+    fn load(aff: &Path, dic: &Path) -> Result<Self> {
+        let aff = fs::read(aff)?;
+        let encoding = encoding(&aff);
+        let aff = Aff::parse(&decode(&aff, &encoding)?)?;
+        let mut dictionary = Self::new(aff, encoding);
+        dictionary.add_dictionary(dic)?;
+        Ok(dictionary)
+    }
+
+    fn new(aff: Aff, encoding: String) -> Self {
+        let index = |affixes: &[aff::Affix]| {
+            let mut index = HashMap::<String, Vec<usize>>::new();
+            for (idx, affix) in affixes.iter().enumerate() {
+                index.entry(affix.add.clone()).or_default().push(idx);
+            }
+            index
+        };
+        Self {
+            prefixes: index(&aff.prefixes),
+            suffixes: index(&aff.suffixes),
+            aff,
+            encoding,
+            words: HashMap::new(),
+        }
+    }
+
+    /// Add the words of another `.dic` file, with flags of the same affix file.
+    fn add_dictionary(&mut self, path: &Path) -> Result<()> {
+        let content = decode(&fs::read(path)?, &self.encoding)
+            .map_err(|e| e.context(format!("Failed to read dictionary {}", path.display())))?;
+        self.add_words(&content)
+            .map_err(|e| e.context(format!("Failed to parse dictionary {}", path.display())))
+    }
+
+    /// The flags of all homonyms of `word` in the dictionary.
+    fn homonyms<'d>(&'d self, word: &str) -> impl Iterator<Item = &'d [Flag]> {
+        self.words
+            .get(word)
+            .into_iter()
+            .flat_map(|homonyms| homonyms.iter().map(Vec::as_slice))
+    }
+}
+
+impl Dictionary for NativeDictionary {
+    fn check(&self, word: &str) -> bool {
+        self.lookup(word)
+    }
+
+    fn suggest(&self, word: &str) -> Vec<String> {
+        self.suggestions(word)
+    }
+}
+
+/// Capitalization of a word, which determines the accepted variants in the
+/// dictionary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Casing {
+    /// No uppercase characters at all.
+    Lower,
+    /// Only the first character is uppercase.
+    Capitalized,
+    /// No lowercase characters, but more than one character.
+    Upper,
+    /// Everything else, i.e. `HashMap`.
+    Mixed,
+}
+
+impl Casing {
+    fn of(word: &str) -> Self {
+        let mut chars = word.chars();
+        let first_upper = chars.next().map_or(false, char::is_uppercase);
+        let (mut upper, mut lower) = (0, 0);
+        for c in chars {
+            if c.is_uppercase() {
+                upper += 1;
+            } else if c.is_lowercase() {
+                lower += 1;
+            }
+        }
+        match (first_upper, upper, lower) {
+            (false, 0, _) => Self::Lower,
+            (true, 0, _) => Self::Capitalized,
+            (true, _, 0) => Self::Upper,
+            _ => Self::Mixed,
+        }
+    }
+
+    /// Apply to the lowercase `word`.
+    fn apply(self, word: &str) -> String {
+        match self {
+            Self::Capitalized => capitalize(word),
+            Self::Upper => word.to_uppercase(),
+            Self::Lower | Self::Mixed => word.to_owned(),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tiny english dictionary, with a subset of the affixes of `en_US`.
+    pub(super) const AFF: &str = r#"SET UTF-8
+TRY esianrtolcdugmphbyfvkwzxjq'ESIANRTOLCDUGMPHBYFVKWZXJQ
+ICONV 1
+ICONV ’ '
+FORBIDDENWORD !
+NOSUGGEST %
+ONLYINCOMPOUND c
+COMPOUNDMIN 1
+COMPOUNDRULE 2
+COMPOUNDRULE n*1t
+COMPOUNDRULE n*mp
+REP 3
+REP f ph
+REP ph f
+REP ys$ ies
+MAP 1
+MAP eé
+
+PFX U Y 1
+PFX U 0 un .
+
+SFX S Y 4
+SFX S y ies [^aeiou]y
+SFX S 0 s [aeiou]y
+SFX S 0 es [sxzh]
+SFX S 0 s [^sxzhy]
+
+SFX D Y 2
+SFX D 0 d e
+SFX D 0 ed [^e]
+
+SFX N Y 1
+SFX N y iness/S [^aeiou]y
+"#;
+
+    pub(super) const DIC: &str = r#"20
+happy/UN
+hash/S
+map/SD
+try/SD
+stay/SD
+lock/UDS
+cafe/S
+phone/S
+Paris
+NASA
+1/n1
+2/nm
+3/nm
+st/ct
+nd/cp
+rd/cp
+th/cpt
+sux/%
+bad\/good
+colour/!
+"#;
+
+    pub(super) fn dictionary() -> NativeDictionary {
+        let aff = Aff::parse(AFF).unwrap();
+        let mut dictionary = NativeDictionary::new(aff, "UTF-8".to_owned());
+        dictionary.add_words(DIC).unwrap();
+        dictionary
+    }
+
+    #[test]
+    fn casing() {
+        assert_eq!(Casing::of("word"), Casing::Lower);
+        assert_eq!(Casing::of("Word"), Casing::Capitalized);
+        assert_eq!(Casing::of("WORD"), Casing::Upper);
+        assert_eq!(Casing::of("HashMap"), Casing::Mixed);
+        assert_eq!(Casing::of("1st"), Casing::Lower);
+        assert_eq!(Casing::Upper.apply("word"), "WORD");
+    }
+
+    #[test]
+    fn extra_dictionary() {
+        let mut dictionary = dictionary();
+        let cwd = crate::traverse::cwd().unwrap();
+        let extra = cwd.join(".config/lingo.dic");
+        dictionary.add_dictionary(&extra).unwrap();
+
+        let content = fs::read_to_string(&extra).unwrap();
+        for line in content.lines().skip(1) {
+            let (word, _flags) = aff::split_flags(line);
+            assert!(dictionary.lookup(&word), "{} must be correct", word);
+        }
+    }
+
+    #[test]
+    fn checker() {
+        let checker = NativeHunspellChecker {
+            dictionary: dictionary(),
+            quirks: Quirks::default(),
+        };
+        let found = crate::checker::tests::check_rust(
+            &checker,
+            "/// Unhappy hashs of the 21st `colour`.\nstruct X;",
+        );
+        // `of` and `the` are not part of the tiny dictionary
+        let reported = found
+            .into_iter()
+            .map(|(_rule, flagged, replacements, _severity)| (flagged, replacements))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reported,
+            vec![
+                (
+                    "hashs".to_owned(),
+                    vec!["hash".to_owned(), "hashes".to_owned()]
+                ),
+                ("of".to_owned(), vec![]),
+                ("the".to_owned(), vec![]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Suggestions for misspelled words.
+//!
+//! Candidates are, in this order, case variants, the replacements of the
+//! `REP` table, related characters of the `MAP` table, swapped, removed,
+//! replaced and inserted characters, and finally splits into two words. The
+//! first valid ones are suggested, in the case of the misspelled word.
+
+use super::{capitalize, Casing, NativeDictionary};
+
+use indexmap::IndexSet;
+
+/// The maximum number of suggestions for a single word.
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Characters to insert and replace with, if the affix file has no `TRY`.
+const DEFAULT_TRY: &str = "esianrtolcdugmphbyfvkwzxjq";
+
+impl NativeDictionary {
+    /// Suggest replacements for the misspelled `word`, best first.
+    pub(super) fn suggestions(&self, word: &str) -> Vec<String> {
+        let word = self.convert_input(word);
+        let casing = Casing::of(&word);
+        let base = match casing {
+            Casing::Capitalized | Casing::Upper => word.to_lowercase(),
+            Casing::Lower | Casing::Mixed => word.clone(),
+        };
+
+        // names and acronyms, as they are in the dictionary
+        let mut candidates = vec![capitalize(&base), base.to_uppercase()]
+            .into_iter()
+            .filter(|candidate| self.words.contains_key(candidate))
+            .collect::<Vec<_>>();
+        candidates.extend(self.replacements(&base));
+        candidates.extend(self.related(&base));
+        candidates.extend(self.edits(&base));
+        candidates.extend(splits(&base));
+
+        candidates
+            .into_iter()
+            .map(|candidate| casing.apply(&candidate))
+            .filter(|candidate| *candidate != word)
+            .collect::<IndexSet<_>>()
+            .into_iter()
+            .filter(|candidate| self.is_suggestible(candidate))
+            .take(MAX_SUGGESTIONS)
+            .collect()
+    }
+
+    /// Check if all words of the candidate are correct and none of them is
+    /// flagged with `NOSUGGEST`.
+    fn is_suggestible(&self, candidate: &str) -> bool {
+        candidate.split(' ').all(|word| {
+            self.lookup(word)
+                && self.aff.no_suggest.map_or(true, |flag| {
+                    !self
+                        .homonyms(word)
+                        .chain(self.homonyms(&word.to_lowercase()))
+                        .any(|flags| flags.contains(&flag))
+                })
+        })
+    }
+
+    /// Apply each `REP` replacement at every position it matches, `^` and `$`
+    /// anchor it at the start and end of the word.
+    fn replacements(&self, word: &str) -> Vec<String> {
+        let mut acc = Vec::new();
+        for (from, to) in &self.aff.replacements {
+            let (from, at_start) = match from.strip_prefix('^') {
+                Some(from) => (from, true),
+                None => (from.as_str(), false),
+            };
+            let (from, at_end) = match from.strip_suffix('$') {
+                Some(from) => (from, true),
+                None => (from, false),
+            };
+            if from.is_empty() {
+                continue;
+            }
+            for (idx, _) in word.match_indices(from) {
+                let end = idx + from.len();
+                if (at_start && idx != 0) || (at_end && end != word.len()) {
+                    continue;
+                }
+                acc.push(format!("{}{}{}", &word[..idx], to, &word[end..]));
+            }
+        }
+        acc
+    }
+
+    /// Replace characters, or sequences, with the others of their `MAP` group.
+    fn related(&self, word: &str) -> Vec<String> {
+        let mut acc = Vec::new();
+        for group in &self.aff.related {
+            for from in group {
+                for (idx, _) in word.match_indices(from.as_str()) {
+                    let end = idx + from.len();
+                    acc.extend(
+                        group
+                            .iter()
+                            .filter(|to| *to != from)
+                            .map(|to| format!("{}{}{}", &word[..idx], to, &word[end..])),
+                    );
+                }
+            }
+        }
+        acc
+    }
+
+    /// Single character edits, swapped neighbours, removed, replaced and
+    /// inserted characters, with the characters of `TRY`.
+    fn edits(&self, word: &str) -> Vec<String> {
+        let chars = word.chars().collect::<Vec<_>>();
+        let try_chars = if self.aff.try_chars.is_empty() {
+            DEFAULT_TRY.chars().collect()
+        } else {
+            self.aff.try_chars.clone()
+        };
+        let collect = |chars: &[char]| chars.iter().collect::<String>();
+
+        let mut acc = Vec::new();
+        for idx in 1..chars.len() {
+            let mut swapped = chars.clone();
+            swapped.swap(idx - 1, idx);
+            acc.push(collect(&swapped));
+        }
+        for idx in 0..chars.len() {
+            let mut removed = chars.clone();
+            removed.remove(idx);
+            acc.push(collect(&removed));
+        }
+        for idx in 0..chars.len() {
+            for c in try_chars.iter().filter(|c| **c != chars[idx]) {
+                let mut replaced = chars.clone();
+                replaced[idx] = *c;
+                acc.push(collect(&replaced));
+            }
+        }
+        for idx in 0..=chars.len() {
+            for c in &try_chars {
+                let mut inserted = chars.clone();
+                inserted.insert(idx, *c);
+                acc.push(collect(&inserted));
+            }
+        }
+        acc
+    }
+}
+
+/// Two words, which were written without a space.
+fn splits(word: &str) -> Vec<String> {
+    word.char_indices()
+        .skip(1)
+        .map(|(idx, _)| format!("{} {}", &word[..idx], &word[idx..]))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::dictionary;
+
+    #[test]
+    fn suggestions() {
+        let dictionary = dictionary();
+        let suggest = |word: &str| dictionary.suggestions(word);
+        assert_eq!(suggest("paris"), vec!["Paris"]);
+        assert_eq!(suggest("nasa"), vec!["NASA"]);
+        assert_eq!(suggest("fone"), vec!["phone"]);
+        assert_eq!(suggest("Cafés"), vec!["Cafes"]);
+        assert_eq!(suggest("HAPYY"), vec!["HAPPY"]);
+        assert_eq!(suggest("trys"), vec!["tries", "try"]);
+        assert_eq!(suggest("hashmap"), vec!["hash map"]);
+        // neither forbidden nor words flagged with `NOSUGGEST`
+        assert_eq!(suggest("colours"), Vec::<String>::new());
+        assert_eq!(suggest("suxx"), Vec::<String>::new());
+    }
+}
@@ -3,13 +3,12 @@
 //! Does check grammar, and is supposed to only check for grammar.
 //! Sentence splitting is done in hand-waving way. To be improved.
 
-use super::{Checker, Detector, Documentation, Suggestion, SuggestionSet};
-use crate::{CheckableChunk, ContentOrigin};
+use super::{Checker, Detector, Suggestion};
+use crate::{CheckableChunk, ContentOrigin, RuleId, Severity};
 
 use anyhow::Result;
 use fs_err as fs;
-use log::{debug, info, trace, warn};
-use rayon::prelude::*;
+use log::{info, trace, warn};
 
 use nlprule::types::Suggestion as NlpFix;
 use nlprule::{Rules, Tokenizer};
@@ -18,7 +17,10 @@ static DEFAULT_TOKENIZER_BYTES: &[u8] =
     include_bytes!(concat!(env!("OUT_DIR"), "/en_tokenizer.bin"));
 static DEFAULT_RULES_BYTES: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/en_rules.bin"));
 
-pub(crate) struct NlpRulesChecker;
+pub(crate) struct NlpRulesChecker {
+    tokenizer: Tokenizer,
+    rules: Rules,
+}
 
 impl Checker for NlpRulesChecker {
     type Config = crate::config::NlpRulesConfig;
@@ -27,10 +29,7 @@ impl Checker for NlpRulesChecker {
         Detector::NlpRules
     }
 
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
-    where
-        'a: 's,
-    {
+    fn new(config: &Self::Config) -> Result<Self> {
         info!("Loading tokenizer...");
 
         let tokenizer = config.override_tokenizer.as_ref().map_or_else(
@@ -61,50 +60,32 @@ impl Checker for NlpRulesChecker {
 
         let rules = rules
             .into_iter()
-            .filter(|rule| {
-                match rule.category_id().to_lowercase().as_ref() {
-                    // The hunspell backend is aware of
-                    // custom lingo, which this one is not,
-                    // so there would be a lot of false
-                    // positives.
-                    "misspelling" => false,
-                    // Anything quotes related is not relevant
-                    // for code documentation.
-                    "typography" => false,
-                    _other => true,
-                }
-            })
+            .filter(|rule| config.is_rule_enabled(rule.id(), rule.category_id()))
             .collect::<Rules>();
 
         info!("Loaded rules.");
 
-        let rules = &rules;
-        let tokenizer = &tokenizer;
-        let suggestions = docu
-            .par_iter()
-            .try_fold::<SuggestionSet, Result<_>, _, _>(
-                || SuggestionSet::new(),
-                move |mut acc, (origin, chunks)| {
-                    debug!("Processing {}", origin.as_path().display());
-
-                    for chunk in chunks {
-                        acc.extend(
-                            origin.clone(),
-                            check_chunk(origin.clone(), chunk, tokenizer, rules),
-                        );
-                    }
-                    Ok(acc)
-                },
-            )
-            .try_reduce(
-                || SuggestionSet::new(),
-                |mut a, b| {
-                    a.join(b);
-                    Ok(a)
-                },
-            )?;
-
-        Ok(suggestions)
+        Ok(Self { tokenizer, rules })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut acc = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            acc.extend(check_chunk(
+                origin.clone(),
+                chunk,
+                &self.tokenizer,
+                &self.rules,
+            ));
+        }
+        Ok(acc)
     }
 }
 
@@ -128,17 +109,21 @@ fn check_chunk<'a>(
     }
 
     'nlp: for NlpFix {
+        source,
         message,
         start,
         end,
         replacements,
-        ..
     } in nlpfixes
     {
         if start > end {
             warn!("BUG: crate nlprule yielded a negative range, please file a bug");
             continue 'nlp;
         }
+        let rule = rules.rule(&source).map(|rule| RuleId {
+            category: rule.category_id().to_owned(),
+            id: source.clone(),
+        });
         let range = start..end;
         acc.extend(
             plain
@@ -152,6 +137,8 @@ fn check_chunk<'a>(
                     replacements: replacements.iter().map(|x| x.clone()).collect(),
                     chunk,
                     description: Some(message.clone()),
+                    rule: rule.clone(),
+                    severity: Severity::Error,
                 }),
         );
     }
@@ -0,0 +1,520 @@
+//! Structure of rustdoc comments.
+//!
+//! Lints `///` documentation the way rustdoc presents it: the first paragraph
+//! is shown as summary in the module overview and must be a single sentence,
+//! sections use the conventional names, and the documentation of an
+//! `unsafe fn` explains how to call it in a `# Safety` section.
+
+use super::{Checker, Detector, Suggestion};
+use crate::config::RustdocConfig;
+use crate::documentation::CommentVariant;
+use crate::util::byte_range_to_char_range;
+use crate::{CheckableChunk, ContentOrigin, LineColumn, Range, RuleId, Severity, Span};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use log::trace;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+use regex::Regex;
+
+/// Conventional section names.
+const SECTIONS: &[&str] = &["Examples", "Panics", "Errors", "Safety"];
+
+/// Abbreviations ending in a period, which do not end the sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "approx.", "cf.", "e.g.", "etc.", "i.e.", "resp.", "viz.", "vs.",
+];
+
+lazy_static! {
+    /// The end of a sentence, followed by the start of the next one.
+    static ref BOUNDARY: Regex = Regex::new(r"[.!?]\s+[\p{Lu}`\[*_]").unwrap();
+}
+
+pub(crate) struct RustdocChecker {
+    config: RustdocConfig,
+}
+
+impl Checker for RustdocChecker {
+    type Config = RustdocConfig;
+
+    fn detector() -> Detector {
+        Detector::Rustdoc
+    }
+
+    fn new(config: &Self::Config) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut acc = Vec::new();
+        for chunk in chunks
+            .iter()
+            .filter(|chunk| chunk.variant() == CommentVariant::TripleSlash)
+        {
+            let report = Report { origin, chunk };
+            if chunk.as_str().trim().is_empty() {
+                if self.config.empty_docs {
+                    acc.extend(report.empty());
+                }
+                continue;
+            }
+
+            let outline = Outline::parse(chunk.as_str());
+            trace!("{:?}", &outline);
+            if self.config.summary_sentence {
+                acc.extend(summary(&report, &outline));
+            }
+            if self.config.section_names {
+                acc.extend(section_names(&report, &outline));
+            }
+            if self.config.safety_section && chunk.is_unsafe_fn() {
+                acc.extend(safety_section(&report, &outline));
+            }
+        }
+        Ok(acc)
+    }
+}
+
+/// The parts of a doc comment relevant for its structure, as byte ranges of
+/// the markdown.
+#[derive(Debug, Default)]
+struct Outline {
+    /// The first paragraph, if the documentation starts with one.
+    summary: Option<std::ops::Range<usize>>,
+    /// Inline code within the summary.
+    code: Vec<std::ops::Range<usize>>,
+    /// Text of all headings.
+    headings: Vec<(std::ops::Range<usize>, String)>,
+}
+
+impl Outline {
+    fn parse(markdown: &str) -> Self {
+        let parser = Parser::new_ext(
+            markdown,
+            Options::ENABLE_TABLES
+                | Options::ENABLE_FOOTNOTES
+                | Options::ENABLE_STRIKETHROUGH
+                | Options::ENABLE_TASKLISTS,
+        );
+
+        let mut outline = Self::default();
+        let mut first_block = true;
+        let mut in_summary = false;
+        let mut heading: Option<(std::ops::Range<usize>, String)> = None;
+        for (event, byte_range) in parser.into_offset_iter() {
+            match event {
+                Event::Start(Tag::Paragraph) if first_block => {
+                    outline.summary = Some(byte_range);
+                    in_summary = true;
+                }
+                Event::End(Tag::Paragraph) => in_summary = false,
+                Event::Start(Tag::Heading(_)) => {
+                    heading = Some((byte_range.end..byte_range.end, String::new()))
+                }
+                Event::End(Tag::Heading(_)) => {
+                    if let Some((range, text)) = heading.take() {
+                        if !text.is_empty() {
+                            outline.headings.push((range, text));
+                        }
+                    }
+                }
+                Event::Text(text) => {
+                    if let Some((range, acc)) = heading.as_mut() {
+                        if acc.is_empty() {
+                            range.start = byte_range.start;
+                        }
+                        range.end = byte_range.end;
+                        acc.push_str(&text);
+                    }
+                }
+                Event::Code(_) if in_summary => outline.code.push(byte_range),
+                _ => {}
+            }
+            first_block = false;
+        }
+        outline
+    }
+}
+
+/// Creates the suggestions for a single chunk.
+struct Report<'o, 'a> {
+    origin: &'o ContentOrigin,
+    chunk: &'a CheckableChunk,
+}
+
+impl<'o, 'a> Report<'o, 'a> {
+    fn suggestion(
+        &self,
+        id: &str,
+        range: Range,
+        span: Span,
+        description: &str,
+        replacements: Vec<String>,
+    ) -> Suggestion<'a> {
+        Suggestion {
+            detector: Detector::Rustdoc,
+            range,
+            span,
+            origin: self.origin.clone(),
+            replacements,
+            chunk: self.chunk,
+            description: Some(description.to_owned()),
+            rule: Some(RuleId {
+                category: "RUSTDOC".to_owned(),
+                id: id.to_owned(),
+            }),
+            severity: Severity::Error,
+        }
+    }
+
+    /// Report the byte range of the markdown of the chunk.
+    fn markdown(
+        &self,
+        id: &str,
+        byte_range: std::ops::Range<usize>,
+        description: &str,
+        replacements: Vec<String>,
+    ) -> Vec<Suggestion<'a>> {
+        byte_range_to_char_range(self.chunk.as_str(), byte_range)
+            .map(|range| self.chunk.find_spans(range))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(range, span)| {
+                self.suggestion(id, range, span, description, replacements.clone())
+            })
+            .collect()
+    }
+
+    /// Report the empty chunk at its comment markers, since there is no content.
+    fn empty(&self) -> Option<Suggestion<'a>> {
+        let (_range, span) = self.chunk.iter().next()?;
+        // the span of empty content starts right after the `///`
+        let line = span.start.line;
+        let column = span.start.column;
+        let span = Span {
+            start: LineColumn {
+                line,
+                column: column.saturating_sub(3),
+            },
+            end: LineColumn {
+                line,
+                column: column.saturating_sub(1),
+            },
+        };
+        Some(self.suggestion(
+            "EMPTY",
+            0..0,
+            span,
+            "Empty documentation, remove it or describe the item",
+            Vec::new(),
+        ))
+    }
+}
+
+/// The first paragraph must be a single sentence, ending in a period.
+fn summary<'a>(report: &Report<'_, 'a>, outline: &Outline) -> Vec<Suggestion<'a>> {
+    let markdown = report.chunk.as_str();
+    let paragraph = match outline.summary.clone() {
+        Some(paragraph) => paragraph,
+        None => return Vec::new(),
+    };
+    let text = markdown[paragraph.clone()].trim_end();
+    let end = paragraph.start + text.len();
+
+    let mut acc = Vec::new();
+    let boundary = BOUNDARY
+        .find_iter(text)
+        .map(|m| (paragraph.start + m.start(), paragraph.start + m.end()))
+        .filter(|(punctuation, _)| !outline.code.iter().any(|code| code.contains(punctuation)))
+        .find(|(punctuation, _)| {
+            let word = markdown[..=*punctuation]
+                .rsplit(char::is_whitespace)
+                .next()
+                .unwrap_or_default()
+                .trim_start_matches(|c| c == '(' || c == '*' || c == '_')
+                .to_lowercase();
+            !ABBREVIATIONS.contains(&word.as_str())
+        });
+    if let Some((_, next)) = boundary {
+        // the match ends after the first character of the next sentence
+        let start = markdown[..next]
+            .char_indices()
+            .next_back()
+            .map(|(idx, _)| idx)
+            .unwrap_or(next);
+        let line_end = markdown[start..end]
+            .find('\n')
+            .map(|idx| start + idx)
+            .unwrap_or(end);
+        acc.extend(report.markdown(
+            "SUMMARY",
+            start..line_end,
+            "The summary should be a single sentence, continue in a separate paragraph",
+            Vec::new(),
+        ));
+    }
+
+    if !text.ends_with('.') {
+        let start = text
+            .rfind(char::is_whitespace)
+            .map(|idx| paragraph.start + idx + 1)
+            .unwrap_or(paragraph.start);
+        let word = &markdown[start..end];
+        let replacement = format!(
+            "{}.",
+            word.trim_end_matches(|c| c == ':' || c == ';' || c == ',')
+        );
+        acc.extend(report.markdown(
+            "PERIOD",
+            start..end,
+            "The summary should end with a period",
+            vec![replacement],
+        ));
+    }
+    acc
+}
+
+/// The conventional section `heading` refers to, if it is one or a misspelling
+/// of one.
+fn section_name(heading: &str) -> Option<&'static str> {
+    let heading = heading.trim().to_lowercase();
+    SECTIONS.iter().copied().find(|section| {
+        let tolerance = std::cmp::max(1, section.len() / 4);
+        distance(&heading, &section.to_lowercase()) <= tolerance
+    })
+}
+
+/// Sections should use the conventional names.
+fn section_names<'a>(report: &Report<'_, 'a>, outline: &Outline) -> Vec<Suggestion<'a>> {
+    outline
+        .headings
+        .iter()
+        .filter_map(|(range, heading)| {
+            section_name(heading)
+                .filter(|section| section != heading)
+                .map(|section| (range, heading, section))
+        })
+        .flat_map(|(range, heading, section)| {
+            report.markdown(
+                "SECTION_NAME",
+                range.clone(),
+                &format!(
+                    "The conventional name of the section `{}` is `{}`",
+                    heading, section
+                ),
+                vec![section.to_owned()],
+            )
+        })
+        .collect()
+}
+
+/// The documentation of an `unsafe fn` must have a `# Safety` section.
+fn safety_section<'a>(report: &Report<'_, 'a>, outline: &Outline) -> Vec<Suggestion<'a>> {
+    if outline
+        .headings
+        .iter()
+        .any(|(_range, heading)| section_name(heading) == Some("Safety"))
+    {
+        return Vec::new();
+    }
+    // point at the first line with content
+    let markdown = report.chunk.as_str();
+    let start = markdown.len() - markdown.trim_start().len();
+    let end = markdown[start..]
+        .find('\n')
+        .map(|idx| start + idx)
+        .unwrap_or(markdown.len());
+    report.markdown(
+        "MISSING_SAFETY",
+        start..end,
+        "Document the invariants a caller of an `unsafe fn` must uphold in a `# Safety` section",
+        Vec::new(),
+    )
+}
+
+/// Edit distance, where swapping two adjacent characters counts as a single
+/// edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    // d[i][j] is the distance of the first `i` characters of `a` and the
+    // first `j` of `b`
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=b.len() {
+        d[0][j] = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = std::cmp::min(
+                std::cmp::min(d[i - 1][j] + 1, d[i][j - 1] + 1),
+                d[i - 1][j - 1] + cost,
+            );
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = std::cmp::min(d[i][j], d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::tests::check_rust;
+
+    fn check(source: &str) -> Vec<(String, String, Vec<String>, Severity)> {
+        let checker = RustdocChecker::new(&RustdocConfig::default()).unwrap();
+        check_rust(&checker, source)
+    }
+
+    #[test]
+    fn conventional() {
+        let found = check(
+            r#"
+/// Creates a new instance, i.e. an empty one.
+///
+/// Details follow. And more of them, `e.g. this`.
+///
+/// # Examples
+///
+/// ```rust
+/// let the = the the;
+/// ```
+///
+/// # Safety
+///
+/// Is that that complicated?
+pub unsafe fn new() {}
+
+//! Module level docs are not checked
+"#,
+        );
+        assert_eq!(found, vec![]);
+    }
+
+    #[test]
+    fn summary_sentence() {
+        let found = check(
+            r#"
+/// Creates a new instance. Which is empty,
+/// unless
+/// configured:
+///
+/// Details.
+struct X;
+"#,
+        );
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "RUSTDOC/SUMMARY".to_owned(),
+                    "Which is empty,".to_owned(),
+                    vec![],
+                    Severity::Error
+                ),
+                (
+                    "RUSTDOC/PERIOD".to_owned(),
+                    "configured:".to_owned(),
+                    vec!["configured.".to_owned()],
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn sections() {
+        let found = check(
+            r#"
+/// Frobnicates.
+///
+/// # Example
+///
+/// # errors
+///
+/// # Saftey
+///
+/// # Arguments
+pub unsafe fn frobnicate() {}
+
+/// Frobnicates without care.
+pub unsafe fn frobnicate_unchecked() {}
+"#,
+        );
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "RUSTDOC/SECTION_NAME".to_owned(),
+                    "Example".to_owned(),
+                    vec!["Examples".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "RUSTDOC/SECTION_NAME".to_owned(),
+                    "errors".to_owned(),
+                    vec!["Errors".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "RUSTDOC/SECTION_NAME".to_owned(),
+                    "Saftey".to_owned(),
+                    vec!["Safety".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "RUSTDOC/MISSING_SAFETY".to_owned(),
+                    "Frobnicates without care.".to_owned(),
+                    vec![],
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        // doubled words are reported by the typos checker only
+        let found = check(
+            r#"
+///
+struct X;
+
+/// Adds the the value to the
+/// the set, not `the` the code.
+struct Y;
+"#,
+        );
+        assert_eq!(
+            found,
+            vec![(
+                "RUSTDOC/EMPTY".to_owned(),
+                "".to_owned(),
+                vec![],
+                Severity::Error
+            )]
+        );
+    }
+
+    #[test]
+    fn edit_distance() {
+        assert_eq!(distance("safety", "saftey"), 1);
+        assert_eq!(distance("example", "examples"), 1);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("panics", "errors"), 5);
+    }
+}
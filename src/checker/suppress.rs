@@ -0,0 +1,137 @@
+//! Inline suppression of findings.
+//!
+//! A `<!-- spellcheck:ignore word.. -->` comment suppresses all findings
+//! of the listed words within the chunk containing the comment. Being an
+//! HTML comment, it does not show up in the rendered documentation.
+
+use crate::documentation::CheckableChunk;
+use crate::util::sub_chars;
+use crate::Suggestion;
+
+use regex::Regex;
+use std::collections::HashSet;
+
+lazy_static::lazy_static! {
+    static ref DIRECTIVE: Regex = Regex::new(r"<!--\s*spellcheck:ignore\s+(.*?)\s*-->").unwrap();
+}
+
+/// Create a suppression directive for the given words.
+pub(crate) fn directive(words: &[&str]) -> String {
+    format!("<!-- spellcheck:ignore {} -->", words.join(" "))
+}
+
+/// All words suppressed by directives within the chunk.
+pub(crate) fn suppressed_words(chunk: &CheckableChunk) -> HashSet<&str> {
+    DIRECTIVE
+        .captures_iter(chunk.as_str())
+        .filter_map(|captures| captures.get(1))
+        .flat_map(|words| words.as_str().split_whitespace())
+        .collect()
+}
+
+/// Drop all suggestions for content suppressed within `chunk`, suggestions
+/// for other chunks are retained.
+///
+/// Findings of multiple words, i.e. `hash map` of the terminology checker,
+/// are suppressed if all of their words are listed.
+pub(crate) fn retain_unsuppressed(chunk: &CheckableChunk, suggestions: &mut Vec<Suggestion<'_>>) {
+    let suppressed = suppressed_words(chunk);
+    if suppressed.is_empty() {
+        return;
+    }
+    suggestions.retain(|suggestion| {
+        if !std::ptr::eq(suggestion.chunk, chunk) {
+            return true;
+        }
+        let flagged = sub_chars(chunk.as_str(), suggestion.range.clone());
+        let mut words = flagged.split_whitespace().peekable();
+        if words.peek().is_some() && words.all(|word| suppressed.contains(word)) {
+            log::trace!("Finding {} is suppressed inline", flagged);
+            false
+        } else {
+            true
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::{dummy::DummyChecker, Checker};
+    use crate::{ContentOrigin, Documentation};
+
+    #[test]
+    fn suppress_inline() {
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityRust,
+            "/// Alpha <!-- spellcheck:ignore Alpha gamma --> beta gamma\nstruct X;",
+            false,
+        );
+        let (origin, chunks) = docs.iter().next().unwrap();
+        let chunk = &chunks[0];
+        assert_eq!(
+            suppressed_words(chunk),
+            ["Alpha", "gamma"].iter().copied().collect::<HashSet<_>>()
+        );
+
+        let mut suggestions = DummyChecker.check(origin, &chunks[..]).unwrap();
+        retain_unsuppressed(chunk, &mut suggestions);
+        let flagged = suggestions
+            .iter()
+            .map(|suggestion| sub_chars(chunk.as_str(), suggestion.range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, vec!["beta"]);
+    }
+
+    #[test]
+    fn suppress_terms() {
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityCommonMark,
+            "A hash map, a hash set. <!-- spellcheck:ignore hash map -->",
+            false,
+        );
+        let (origin, chunks) = docs.iter().next().unwrap();
+        let chunk = &chunks[0];
+        let mut suggestions = ["hash map", "hash set"]
+            .iter()
+            .map(|term| {
+                let start = chunk.as_str().find(term).unwrap();
+                let range = start..(start + term.len());
+                let span = chunk
+                    .find_spans(range.clone())
+                    .into_iter()
+                    .next()
+                    .unwrap()
+                    .1;
+                Suggestion {
+                    detector: crate::Detector::Terminology,
+                    origin: origin.clone(),
+                    chunk,
+                    span,
+                    range,
+                    replacements: vec![],
+                    description: None,
+                    rule: None,
+                    severity: crate::Severity::default(),
+                }
+            })
+            .collect::<Vec<_>>();
+        retain_unsuppressed(chunk, &mut suggestions);
+        let flagged = suggestions
+            .iter()
+            .map(|suggestion| sub_chars(chunk.as_str(), suggestion.range.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(flagged, vec!["hash set"]);
+    }
+
+    #[test]
+    fn directive_roundtrip() {
+        let docs = Documentation::load_from_str(
+            ContentOrigin::TestEntityCommonMark,
+            &format!("Teh cat {}", directive(&["Teh", "cat"])),
+            false,
+        );
+        let (_origin, chunks) = docs.iter().next().unwrap();
+        assert_eq!(suppressed_words(&chunks[0]).len(), 2);
+    }
+}
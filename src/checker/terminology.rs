@@ -0,0 +1,620 @@
+//! Consistent terminology across all documentation.
+//!
+//! Collects all words and pairs of words joined by a space or a hyphen, and
+//! groups them by a key insensitive to case, hyphenation, spacing and
+//! optionally British and American spelling. Variants of a term used less
+//! often than the dominant one, or differing from the one preferred by the
+//! glossary, are reported.
+//!
+//! Unlike the other checkers, this requires all of the documentation at once.
+
+use super::{is_contiguous, suppress};
+use crate::config::{TerminologyConfig, VariantCheckers};
+use crate::documentation::PlainOverlay;
+use crate::{
+    CheckableChunk, ContentOrigin, Detector, Documentation, Range, RuleId, Severity, Suggestion,
+    SuggestionSet,
+};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use log::{debug, trace};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+
+/// Words which are no terms, neither alone nor as part of a pair, so `in to`
+/// and `into` or `us` and `US` are not considered variants, nor is the
+/// emphasis of `MUST`.
+const STOPWORDS: &[&str] = &[
+    "a", "all", "always", "am", "an", "and", "any", "are", "as", "at", "be", "been", "by", "can",
+    "do", "every", "for", "from", "in", "is", "it", "its", "may", "must", "never", "no", "not",
+    "note", "of", "on", "only", "or", "out", "shall", "should", "some", "that", "the", "these",
+    "this", "those", "to", "up", "us", "will", "with",
+];
+
+/// British spellings of whole words and their American counterparts.
+const WORDS: &[(&str, &str)] = &[
+    ("acknowledgement", "acknowledgment"),
+    ("aluminium", "aluminum"),
+    ("artefact", "artifact"),
+    ("artefacts", "artifacts"),
+    ("defence", "defense"),
+    ("grey", "gray"),
+    ("judgement", "judgment"),
+    ("offence", "offense"),
+    ("programme", "program"),
+    ("programmes", "programs"),
+];
+
+/// Stems, whose final `l` is doubled in British spelling when inflected.
+const DOUBLED_L_STEMS: &[&str] = &[
+    "cancel", "channel", "counsel", "dial", "equal", "fuel", "label", "level", "marshal", "model",
+    "signal", "total", "travel", "tunnel",
+];
+
+/// British endings and their American counterparts, along with the minimum
+/// length of words they apply to, so `hour` does not become `hor`.
+const ENDINGS: &[(&str, &str, usize)] = &[
+    ("isations", "izations", 8),
+    ("isation", "ization", 7),
+    ("ising", "izing", 6),
+    ("isers", "izers", 6),
+    ("iser", "izer", 5),
+    ("ised", "ized", 5),
+    ("ises", "izes", 5),
+    ("ise", "ize", 5),
+    ("ysing", "yzing", 6),
+    ("ysed", "yzed", 5),
+    ("yse", "yze", 5),
+    ("ourable", "orable", 8),
+    ("ourites", "orites", 8),
+    ("ourite", "orite", 7),
+    ("ouring", "oring", 7),
+    ("oured", "ored", 6),
+    ("ours", "ors", 7),
+    ("our", "or", 6),
+    ("tres", "ters", 6),
+    ("tre", "ter", 5),
+    ("bres", "bers", 6),
+    ("bre", "ber", 5),
+    ("ogues", "ogs", 7),
+    ("ogue", "og", 6),
+];
+
+/// Map a lowercase British `word` to its American spelling.
+fn americanize(word: &str) -> Cow<'_, str> {
+    if let Some((_british, american)) = WORDS.iter().find(|(british, _)| *british == word) {
+        return Cow::Borrowed(american);
+    }
+    for suffix in &["led", "ling", "ler", "lers"] {
+        if let Some(stem) = word.strip_suffix(suffix) {
+            if DOUBLED_L_STEMS.contains(&stem) {
+                return Cow::Owned(format!("{}{}", stem, &suffix[1..]));
+            }
+        }
+    }
+    for (british, american, min_len) in ENDINGS {
+        if word.len() >= *min_len {
+            if let Some(stem) = word.strip_suffix(british) {
+                return Cow::Owned(format!("{}{}", stem, american));
+            }
+        }
+    }
+    Cow::Borrowed(word)
+}
+
+/// A word of the plain text.
+#[derive(Debug, Clone)]
+struct Word<'t> {
+    /// Range in characters.
+    range: Range,
+    /// Range in bytes.
+    bytes: std::ops::Range<usize>,
+    text: &'t str,
+}
+
+/// Split `text` into words, which are runs of alphanumeric characters.
+fn words(text: &str) -> Vec<Word<'_>> {
+    let mut acc = Vec::new();
+    let mut start = None;
+    let mut end = (0, 0);
+    for (idx, (offset, c)) in text.char_indices().enumerate() {
+        if c.is_alphanumeric() {
+            if start.is_none() {
+                start = Some((idx, offset));
+            }
+            end = (idx + 1, offset + c.len_utf8());
+        } else if let Some((start_idx, start_offset)) = start.take() {
+            acc.push(Word {
+                range: start_idx..end.0,
+                bytes: start_offset..end.1,
+                text: &text[start_offset..end.1],
+            });
+        }
+    }
+    if let Some((start_idx, start_offset)) = start {
+        acc.push(Word {
+            range: start_idx..end.0,
+            bytes: start_offset..end.1,
+            text: &text[start_offset..end.1],
+        });
+    }
+    acc
+}
+
+/// A word or pair of words, which is a candidate for a term.
+#[derive(Debug, Clone)]
+struct Candidate {
+    range: Range,
+    /// The text as found, with line breaks replaced by spaces.
+    surface: String,
+    key: String,
+}
+
+/// Is the word a candidate for a term or part of one.
+fn is_term_word(word: &str) -> bool {
+    !word.chars().all(|c| c.is_numeric()) && !STOPWORDS.contains(&word.to_lowercase().as_str())
+}
+
+/// Determine the key of a term from its words, identical for all variants.
+fn key<'w>(words: impl IntoIterator<Item = &'w str>, spelling_variants: bool) -> String {
+    words
+        .into_iter()
+        .map(|word| {
+            let word = word.to_lowercase();
+            if spelling_variants {
+                americanize(&word).into_owned()
+            } else {
+                word
+            }
+        })
+        .collect()
+}
+
+/// All words and pairs of words in `text`, which might be terms.
+fn candidates(text: &str, spelling_variants: bool) -> Vec<Candidate> {
+    let words = words(text);
+    let mut acc = Vec::with_capacity(words.len() * 2);
+    for (idx, word) in words.iter().enumerate() {
+        if !is_term_word(word.text) {
+            continue;
+        }
+        if word.range.len() > 1 {
+            acc.push(Candidate {
+                range: word.range.clone(),
+                surface: word.text.to_owned(),
+                key: key(Some(word.text), spelling_variants),
+            });
+        }
+        let next = match words.get(idx + 1) {
+            Some(next) if is_term_word(next.text) => next,
+            _ => continue,
+        };
+        let separator = &text[word.bytes.end..next.bytes.start];
+        if let " " | "-" | "\n" = separator {
+            acc.push(Candidate {
+                range: word.range.start..next.range.end,
+                surface: format!("{}{}{}", word.text, separator.replace('\n', " "), next.text),
+                key: key(vec![word.text, next.text], spelling_variants),
+            });
+        }
+    }
+    acc
+}
+
+/// The form of a term, ignoring the capitalization of words at the start of a
+/// sentence or in a title, so `Hashmap` is the same form as `hashmap`, unlike
+/// `HashMap`.
+fn canonical(surface: &str) -> Cow<'_, str> {
+    let is_separator = |c: char| c == ' ' || c == '-';
+    let is_capitalized = |word: &str| {
+        let mut chars = word.chars();
+        chars.next().map_or(false, char::is_uppercase) && !chars.any(char::is_uppercase)
+    };
+    if !surface.split(is_separator).any(is_capitalized) {
+        return Cow::Borrowed(surface);
+    }
+    let mut acc = String::with_capacity(surface.len());
+    for part in surface.split_inclusive(is_separator) {
+        if is_capitalized(part.trim_end_matches(is_separator)) {
+            let mut chars = part.chars();
+            acc.extend(chars.next().into_iter().flat_map(char::to_lowercase));
+            acc.extend(chars);
+        } else {
+            acc.push_str(part);
+        }
+    }
+    Cow::Owned(acc)
+}
+
+/// Check if `surface` is an acceptable use of the `term`.
+fn is_form_of(surface: &str, term: &str) -> bool {
+    surface == term || (!term.starts_with(char::is_uppercase) && canonical(surface) == term)
+}
+
+/// Suggest `term` as replacement for `surface`, capitalized if `surface` is.
+fn replacement(term: &str, surface: &str) -> String {
+    if surface.starts_with(char::is_uppercase) && !term.starts_with(char::is_uppercase) {
+        let mut chars = term.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        term.to_owned()
+    }
+}
+
+/// A candidate within a particular chunk.
+struct Occurrence {
+    /// Index of the chunk.
+    entry: usize,
+    candidate: Candidate,
+}
+
+/// Which term should be used instead of a variant and why.
+struct Preference {
+    term: String,
+    message: String,
+    rule: &'static str,
+}
+
+pub(crate) struct TerminologyChecker {
+    spelling_variants: bool,
+    /// Preferred terms by their key.
+    glossary: HashMap<String, String>,
+    /// Keys of the ignored terms.
+    ignore: HashSet<String>,
+}
+
+impl TerminologyChecker {
+    pub(crate) fn detector() -> Detector {
+        Detector::Terminology
+    }
+
+    pub(crate) fn new(config: &TerminologyConfig) -> Result<Self> {
+        let spelling_variants = config.spelling_variants;
+        let key_of = |term: &str| key(words(term).iter().map(|word| word.text), spelling_variants);
+        Ok(Self {
+            spelling_variants,
+            glossary: config
+                .glossary
+                .iter()
+                .map(|term| (key_of(term), term.clone()))
+                .collect(),
+            ignore: config.ignore.iter().map(|term| key_of(term)).collect(),
+        })
+    }
+
+    /// Determine the term to use instead of the variants in `occurrences`,
+    /// `None` if there is no inconsistency.
+    fn preference(&self, key: &str, occurrences: &[Occurrence]) -> Option<Preference> {
+        if let Some(term) = self.glossary.get(key) {
+            return Some(Preference {
+                term: term.clone(),
+                message: format!("Use `{}`, as listed in the glossary", term),
+                rule: "GLOSSARY",
+            });
+        }
+        let mut counts = IndexMap::<Cow<'_, str>, usize>::new();
+        for occurrence in occurrences {
+            *counts
+                .entry(canonical(&occurrence.candidate.surface))
+                .or_default() += 1;
+        }
+        if counts.len() < 2 {
+            return None;
+        }
+        let (dominant, count) = counts
+            .iter()
+            .rev()
+            .max_by_key(|(_form, count)| **count)
+            .expect("There are at least two forms. qed");
+        if counts.values().filter(|other| *other == count).count() > 1 {
+            debug!("No dominant form of {}, used as {:?}", key, counts);
+            return None;
+        }
+        Some(Preference {
+            term: dominant.clone().into_owned(),
+            message: format!(
+                "Inconsistent terminology, `{}` is used {} times",
+                dominant, count
+            ),
+            rule: "INCONSISTENT",
+        })
+    }
+
+    /// Check all chunks of the documentation selected for the terminology
+    /// checker at once.
+    pub(crate) fn check<'a, 's>(
+        &self,
+        documentation: &'a Documentation,
+        selection: &VariantCheckers,
+    ) -> Result<SuggestionSet<'s>>
+    where
+        'a: 's,
+    {
+        let entries = documentation
+            .iter()
+            .flat_map(|(origin, chunks)| chunks.iter().map(move |chunk| (origin, chunk)))
+            .filter(|(_origin, chunk)| selection.is_selected(&chunk.variant(), Self::detector()))
+            .map(|(origin, chunk)| (origin, chunk, chunk.erase_cmark()))
+            .collect::<Vec<(&ContentOrigin, &CheckableChunk, PlainOverlay)>>();
+
+        let mut terms = IndexMap::<String, Vec<Occurrence>>::new();
+        for (entry, (_origin, chunk, plain)) in entries.iter().enumerate() {
+            for candidate in candidates(plain.as_str(), self.spelling_variants)
+                .into_iter()
+                .filter(|candidate| is_contiguous(plain, chunk, candidate.range.clone()))
+            {
+                terms
+                    .entry(candidate.key.clone())
+                    .or_default()
+                    .push(Occurrence { entry, candidate });
+            }
+        }
+
+        // longest first, so overlapping findings can be dropped
+        let mut findings = Vec::new();
+        for (key, occurrences) in &terms {
+            if self.ignore.contains(key) {
+                continue;
+            }
+            if let Some(preference) = self.preference(key, occurrences) {
+                for occurrence in occurrences {
+                    if !is_form_of(&occurrence.candidate.surface, &preference.term) {
+                        trace!(
+                            "Variant {} of {}",
+                            occurrence.candidate.surface,
+                            preference.term
+                        );
+                        findings.push((
+                            occurrence,
+                            preference.term.clone(),
+                            preference.message.clone(),
+                            preference.rule,
+                        ));
+                    }
+                }
+            }
+        }
+        findings.sort_by_key(|(occurrence, ..)| {
+            (
+                occurrence.entry,
+                occurrence.candidate.range.start,
+                std::cmp::Reverse(occurrence.candidate.range.end),
+            )
+        });
+
+        let mut per_origin = IndexMap::<&ContentOrigin, Vec<Suggestion<'s>>>::new();
+        let mut last: Option<(usize, Range)> = None;
+        for (occurrence, term, message, rule) in findings {
+            let range = occurrence.candidate.range.clone();
+            if let Some((entry, ref previous)) = last {
+                if entry == occurrence.entry && range.start < previous.end {
+                    continue;
+                }
+            }
+            last = Some((occurrence.entry, range.clone()));
+
+            let (origin, chunk, plain) = &entries[occurrence.entry];
+            let spans = plain.find_spans(range);
+            // a term split across lines is reported per line, replacing each
+            // part with the whole term would duplicate it
+            let replacements = if spans.len() == 1 {
+                vec![replacement(&term, &occurrence.candidate.surface)]
+            } else {
+                Vec::new()
+            };
+            let rule = RuleId {
+                category: "TERMINOLOGY".to_owned(),
+                id: rule.to_owned(),
+            };
+            per_origin
+                .entry(*origin)
+                .or_default()
+                .extend(spans.into_iter().map(|(range, span)| Suggestion {
+                    detector: Self::detector(),
+                    range,
+                    span,
+                    origin: (*origin).clone(),
+                    replacements: replacements.clone(),
+                    chunk: *chunk,
+                    description: Some(message.clone()),
+                    rule: Some(rule.clone()),
+                    severity: Severity::Error,
+                }));
+        }
+
+        let mut set = SuggestionSet::new();
+        for (origin, mut suggestions) in per_origin {
+            if let Some(chunks) = documentation.get(origin) {
+                for chunk in chunks {
+                    suppress::retain_unsuppressed(chunk, &mut suggestions);
+                }
+            }
+            set.extend(origin.clone(), suggestions);
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::sub_chars;
+
+    #[test]
+    fn spelling_variants() {
+        for (british, american) in &[
+            ("serialise", "serialize"),
+            ("serialisation", "serialization"),
+            ("analysed", "analyzed"),
+            ("behaviour", "behavior"),
+            ("colours", "colors"),
+            ("favourite", "favorite"),
+            ("centre", "center"),
+            ("catalogue", "catalog"),
+            ("cancelled", "canceled"),
+            ("modelling", "modeling"),
+            ("defence", "defense"),
+        ] {
+            assert_eq!(americanize(british), *american);
+            assert_eq!(americanize(american), *american);
+        }
+        for word in &["hour", "four", "rise", "filled", "analyses", "rogue"] {
+            assert_eq!(americanize(word), *word);
+        }
+    }
+
+    #[test]
+    fn candidate_keys() {
+        let keys = |text| {
+            candidates(text, true)
+                .into_iter()
+                .map(|candidate| (candidate.surface, candidate.key))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            keys("A hash-map, a\nhash map."),
+            vec![
+                ("hash".to_owned(), "hash".to_owned()),
+                ("hash-map".to_owned(), "hashmap".to_owned()),
+                ("map".to_owned(), "map".to_owned()),
+                ("hash".to_owned(), "hash".to_owned()),
+                ("hash map".to_owned(), "hashmap".to_owned()),
+                ("map".to_owned(), "map".to_owned()),
+            ]
+        );
+        // stop words and numbers are no terms
+        assert_eq!(
+            keys("into the 42"),
+            vec![("into".to_owned(), "into".to_owned())]
+        );
+    }
+
+    #[test]
+    fn forms() {
+        assert_eq!(canonical("Hashmap"), "hashmap");
+        assert_eq!(canonical("HashMap"), "HashMap");
+        assert_eq!(canonical("Hash map"), "hash map");
+        assert_eq!(canonical("Hash-Map"), "hash-map");
+        assert_eq!(canonical("Hash MAP"), "hash MAP");
+        assert!(is_form_of("Serialize", "serialize"));
+        assert!(!is_form_of("rust", "Rust"));
+        assert_eq!(replacement("hash map", "Hashmap"), "Hash map");
+        assert_eq!(replacement("HashMap", "hashmap"), "HashMap");
+    }
+
+    fn check(config: &TerminologyConfig) -> Vec<(String, String, String)> {
+        let mut docs = Documentation::new();
+        docs.add_rust(
+            ContentOrigin::TestEntityRust,
+            r#"
+/// Stores values in a hash map, to serialize them.
+struct A;
+
+/// Serialize the hash map, then serialise it again.
+struct B;
+"#,
+            false,
+        )
+        .unwrap();
+        docs.add_commonmark(
+            ContentOrigin::TestEntityCommonMark,
+            "# Hash Map Usage\n\nA hashmap is serialized. Wrap it in a\nhash-map <!-- spellcheck:ignore hash-map -->\nor a `hash`map.\n",
+        )
+        .unwrap();
+        let checker = TerminologyChecker::new(config).unwrap();
+        let set = checker.check(&docs, &VariantCheckers::default()).unwrap();
+        set.into_iter()
+            .flat_map(|(_origin, suggestions)| suggestions)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements.first().cloned().unwrap_or_default(),
+                    suggestion.rule.unwrap().id,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn minority_variants() {
+        let found = check(&TerminologyConfig::default());
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "serialise".to_owned(),
+                    "serialize".to_owned(),
+                    "INCONSISTENT".to_owned()
+                ),
+                (
+                    "hashmap".to_owned(),
+                    "hash map".to_owned(),
+                    "INCONSISTENT".to_owned()
+                ),
+            ]
+        );
+
+        let found = check(&TerminologyConfig {
+            spelling_variants: false,
+            ignore: vec!["hash map".to_owned()],
+            ..Default::default()
+        });
+        assert!(found.is_empty());
+    }
+
+    #[test]
+    fn glossary() {
+        let found = check(&TerminologyConfig {
+            glossary: vec!["HashMap".to_owned()],
+            ..Default::default()
+        });
+        assert_eq!(
+            found
+                .iter()
+                .filter(|(_variant, _replacement, rule)| rule == "GLOSSARY")
+                .map(|(variant, replacement, _rule)| (variant.as_str(), replacement.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("hash map", "HashMap"),
+                ("hash map", "HashMap"),
+                ("Hash Map", "HashMap"),
+                ("hashmap", "HashMap"),
+            ]
+        );
+    }
+
+    #[test]
+    fn across_lines() {
+        let mut docs = Documentation::new();
+        docs.add_rust(
+            ContentOrigin::TestEntityRust,
+            "/// Look it up in the hash\n/// map, not in a list.\nstruct A;\n",
+            false,
+        )
+        .unwrap();
+        let checker = TerminologyChecker::new(&TerminologyConfig {
+            glossary: vec!["HashMap".to_owned()],
+            ..Default::default()
+        })
+        .unwrap();
+        let set = checker.check(&docs, &VariantCheckers::default()).unwrap();
+        let found = set
+            .into_iter()
+            .flat_map(|(_origin, suggestions)| suggestions)
+            .map(|suggestion| {
+                (
+                    sub_chars(suggestion.chunk.as_str(), suggestion.range.clone()),
+                    suggestion.replacements,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![("hash".to_owned(), vec![]), ("map".to_owned(), vec![])]
+        );
+    }
+}
@@ -0,0 +1,363 @@
+//! Mistakes which can be found without any dictionary.
+//!
+//! Reports doubled words, also across lines, `a` and `an` not matching the
+//! sound of the following word, and common misspellings from a bundled list.
+//! All of them operate on the plain text, so code is never reported.
+
+use super::misspellings::MISSPELLINGS;
+use super::{is_contiguous, Checker, Detector, Suggestion};
+use crate::config::TyposConfig;
+use crate::documentation::PlainOverlay;
+use crate::util::byte_range_to_char_range;
+use crate::{CheckableChunk, ContentOrigin, Range, RuleId, Severity, Span};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use log::trace;
+use regex::{Match, Regex};
+use std::collections::HashSet;
+
+/// Words which are commonly correct when doubled, i.e. `that that`.
+const REPEATABLE: &[&str] = &["had", "that"];
+
+/// Beginnings of words starting with a vowel, but not with a vowel sound.
+const CONSONANT_SOUND: &[&str] = &[
+    "eu", "ewe", "ubiq", "uni", "ura", "uri", "uro", "usa", "use", "usu", "uti",
+];
+
+/// Beginnings of words starting with a consonant, but with a vowel sound, and
+/// exceptions of the above.
+const VOWEL_SOUND: &[&str] = &["heir", "honest", "honor", "honour", "hour", "unim", "unin"];
+
+lazy_static! {
+    static ref WORD: Regex = Regex::new(r"\b\p{Alphabetic}+(?:'\p{Alphabetic}+)?\b").unwrap();
+}
+
+pub(crate) struct TyposChecker {
+    config: TyposConfig,
+    /// Lowercase words never reported as misspelling.
+    ignore: HashSet<String>,
+}
+
+impl Checker for TyposChecker {
+    type Config = TyposConfig;
+
+    fn detector() -> Detector {
+        Detector::Typos
+    }
+
+    fn new(config: &Self::Config) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+            ignore: config
+                .ignore
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect(),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
+    where
+        'a: 's,
+    {
+        let mut acc = Vec::new();
+        for chunk in chunks {
+            let plain = chunk.erase_cmark();
+            trace!("{:?}", &plain);
+            let mut report = |spans: IndexMap<Range, Span>,
+                              id: &str,
+                              description: String,
+                              replacements: Vec<String>| {
+                acc.extend(spans.into_iter().map(|(range, span)| Suggestion {
+                    detector: Detector::Typos,
+                    range,
+                    span,
+                    origin: origin.clone(),
+                    replacements: replacements.clone(),
+                    chunk,
+                    description: Some(description.clone()),
+                    rule: Some(RuleId {
+                        category: "TYPOS".to_owned(),
+                        id: id.to_owned(),
+                    }),
+                    severity: Severity::Error,
+                }))
+            };
+
+            if self.config.doubled_words {
+                for (word, spans, fixable) in doubled_words(&plain, chunk) {
+                    let replacements = if fixable {
+                        vec![word.to_owned()]
+                    } else {
+                        Vec::new()
+                    };
+                    report(
+                        spans,
+                        "DOUBLED_WORD",
+                        format!("The word `{}` is doubled", word),
+                        replacements,
+                    );
+                }
+            }
+            if self.config.a_an {
+                for (spans, article) in articles(&plain, chunk) {
+                    report(
+                        spans,
+                        "A_AN",
+                        format!("Use `{}` before the sound of the next word", article),
+                        vec![article],
+                    );
+                }
+            }
+            if self.config.misspellings {
+                for (spans, correction) in self.misspellings(&plain) {
+                    report(
+                        spans,
+                        "MISSPELLING",
+                        format!("Common misspelling of `{}`", correction),
+                        vec![correction],
+                    );
+                }
+            }
+        }
+        Ok(acc)
+    }
+}
+
+impl TyposChecker {
+    /// Words on the bundled list, with their correction in the same case.
+    fn misspellings(&self, plain: &PlainOverlay) -> Vec<(IndexMap<Range, Span>, String)> {
+        let text = plain.as_str();
+        WORD.find_iter(text)
+            .filter_map(|word| {
+                let lowercase = word.as_str().to_lowercase();
+                if self.ignore.contains(&lowercase) {
+                    return None;
+                }
+                let idx = MISSPELLINGS
+                    .binary_search_by_key(&lowercase.as_str(), |(misspelling, _)| misspelling)
+                    .ok()?;
+                let range = byte_range_to_char_range(text, word.range())?;
+                Some((
+                    plain.find_spans(range),
+                    same_case(MISSPELLINGS[idx].1, word.as_str()),
+                ))
+            })
+            .collect()
+    }
+}
+
+/// Pairs of consecutive words, separated by nothing but whitespace in the
+/// chunk, with their plain text range.
+fn pairs<'p>(
+    plain: &'p PlainOverlay,
+    chunk: &CheckableChunk,
+) -> Vec<(Match<'p>, Match<'p>, Range)> {
+    let text = plain.as_str();
+    let words = WORD.find_iter(text).collect::<Vec<_>>();
+    words
+        .windows(2)
+        .filter(|pair| {
+            text[pair[0].end()..pair[1].start()]
+                .chars()
+                .all(char::is_whitespace)
+        })
+        .filter_map(|pair| {
+            let range = byte_range_to_char_range(text, pair[0].start()..pair[1].end())?;
+            if is_contiguous(plain, chunk, range.clone()) {
+                Some((pair[0], pair[1], range))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// The same word twice in a row, also across lines, i.e. `the the`.
+///
+/// Yields the word and the spans to report. If the doubled words are on the
+/// same line, the spans cover both and can be replaced by the word, otherwise
+/// they only cover the second one.
+fn doubled_words<'p>(
+    plain: &'p PlainOverlay,
+    chunk: &CheckableChunk,
+) -> Vec<(&'p str, IndexMap<Range, Span>, bool)> {
+    let text = plain.as_str();
+    pairs(plain, chunk)
+        .into_iter()
+        .filter(|(first, second, _range)| {
+            let word = first.as_str().to_lowercase();
+            word == second.as_str().to_lowercase() && !REPEATABLE.contains(&word.as_str())
+        })
+        .filter_map(|(first, second, range)| {
+            let spans = plain.find_spans(range);
+            if spans.len() == 1 {
+                return Some((first.as_str(), spans, true));
+            }
+            let range = byte_range_to_char_range(text, second.range())?;
+            Some((first.as_str(), plain.find_spans(range), false))
+        })
+        .collect()
+}
+
+/// Articles not matching the sound of the following word, with the spans of
+/// the article and the correct one.
+fn articles(plain: &PlainOverlay, chunk: &CheckableChunk) -> Vec<(IndexMap<Range, Span>, String)> {
+    let text = plain.as_str();
+    pairs(plain, chunk)
+        .into_iter()
+        .filter_map(|(article, word, _range)| {
+            let is_an = match article.as_str() {
+                "a" | "A" => false,
+                "an" | "An" | "AN" => true,
+                _ => return None,
+            };
+            // acronyms, names like `mdBook` and single letters are often
+            // pronounced by the names of their letters, which is not covered
+            let mut chars = word.as_str().chars().skip(1).peekable();
+            if chars.peek().is_none() || chars.any(char::is_uppercase) {
+                return None;
+            }
+            let vowel = has_vowel_sound(word.as_str());
+            if vowel == is_an {
+                return None;
+            }
+            let correct = if vowel { "an" } else { "a" };
+            let range = byte_range_to_char_range(text, article.range())?;
+            Some((
+                plain.find_spans(range),
+                same_case(correct, article.as_str()),
+            ))
+        })
+        .collect()
+}
+
+/// Check if the pronunciation of `word` starts with a vowel sound.
+fn has_vowel_sound(word: &str) -> bool {
+    let word = word.to_lowercase();
+    if VOWEL_SOUND.iter().any(|start| word.starts_with(start)) {
+        return true;
+    }
+    if word == "one"
+        || word == "once"
+        || CONSONANT_SOUND.iter().any(|start| word.starts_with(start))
+    {
+        return false;
+    }
+    word.starts_with(|c| "aeiou".contains(c))
+}
+
+/// Adjust the case of the lowercase `word` to the one of `original`.
+fn same_case(word: &str, original: &str) -> String {
+    let mut chars = original.chars();
+    match chars.next() {
+        Some(first) if first.is_uppercase() => {
+            if original.chars().count() > 1 && chars.all(char::is_uppercase) {
+                word.to_uppercase()
+            } else {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        }
+        _ => word.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checker::tests::check_rust;
+
+    fn check(source: &str) -> Vec<(String, String, Vec<String>, Severity)> {
+        let config = TyposConfig {
+            ignore: vec!["Wich".to_owned()],
+            ..Default::default()
+        };
+        check_rust(&TyposChecker::new(&config).unwrap(), source)
+    }
+
+    #[test]
+    fn bundled_list_is_sorted() {
+        assert!(MISSPELLINGS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(MISSPELLINGS
+            .iter()
+            .all(
+                |(misspelling, correction)| misspelling.to_lowercase() == *misspelling
+                    && misspelling != correction
+            ));
+    }
+
+    #[test]
+    fn doubled_across_lines() {
+        assert_eq!(
+            check("/// Adds the the value to the\n/// the set, not `the` the code.\nstruct X;"),
+            vec![
+                (
+                    "TYPOS/DOUBLED_WORD".to_owned(),
+                    "the the".to_owned(),
+                    vec!["the".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "TYPOS/DOUBLED_WORD".to_owned(),
+                    "the".to_owned(),
+                    vec![],
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_an() {
+        assert_eq!(
+            check(
+                "/// A iterator over an user, an hour, a uninitialized and an unique\n/// value, a `Vec` of an URL, an one-off and A enum in an mdBook.\nstruct X;"
+            ),
+            vec![
+                ("TYPOS/A_AN".to_owned(), "A".to_owned(), vec!["An".to_owned()], Severity::Error),
+                ("TYPOS/A_AN".to_owned(), "an".to_owned(), vec!["a".to_owned()], Severity::Error),
+                ("TYPOS/A_AN".to_owned(), "a".to_owned(), vec!["an".to_owned()], Severity::Error),
+                ("TYPOS/A_AN".to_owned(), "an".to_owned(), vec!["a".to_owned()], Severity::Error),
+                ("TYPOS/A_AN".to_owned(), "an".to_owned(), vec!["a".to_owned()], Severity::Error),
+                ("TYPOS/A_AN".to_owned(), "A".to_owned(), vec!["An".to_owned()], Severity::Error),
+            ]
+        );
+    }
+
+    #[test]
+    fn misspellings() {
+        assert_eq!(
+            check("/// Seperate the ARGUEMENTS, wich are `recieve`d,\n/// alot.\nstruct X;"),
+            vec![
+                (
+                    "TYPOS/MISSPELLING".to_owned(),
+                    "Seperate".to_owned(),
+                    vec!["Separate".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "TYPOS/MISSPELLING".to_owned(),
+                    "ARGUEMENTS".to_owned(),
+                    vec!["ARGUMENTS".to_owned()],
+                    Severity::Error
+                ),
+                (
+                    "TYPOS/MISSPELLING".to_owned(),
+                    "alot".to_owned(),
+                    vec!["a lot".to_owned()],
+                    Severity::Error
+                ),
+            ]
+        );
+    }
+}
@@ -1,236 +1,850 @@
+//! Command line arguments.
+//!
+//! Every configuration value can be overridden by a flag or an environment
+//! variable, the layers are merged in the order defaults, configuration file,
+//! environment variables and command line flags.
+
 use std::path::PathBuf;
 
-use anyhow::{bail, Result};
-use docopt::Docopt;
+use anyhow::{anyhow, bail, Result};
+use clap::builder::BoolishValueParser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{
+    ArgAction, ArgGroup, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
 
 use crate::traverse;
-use itertools::Itertools;
-use serde::Deserialize;
-
-use crate::Action;
+use crate::{Action, Detector, Severity};
 
-use super::Config;
+use super::{
+    Config, HunspellConfig, LanguageToolConfig, NlpRulesConfig, Provenance, ReflowStrategy,
+    RustdocConfig, TerminologyConfig, TyposConfig, WrappedRegex,
+};
 
 use log::{debug, info, warn};
 
-/// Docopt usage string.
-const USAGE: &str = r#"
-Spellcheck all your doc comments
-
-Usage:
-    cargo-spellcheck [(-v...|-q)] fix [--cfg=<cfg>] [--code=<code>] [--dev-comments] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
-    cargo-spellcheck [(-v...|-q)] reflow [--cfg=<cfg>] [--code=<code>] [--dev-comments] [--skip-readme] [[--recursive] <paths>... ]
-    cargo-spellcheck [(-v...|-q)] config (--user|--stdout|--cfg=<cfg>) [--checkers=<checkers>] [--force]
-    cargo-spellcheck [(-v...|-q)] [check] [--fix] [--cfg=<cfg>] [--code=<code>] [--dev-comments] [--skip-readme] [--checkers=<checkers>] [[--recursive] <paths>... ]
-    cargo-spellcheck --version
-    cargo-spellcheck --help
-
-Options:
-  -h --help                 Show this screen.
-  --version                 Print the version and exit.
-
-  --fix                     Interactively apply spelling and grammer fixes, synonym to `fix` sub-command.
-  -r --recursive            If a path is provided, if recursion into subdirectories is desired.
-  --checkers=<checkers>     Calculate the intersection between
-                            configured by config file and the ones provided on commandline.
-  -f --force                Overwrite any existing configuration file. [default=false]
-  -c --cfg=<cfg>            Use a non default configuration file.
-                            Passing a directory will attempt to open `cargo_spellcheck.toml` in that directory.
-  --user                    Write the configuration file to the default user configuration directory.
-  --stdout                  Print the configuration file to stdout and exit.
-  -v --verbose              Verbosity level.
-  -q --quiet                Silences all printed messages. Overrules `-v`.
-  -m --code=<code>          Overwrite the exit value for a successful run with content mistakes found. [default=0]
-  --skip-readme             Do not attempt to process README.md files listed in Cargo.toml manifests.
-"#;
-
 /// Checker types to be derived from the stringly typed arguments.
-#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, ValueEnum)]
 pub enum CheckerType {
-    #[serde(alias = "hunspell")]
-    #[serde(alias = "hunSpell")]
+    #[value(name = "hunspell")]
     Hunspell,
-    #[serde(alias = "nlprules")]
-    #[serde(alias = "nlpRules")]
+    #[value(name = "nlprules", alias = "nlp-rules")]
     NlpRules,
-    #[serde(alias = "languageTool")]
-    #[serde(alias = "Languagetool")]
-    #[serde(alias = "languagetool")]
+    #[value(name = "languagetool", alias = "language-tool")]
     LanguageTool,
-    #[serde(alias = "ReFlow")]
-    #[serde(alias = "reflow")]
+    #[value(name = "reflow")]
     Reflow,
+    #[value(name = "custom-rules", alias = "customrules")]
+    CustomRules,
+    #[value(name = "terminology")]
+    Terminology,
+    #[value(name = "rustdoc")]
+    Rustdoc,
+    #[value(name = "typos")]
+    Typos,
+    #[value(name = "native-hunspell", alias = "nativehunspell")]
+    NativeHunspell,
+}
+
+impl From<CheckerType> for Detector {
+    fn from(checker: CheckerType) -> Self {
+        match checker {
+            CheckerType::Hunspell => Detector::Hunspell,
+            CheckerType::NlpRules => Detector::NlpRules,
+            CheckerType::LanguageTool => Detector::LanguageTool,
+            CheckerType::Reflow => Detector::Reflow,
+            CheckerType::CustomRules => Detector::CustomRules,
+            CheckerType::Terminology => Detector::Terminology,
+            CheckerType::Rustdoc => Detector::Rustdoc,
+            CheckerType::Typos => Detector::Typos,
+            CheckerType::NativeHunspell => Detector::NativeHunspell,
+        }
+    }
+}
+
+/// Parse a checker type, ignoring the case.
+fn parse_checker_type(s: &str) -> Result<CheckerType> {
+    CheckerType::from_str(s.trim(), true).map_err(|e| anyhow!("Unknown checker {}: {}", s, e))
+}
+
+/// Parse a checker to run on its own, which excludes reflow.
+fn parse_checker(s: &str) -> Result<CheckerType> {
+    match parse_checker_type(s)? {
+        CheckerType::Reflow => {
+            bail!("Reflow is not a checker, use the `reflow` subcommand instead")
+        }
+        checker => Ok(checker),
+    }
+}
+
+/// Parse a regular expression used for the hunspell quirks.
+fn parse_regex(s: &str) -> Result<WrappedRegex> {
+    Ok(WrappedRegex(fancy_regex::Regex::new(s)?))
+}
+
+/// Selection of checkers for one comment variant, i.e. `dev=hunspell,nlprules`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariantSelection {
+    pub variant: String,
+    pub checkers: Vec<CheckerType>,
+}
+
+impl std::str::FromStr for VariantSelection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (variant, checkers) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("Expected `<variant>=<checker>[,<checker>..]`, got {}", s))?;
+        let variant = variant.trim().to_owned();
+        if super::VariantCheckers::default()
+            .slot_mut(variant.as_str())
+            .is_none()
+        {
+            bail!("Unknown comment variant {}", variant)
+        }
+        let checkers = checkers
+            .split(',')
+            .filter(|checker| !checker.trim().is_empty())
+            .map(parse_checker_type)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { variant, checkers })
+    }
+}
+
+/// Overrides for the values of the configuration file.
+///
+/// All of these can also be provided via `CARGO_SPELLCHECK_*` environment
+/// variables, flags take precedence.
+#[derive(Debug, Clone, Default, clap::Args)]
+#[command(next_help_heading = "Configuration overrides")]
+pub struct ConfigOverrides {
+    /// Also check developer comments, `//` and `/* */`.
+    #[arg(long, env = "CARGO_SPELLCHECK_DEV_COMMENTS", value_name = "BOOL")]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub dev_comments: Option<bool>,
+
+    /// Do not attempt to process README.md files listed in Cargo.toml manifests.
+    #[arg(long, env = "CARGO_SPELLCHECK_SKIP_README", value_name = "BOOL")]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub skip_readme: Option<bool>,
+
+    /// Keep a copy of each modified file with an additional `.orig` extension.
+    #[arg(long, env = "CARGO_SPELLCHECK_BACKUP", value_name = "BOOL")]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub backup: Option<bool>,
+
+    /// Only use the given checkers, the ones missing in the
+    /// configuration file are used with their defaults.
+    #[arg(long, env = "CARGO_SPELLCHECK_CHECKERS", value_delimiter = ',')]
+    #[arg(value_parser = parse_checker)]
+    pub checkers: Option<Vec<CheckerType>>,
+
+    /// Restrict the checkers for a comment variant, i.e. `dev=hunspell`.
+    /// An empty list skips the variant.
+    #[arg(long = "variant-checkers", env = "CARGO_SPELLCHECK_VARIANT_CHECKERS")]
+    #[arg(value_name = "VARIANT=CHECKERS", value_delimiter = ';')]
+    pub variant_checkers: Vec<VariantSelection>,
+
+    /// The language to check against, the name of the `.dic` and `.aff` files.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANG")]
+    pub lang: Option<String>,
+
+    /// Additional dictionary search dirs, take precedence over the configured ones.
+    #[arg(
+        long = "search-dir",
+        env = "CARGO_SPELLCHECK_SEARCH_DIRS",
+        value_delimiter = ','
+    )]
+    pub search_dirs: Vec<PathBuf>,
+
+    /// Additional dictionaries, relative paths are relative to the current dir.
+    #[arg(long = "extra-dictionary", env = "CARGO_SPELLCHECK_EXTRA_DICTIONARIES")]
+    #[arg(value_delimiter = ',')]
+    pub extra_dictionaries: Vec<PathBuf>,
+
+    /// Additional transform regular expression for the hunspell quirks.
+    #[arg(long = "transform-regex", env = "CARGO_SPELLCHECK_TRANSFORM_REGEX")]
+    #[arg(value_parser = parse_regex)]
+    pub transform_regex: Vec<WrappedRegex>,
+
+    /// Accept `alphabeta` if a suggested replacement is `alpha-beta`.
+    #[arg(
+        long,
+        env = "CARGO_SPELLCHECK_ALLOW_CONCATENATION",
+        value_name = "BOOL"
+    )]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub allow_concatenation: Option<bool>,
+
+    /// Accept `alpha-beta` if a suggested replacement is `alphabeta`.
+    #[arg(long, env = "CARGO_SPELLCHECK_ALLOW_DASHES", value_name = "BOOL")]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub allow_dashes: Option<bool>,
+
+    /// Also use the nlprules of these categories, which are disabled by default.
+    #[arg(long, env = "CARGO_SPELLCHECK_NLPRULES_ENABLE_CATEGORIES")]
+    #[arg(value_name = "CATEGORIES", value_delimiter = ',')]
+    pub nlprules_enable_categories: Vec<String>,
+
+    /// Do not use the nlprules of these categories.
+    #[arg(long, env = "CARGO_SPELLCHECK_NLPRULES_DISABLE_CATEGORIES")]
+    #[arg(value_name = "CATEGORIES", value_delimiter = ',')]
+    pub nlprules_disable_categories: Vec<String>,
+
+    /// Do not use the nlprules with these ids or rule group ids.
+    #[arg(long, env = "CARGO_SPELLCHECK_NLPRULES_DISABLE_RULES")]
+    #[arg(value_name = "RULES", value_delimiter = ',')]
+    pub nlprules_disable_rules: Vec<String>,
+
+    /// Only use the nlprules with these ids or categories.
+    #[arg(long, env = "CARGO_SPELLCHECK_NLPRULES_ENABLE_ONLY")]
+    #[arg(value_name = "RULES", value_delimiter = ',')]
+    pub nlprules_enable_only: Option<Vec<String>>,
+
+    /// Binary encoded rules to use instead of the builtin ones.
+    #[arg(long, env = "CARGO_SPELLCHECK_OVERRIDE_RULES")]
+    pub override_rules: Option<PathBuf>,
+
+    /// Binary encoded tokenizer to use instead of the builtin one.
+    #[arg(long, env = "CARGO_SPELLCHECK_OVERRIDE_TOKENIZER")]
+    pub override_tokenizer: Option<PathBuf>,
+
+    /// Url of the languagetool server, enables the languagetool checker.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANGUAGETOOL_URL")]
+    pub languagetool_url: Option<url::Url>,
+
+    /// The language code languagetool checks against, i.e. `en-GB`.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANGUAGETOOL_LANGUAGE")]
+    #[arg(value_name = "LANGUAGE")]
+    pub languagetool_language: Option<String>,
+
+    /// Languagetool rule ids to use in addition to the default ones.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANGUAGETOOL_ENABLED_RULES")]
+    #[arg(value_name = "RULES", value_delimiter = ',')]
+    pub languagetool_enabled_rules: Vec<String>,
+
+    /// Languagetool rule ids to not use.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANGUAGETOOL_DISABLED_RULES")]
+    #[arg(value_name = "RULES", value_delimiter = ',')]
+    pub languagetool_disabled_rules: Vec<String>,
+
+    /// Upper limit of requests sent to the languagetool server per second.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANGUAGETOOL_REQUESTS_PER_SECOND")]
+    #[arg(value_name = "RATE")]
+    pub languagetool_requests_per_second: Option<f64>,
+
+    /// How often a failed request to the languagetool server is retried.
+    #[arg(long, env = "CARGO_SPELLCHECK_LANGUAGETOOL_RETRIES")]
+    #[arg(value_name = "RETRIES")]
+    pub languagetool_retries: Option<usize>,
+
+    /// Maximum line length for reflowing comments.
+    #[arg(long, env = "CARGO_SPELLCHECK_MAX_LINE_LENGTH")]
+    pub max_line_length: Option<usize>,
+
+    /// Line breaking strategy for reflowing comments.
+    #[arg(
+        long,
+        env = "CARGO_SPELLCHECK_REFLOW_STRATEGY",
+        value_name = "STRATEGY"
+    )]
+    pub reflow_strategy: Option<ReflowStrategy>,
+
+    /// Number of columns between two tab stops for reflowing comments.
+    #[arg(long, env = "CARGO_SPELLCHECK_TAB_WIDTH")]
+    pub tab_width: Option<usize>,
+
+    /// Limit for the width of reflowed comments, excluding the indentation.
+    #[arg(long, env = "CARGO_SPELLCHECK_COMMENT_WIDTH")]
+    pub comment_width: Option<usize>,
+
+    /// Reflow with the widths of the `rustfmt.toml` closest to each rust file.
+    #[arg(long, env = "CARGO_SPELLCHECK_RUSTFMT", value_name = "BOOL")]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub rustfmt: Option<bool>,
+
+    /// Also reflow developer comments, given `--dev-comments` are checked.
+    #[arg(
+        long,
+        env = "CARGO_SPELLCHECK_REFLOW_DEV_COMMENTS",
+        value_name = "BOOL"
+    )]
+    #[arg(num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    #[arg(value_parser = BoolishValueParser::new())]
+    pub reflow_dev_comments: Option<bool>,
+
+    /// Fail if there is a finding at least this severe, i.e. `--deny=warnings`.
+    #[arg(long, env = "CARGO_SPELLCHECK_DENY", value_name = "SEVERITY")]
+    pub deny: Option<Severity>,
+}
+
+impl ConfigOverrides {
+    /// The dotted configuration keys affected by the overrides, by argument id.
+    const KEYS: &'static [(&'static str, &'static str)] = &[
+        ("dev_comments", "dev_comments"),
+        ("skip_readme", "skip_readme"),
+        ("backup", "backup"),
+        ("checkers", "hunspell"),
+        ("checkers", "nlprules"),
+        ("checkers", "languagetool"),
+        ("checkers", "custom_rules"),
+        ("checkers", "terminology"),
+        ("checkers", "rustdoc"),
+        ("checkers", "typos"),
+        ("checkers", "native_hunspell"),
+        ("variant_checkers", "variant_checkers.*"),
+        ("lang", "hunspell.lang"),
+        ("search_dirs", "hunspell.search_dirs"),
+        ("extra_dictionaries", "hunspell.extra_dictionaries"),
+        ("transform_regex", "hunspell.quirks.transform_regex"),
+        ("allow_concatenation", "hunspell.quirks.allow_concatenation"),
+        ("allow_dashes", "hunspell.quirks.allow_dashes"),
+        ("nlprules_enable_categories", "nlprules.enable_categories"),
+        ("nlprules_disable_categories", "nlprules.disable_categories"),
+        ("nlprules_disable_rules", "nlprules.disable_rules"),
+        ("nlprules_enable_only", "nlprules.enable_only"),
+        ("override_rules", "nlprules.override_rules"),
+        ("override_tokenizer", "nlprules.override_tokenizer"),
+        ("languagetool_url", "languagetool"),
+        ("languagetool_url", "languagetool.url"),
+        ("languagetool_language", "languagetool.language"),
+        ("languagetool_enabled_rules", "languagetool.enabled_rules"),
+        ("languagetool_disabled_rules", "languagetool.disabled_rules"),
+        (
+            "languagetool_requests_per_second",
+            "languagetool.requests_per_second",
+        ),
+        ("languagetool_retries", "languagetool.retries"),
+        ("max_line_length", "reflow.max_line_length"),
+        ("reflow_strategy", "reflow.strategy"),
+        ("tab_width", "reflow.tab_width"),
+        ("comment_width", "reflow.comment_width"),
+        ("rustfmt", "reflow.rustfmt"),
+        ("reflow_dev_comments", "reflow.dev_comments"),
+        ("deny", "severity.fail_on"),
+    ];
+
+    /// Collect the origin of all overrides set by a flag or an environment variable
+    /// for the innermost sub command.
+    fn sources(cmd: &clap::Command, matches: &ArgMatches) -> Vec<(&'static str, Provenance)> {
+        if let Some((name, sub_matches)) = matches.subcommand() {
+            if let Some(sub_cmd) = cmd.find_subcommand(name) {
+                return Self::sources(sub_cmd, sub_matches);
+            }
+        }
+        let mut sources = Vec::new();
+        for (id, key) in Self::KEYS {
+            let arg = if let Some(arg) = cmd.get_arguments().find(|arg| arg.get_id() == id) {
+                arg
+            } else {
+                continue;
+            };
+            let provenance = match matches.value_source(id) {
+                Some(ValueSource::CommandLine) => {
+                    Provenance::Cli(format!("--{}", arg.get_long().unwrap_or(id)))
+                }
+                Some(ValueSource::EnvVariable) => Provenance::Env(
+                    arg.get_env()
+                        .map(|var| var.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                ),
+                _ => continue,
+            };
+            sources.push((*key, provenance));
+        }
+        sources
+    }
+
+    /// Apply the overrides on top of the values in `config`.
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        if let Some(dev_comments) = self.dev_comments {
+            config.dev_comments = dev_comments;
+        }
+        if let Some(skip_readme) = self.skip_readme {
+            config.skip_readme = skip_readme;
+        }
+        if let Some(backup) = self.backup {
+            config.backup = backup;
+        }
+
+        // mask all disabled checkers, use the default config
+        // for those which have one if not enabled already
+        if let Some(filter_set) = &self.checkers {
+            // uses the dictionaries of hunspell, unless configured on its own
+            if filter_set.contains(&CheckerType::NativeHunspell) {
+                if config.native_hunspell.is_none() {
+                    config.native_hunspell = Some(config.hunspell.clone().unwrap_or_default());
+                }
+            } else {
+                config.native_hunspell = None;
+            }
+            if filter_set.contains(&CheckerType::Hunspell) {
+                if config.hunspell.is_none() {
+                    config.hunspell = Some(HunspellConfig::default());
+                }
+            } else {
+                config.hunspell = None;
+            }
+            if filter_set.contains(&CheckerType::NlpRules) {
+                if config.nlprules.is_none() {
+                    config.nlprules = Some(NlpRulesConfig::default());
+                }
+            } else {
+                config.nlprules = None;
+            }
+            if filter_set.contains(&CheckerType::LanguageTool) {
+                if config.languagetool.is_none() && self.languagetool_url.is_none() {
+                    warn!("Language tool must be explicitly configured via config file or `--languagetool-url`")
+                }
+            } else {
+                config.languagetool = None;
+            }
+            if filter_set.contains(&CheckerType::CustomRules) {
+                if config.custom_rules.is_none() {
+                    warn!("Custom rules must be configured via config file to report anything")
+                }
+            } else {
+                config.custom_rules = None;
+            }
+            if filter_set.contains(&CheckerType::Terminology) {
+                if config.terminology.is_none() {
+                    config.terminology = Some(TerminologyConfig::default());
+                }
+            } else {
+                config.terminology = None;
+            }
+            if filter_set.contains(&CheckerType::Rustdoc) {
+                if config.rustdoc.is_none() {
+                    config.rustdoc = Some(RustdocConfig::default());
+                }
+            } else {
+                config.rustdoc = None;
+            }
+            if filter_set.contains(&CheckerType::Typos) {
+                if config.typos.is_none() {
+                    config.typos = Some(TyposConfig::default());
+                }
+            } else {
+                config.typos = None;
+            }
+        }
+
+        for selection in &self.variant_checkers {
+            let slot = config
+                .variant_checkers
+                .slot_mut(selection.variant.as_str())
+                .expect("Variant names are validated when parsing. qed");
+            *slot = Some(
+                selection
+                    .checkers
+                    .iter()
+                    .copied()
+                    .map(Detector::from)
+                    .collect(),
+            );
+        }
+
+        let cwd = traverse::cwd()?;
+
+        let mut dictionaries = config
+            .hunspell
+            .iter_mut()
+            .chain(config.native_hunspell.iter_mut())
+            .peekable();
+        if dictionaries.peek().is_none()
+            && (self.lang.is_some()
+                || !self.search_dirs.is_empty()
+                || !self.extra_dictionaries.is_empty()
+                || !self.transform_regex.is_empty()
+                || self.allow_concatenation.is_some()
+                || self.allow_dashes.is_some())
+        {
+            warn!("Hunspell is disabled, ignoring the hunspell specific overrides.")
+        }
+        for hunspell in dictionaries {
+            if let Some(lang) = &self.lang {
+                hunspell.lang = Some(lang.clone());
+            }
+            if !self.search_dirs.is_empty() {
+                let search_dirs = self
+                    .search_dirs
+                    .iter()
+                    .map(|search_dir| cwd.join(search_dir))
+                    .chain(hunspell.search_dirs.iter().cloned())
+                    .collect::<Vec<_>>();
+                hunspell.search_dirs = search_dirs.into();
+            }
+            hunspell.extra_dictionaries.extend(
+                self.extra_dictionaries
+                    .iter()
+                    .map(|extra_dic| cwd.join(extra_dic)),
+            );
+            let quirks = &mut hunspell.quirks;
+            quirks
+                .transform_regex
+                .extend(self.transform_regex.iter().cloned());
+            if let Some(allow_concatenation) = self.allow_concatenation {
+                quirks.allow_concatenation = allow_concatenation;
+            }
+            if let Some(allow_dashes) = self.allow_dashes {
+                quirks.allow_dashes = allow_dashes;
+            }
+        }
+
+        if let Some(nlprules) = config.nlprules.as_mut() {
+            if let Some(override_rules) = &self.override_rules {
+                nlprules.override_rules = Some(cwd.join(override_rules));
+            }
+            if let Some(override_tokenizer) = &self.override_tokenizer {
+                nlprules.override_tokenizer = Some(cwd.join(override_tokenizer));
+            }
+            nlprules
+                .enable_categories
+                .extend(self.nlprules_enable_categories.iter().cloned());
+            nlprules
+                .disable_categories
+                .extend(self.nlprules_disable_categories.iter().cloned());
+            nlprules
+                .disable_rules
+                .extend(self.nlprules_disable_rules.iter().cloned());
+            if let Some(enable_only) = &self.nlprules_enable_only {
+                nlprules.enable_only = Some(enable_only.clone());
+            }
+        } else if self.override_rules.is_some()
+            || self.override_tokenizer.is_some()
+            || !self.nlprules_enable_categories.is_empty()
+            || !self.nlprules_disable_categories.is_empty()
+            || !self.nlprules_disable_rules.is_empty()
+            || self.nlprules_enable_only.is_some()
+        {
+            warn!("NlpRules is disabled, ignoring the nlprules specific overrides.")
+        }
+
+        if let Some(url) = &self.languagetool_url {
+            let selected = match &self.checkers {
+                Some(checkers) => checkers.contains(&CheckerType::LanguageTool),
+                None => true,
+            };
+            if selected {
+                if let Some(languagetool) = config.languagetool.as_mut() {
+                    languagetool.url = url.clone();
+                } else {
+                    config.languagetool = Some(LanguageToolConfig::from_url(url.clone()));
+                }
+            }
+        }
+
+        if let Some(languagetool) = config.languagetool.as_mut() {
+            if let Some(language) = &self.languagetool_language {
+                languagetool.language = language.clone();
+            }
+            languagetool
+                .enabled_rules
+                .extend(self.languagetool_enabled_rules.iter().cloned());
+            languagetool
+                .disabled_rules
+                .extend(self.languagetool_disabled_rules.iter().cloned());
+            if let Some(requests_per_second) = self.languagetool_requests_per_second {
+                languagetool.requests_per_second = Some(requests_per_second);
+            }
+            if let Some(retries) = self.languagetool_retries {
+                languagetool.retries = retries;
+            }
+        } else if self.languagetool_language.is_some()
+            || !self.languagetool_enabled_rules.is_empty()
+            || !self.languagetool_disabled_rules.is_empty()
+            || self.languagetool_requests_per_second.is_some()
+            || self.languagetool_retries.is_some()
+        {
+            warn!("Language tool is disabled, ignoring the languagetool specific overrides.")
+        }
+
+        if let Some(max_line_length) = self.max_line_length {
+            config
+                .reflow
+                .get_or_insert_with(Default::default)
+                .max_line_length = max_line_length;
+        }
+        if let Some(strategy) = self.reflow_strategy {
+            config.reflow.get_or_insert_with(Default::default).strategy = strategy;
+        }
+        if let Some(tab_width) = self.tab_width {
+            config.reflow.get_or_insert_with(Default::default).tab_width = tab_width;
+        }
+        if let Some(comment_width) = self.comment_width {
+            config
+                .reflow
+                .get_or_insert_with(Default::default)
+                .comment_width = Some(comment_width);
+        }
+        if let Some(rustfmt) = self.rustfmt {
+            config.reflow.get_or_insert_with(Default::default).rustfmt = rustfmt;
+        }
+        if let Some(dev_comments) = self.reflow_dev_comments {
+            config
+                .reflow
+                .get_or_insert_with(Default::default)
+                .dev_comments = dev_comments;
+        }
+        if let Some(deny) = self.deny {
+            config.severity.fail_on = Some(deny);
+        }
+        Ok(())
+    }
+}
+
+/// Arguments shared by all sub commands operating on files.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct OperateArgs {
+    /// If a path is provided, if recursion into subdirectories is desired.
+    #[arg(short, long)]
+    pub recursive: bool,
+
+    /// Use a non default configuration file.
+    #[arg(short, long, env = "CARGO_SPELLCHECK_CONFIG", value_name = "CFG")]
+    pub cfg: Option<PathBuf>,
+
+    /// Overwrite the exit value for a successful run with content mistakes found.
+    #[arg(short = 'm', long, default_value_t = 0)]
+    pub code: u8,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+
+    /// Files or directories to check, the current dir if none given.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Arguments of the check sub command, also used when no sub command is given.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct CheckArgs {
+    /// Interactively apply spelling and grammar fixes, synonym to the `fix` sub command.
+    #[arg(long)]
+    pub fix: bool,
+
+    #[command(flatten)]
+    pub operate: OperateArgs,
 }
 
-#[derive(Debug, Deserialize, Default)]
+/// Arguments of the reflow sub command.
+#[derive(Debug, Clone, Default, clap::Args)]
+pub struct ReflowArgs {
+    /// Only report the paragraphs which would be reflowed and lines which are
+    /// too long, without modifying any file. Exits with a non-zero code if any
+    /// are found.
+    #[arg(long)]
+    pub check: bool,
+
+    #[command(flatten)]
+    pub operate: OperateArgs,
+}
+
+/// Arguments of the config sub command.
+#[derive(Debug, Clone, Default, clap::Args)]
+#[command(group(ArgGroup::new("destination").required(true).args(["user", "stdout", "cfg"])))]
+#[command(subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
+pub struct ConfigArgs {
+    /// Write the configuration file to the default user configuration directory.
+    #[arg(long)]
+    pub user: bool,
+
+    /// Print the configuration file to stdout and exit.
+    #[arg(long)]
+    pub stdout: bool,
+
+    /// Write the configuration file to the given path.
+    #[arg(short, long, value_name = "CFG")]
+    pub cfg: Option<PathBuf>,
+
+    /// Overwrite any existing configuration file.
+    #[arg(short, long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub overrides: ConfigOverrides,
+
+    #[command(subcommand)]
+    pub command: Option<ConfigSub>,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigSub {
+    /// Print the effective configuration a run would use, annotated with the
+    /// origin of each value, and the resolved dictionary.
+    Show(OperateArgs),
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Sub {
+    /// Only show errors, the default if no sub command is given.
+    Check(CheckArgs),
+    /// Interactively choose from checker provided suggestions.
+    Fix(OperateArgs),
+    /// Reflow doc comments, so they adhere to a given maximum column width.
+    Reflow(ReflowArgs),
+    /// Write the configuration file with all overrides applied.
+    Config(ConfigArgs),
+    /// Diagnose dictionaries, configuration and compiled in checkers.
+    ///
+    /// Exits with a non-zero code if problems were found.
+    Doctor(OperateArgs),
+    /// Print the shell completions for the given shell.
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Print the man page in roff format.
+    Manpage,
+}
+
+/// Spellcheck all your doc comments
+#[derive(Debug, Clone, Parser)]
+#[command(name = "cargo-spellcheck", version)]
 pub struct Args {
-    pub arg_paths: Vec<PathBuf>,
-    pub flag_fix: bool,
-    pub flag_recursive: bool,
-    pub flag_verbose: usize,
-    pub flag_quiet: bool,
-    pub flag_version: bool,
-    pub flag_help: bool,
-    pub flag_checkers: Option<Vec<CheckerType>>,
-    pub flag_cfg: Option<PathBuf>,
-    pub flag_force: bool,
-    pub flag_user: bool,
-    // with fallback from config, so it has to be tri-state
-    pub flag_skip_readme: Option<bool>,
-    pub flag_dev_comments: Option<bool>,
-    pub flag_code: u8,
-    pub flag_stdout: bool,
-    pub cmd_fix: bool,
-    pub cmd_check: bool,
-    pub cmd_reflow: bool,
-    pub cmd_config: bool,
+    /// Verbosity level.
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    pub verbose: u8,
+
+    /// Silences all printed messages. Overrules `-v`.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Sub>,
+
+    #[command(flatten)]
+    pub check: CheckArgs,
+
+    /// Origin of the configuration overrides.
+    #[arg(skip)]
+    pub sources: Vec<(&'static str, Provenance)>,
 }
 
 impl Args {
-    pub const USAGE: &'static str = USAGE;
-
     /// Extract the required action.
     pub fn action(&self) -> Action {
         // extract operation mode
-        let action = if self.cmd_fix {
-            Action::Fix
-        } else if self.flag_fix {
-            Action::Fix
-        } else if self.cmd_reflow {
-            Action::Reflow
-        } else if self.cmd_config {
-            Action::Config
-        } else if self.flag_help {
-            Action::Help
-        } else if self.flag_version {
-            Action::Version
-        } else if self.cmd_check {
-            Action::Check
-        } else {
+        let action = match &self.command {
+            Some(Sub::Fix(_)) => Action::Fix,
+            Some(Sub::Check(CheckArgs { fix: true, .. })) => Action::Fix,
+            Some(Sub::Reflow(ReflowArgs { check: true, .. })) => Action::ReflowCheck,
+            Some(Sub::Reflow(_)) => Action::Reflow,
+            Some(Sub::Config(_)) => Action::Config,
+            Some(Sub::Check(_)) => Action::Check,
+            Some(Sub::Doctor(_)) => Action::Doctor,
+            Some(Sub::Completions { .. }) => Action::Completions,
+            Some(Sub::Manpage) => Action::Manpage,
             // `cargo spellcheck` is short for checking
-            Action::Check
+            None if self.check.fix => Action::Fix,
+            None => Action::Check,
         };
         log::trace!("Derived action {:?} from flags/args/cmds", action);
         action
     }
 
+    /// The arguments of the operating sub commands, the implicit `check` if none.
+    fn operate(&self) -> &OperateArgs {
+        match &self.command {
+            Some(Sub::Check(check)) => &check.operate,
+            Some(Sub::Reflow(reflow)) => &reflow.operate,
+            Some(Sub::Fix(operate)) | Some(Sub::Doctor(operate)) => operate,
+            Some(Sub::Config(ConfigArgs {
+                command: Some(ConfigSub::Show(operate)),
+                ..
+            })) => operate,
+            _ => &self.check.operate,
+        }
+    }
+
     /// Adjust the raw arguments for call variants.
     ///
     /// The program could be called like `cargo-spellcheck`, `cargo spellcheck` or
     /// `cargo spellcheck check` and even ``cargo-spellcheck check`.
-    pub fn parse(argv_iter: impl IntoIterator<Item = String>) -> Result<Self, docopt::Error> {
-        Docopt::new(USAGE).and_then(|d| {
-            // if ends with file name `cargo-spellcheck`
-            let mut argv_iter = argv_iter.into_iter();
-            if let Some(arg0) = argv_iter.next() {
-                match PathBuf::from(&arg0)
-                    .file_name()
-                    .map(|x| x.to_str())
-                    .flatten()
-                {
-                    Some(file_name) => {
-                        // allow all variants to be parsed
-                        // cargo spellcheck ...
-                        // cargo-spellcheck ...
-                        // cargo-spellcheck spellcheck ...
-                        //
-                        // so preprocess them to unified `cargo-spellcheck`
-                        let mut next = vec!["cargo-spellcheck".to_owned()];
-
-                        match argv_iter.next() {
-                            Some(arg)
-                                if file_name.starts_with("cargo-spellcheck")
-                                    && arg == "spellcheck" =>
-                            {
-                                // drop the first arg `spellcheck`
-                            }
-                            Some(arg) if file_name.starts_with("cargo") && &arg == "spellcheck" => {
-                                // drop it, we replace it with `cargo-spellcheck`
-                            }
-                            Some(arg) if arg == "spellcheck" => {
-                                // "spellcheck" but the binary got renamed
-                                // drop the "spellcheck" part
-                            }
-                            Some(arg) => {
-                                // not "spellcheck" so retain it
-                                next.push(arg.to_owned())
-                            }
-                            None => {}
-                        };
-                        let collected = next.into_iter().chain(argv_iter);
-                        d.argv(collected)
-                    }
-                    _ => d,
+    pub fn parse(argv_iter: impl IntoIterator<Item = String>) -> Result<Self, clap::Error> {
+        let mut argv_iter = argv_iter.into_iter();
+        // allow all variants to be parsed
+        // cargo spellcheck ...
+        // cargo-spellcheck ...
+        // cargo-spellcheck spellcheck ...
+        //
+        // so preprocess them to unified `cargo-spellcheck`
+        let mut next = vec!["cargo-spellcheck".to_owned()];
+        if argv_iter.next().is_some() {
+            match argv_iter.next() {
+                Some(arg) if arg == "spellcheck" => {
+                    // drop it, cargo passes the sub command name along
+                    // or the binary got renamed
                 }
-            } else {
-                d
-            }
-            .deserialize()
-        })
-    }
-
-    /// Overrides the enablement status of checkers in the configuration
-    /// based on the checkers enabled by arg, if it is set.
-    ///
-    /// Errors of no checkers are left.
-    pub fn checker_selection_override(
-        filter_set: Option<&[CheckerType]>,
-        config: &mut Config,
-    ) -> Result<()> {
-        // overwrite checkers
-        if let Some(ref checkers) = filter_set {
-            #[cfg(feature = "hunspell")]
-            if !checkers.contains(&CheckerType::Hunspell) {
-                if !config.hunspell.take().is_some() {
-                    warn!("Hunspell was never configured.")
-                }
-            }
-            #[cfg(feature = "nlprule")]
-            if !checkers.contains(&CheckerType::NlpRules) {
-                if !config.nlprules.take().is_some() {
-                    warn!("Nlprules checker was never configured.")
+                Some(arg) => {
+                    // not "spellcheck" so retain it
+                    next.push(arg)
                 }
+                None => {}
             }
-            #[cfg(feature = "languagetool")]
-            if !checkers.contains(&CheckerType::LanguageTool) {
-                if !config.languagetool.take().is_some() {
-                    warn!("Languagetool checker was never configured.")
-                }
-            }
-
-            if !checkers.contains(&CheckerType::Reflow) {
-                warn!("Reflow is a separate sub command.")
+        }
+        let mut cmd = Self::command();
+        let matches = cmd.try_get_matches_from_mut(next.into_iter().chain(argv_iter))?;
+        // the implicit `check` arguments are only valid without a sub command,
+        // they would be silently ignored otherwise
+        if matches.subcommand().is_some() {
+            let misplaced = cmd
+                .get_arguments()
+                .find(|arg| {
+                    !arg.is_global_set()
+                        && matches.value_source(arg.get_id().as_str())
+                            == Some(ValueSource::CommandLine)
+                })
+                .map(ToString::to_string);
+            if let Some(arg) = misplaced {
+                return Err(cmd.error(
+                    ErrorKind::ArgumentConflict,
+                    format!("`{}` must be passed after the sub command", arg),
+                ));
             }
+        }
+        let mut args = Self::from_arg_matches(&matches)?;
+        args.sources = ConfigOverrides::sources(&cmd, &matches);
+        Ok(args)
+    }
 
-            const EXPECTED_COUNT: usize = 1_usize
-                + cfg!(feature = "nlprule") as usize
-                + cfg!(feature = "hunspell") as usize
-                + cfg!(feature = "languagetool") as usize;
+    /// Print the shell completions to `writer`.
+    pub fn write_completions(
+        shell: clap_complete::Shell,
+        writer: &mut dyn std::io::Write,
+    ) -> Result<()> {
+        let mut cmd = Self::command();
+        clap_complete::generate(shell, &mut cmd, "cargo-spellcheck", writer);
+        Ok(())
+    }
 
-            if checkers.iter().unique().count() == EXPECTED_COUNT {
-                bail!("Argument override for checkers disabled all checkers")
-            }
-        }
+    /// Print the man page to `writer`.
+    pub fn write_manpage(writer: &mut dyn std::io::Write) -> Result<()> {
+        clap_mangen::Man::new(Self::command()).render(writer)?;
         Ok(())
     }
 
-    /// Load configuration with fallbacks.
-    ///
-    /// Does IO checks if files exist.
+    /// Determine the configuration file to use.
     ///
-    /// Provides a config and where it was retrieved from,
-    /// if no config file exists, a default is provided
-    /// and the config path becomes `None`.
-    // TODO split the IO operations and lookup dirs.
-    fn load_config(&self) -> Result<(Config, Option<PathBuf>)> {
-        let (explicit_cfg, config_path) = match self.flag_cfg.as_ref() {
+    /// Does IO checks if files exist, the returned
+    /// user configuration file might not exist.
+    fn locate_config(&self) -> Result<Provenance> {
+        let operate = self.operate();
+        let origin = match operate.cfg.as_ref() {
             Some(config_path) => {
                 let config_path = if config_path.is_absolute() {
                     config_path.to_owned()
                 } else {
                     crate::traverse::cwd()?.join(config_path)
                 };
-                (true, config_path)
+                Provenance::Explicit(config_path)
             }
             None => {
                 // TODO refactor needed
@@ -242,7 +856,7 @@ impl Args {
                 // TODO Currently uses the first manifest dir as search dir for a spellcheck.toml
                 // TODO with a fallback to the cwd as project dir.
                 // TODO But it would be preferable to use the config specific to each dir if available.
-                for path in self.arg_paths.iter() {
+                for path in operate.paths.iter() {
                     let path = if let Some(path) = if path.is_absolute() {
                         path.to_owned()
                     } else {
@@ -288,19 +902,36 @@ impl Args {
                         // this is a common case for workspace setups where we want to sanitize a sub project
                         Config::project_config(cwd.as_path())
                     })
+                    .map(Provenance::Project)
                     .or_else(|e| {
                         debug!("Fallback to user default lookup, failed to load project specific config {}: {}", config_path.display(), e);
-                        Config::default_path()
+                        Config::default_path().map(Provenance::User)
                     })?;
-                (false, config_path)
+                config_path
             }
         };
+        Ok(origin)
+    }
+
+    /// Load configuration with fallbacks.
+    ///
+    /// Provides a config and where it was retrieved from,
+    /// if no config file exists, a default is provided
+    /// and the origin becomes `None`.
+    fn load_config(&self) -> Result<(Config, Option<Provenance>)> {
+        let operate = self.operate();
+        let origin = self.locate_config()?;
+        let explicit_cfg = matches!(origin, Provenance::Explicit(_));
+        let config_path = origin
+            .path()
+            .expect("Provenance of a file always has a path. qed")
+            .to_owned();
         info!(
             "Attempting to use configuration file {}",
             config_path.display()
         );
-        let (mut config, config_path) = match Config::load_from(&config_path) {
-            Ok(config) => (config, Some(config_path)),
+        let (mut config, origin) = match Config::load_from(&config_path) {
+            Ok(config) => (config, Some(origin)),
             Err(e) => {
                 if explicit_cfg {
                     return Err(e);
@@ -318,70 +949,77 @@ impl Args {
                 }
             }
         };
-        // mask all disabled checkers, use the default config
-        // for those which have one if not enabled already
-        if let Some(filter_set) = &self.flag_checkers {
-            if filter_set.contains(&CheckerType::Hunspell) {
-                if config.hunspell.is_none() {
-                    config.hunspell = Some(crate::config::HunspellConfig::default());
-                }
-            } else {
-                config.hunspell = None;
-            }
-            if filter_set.contains(&CheckerType::NlpRules) {
-                if config.nlprules.is_none() {
-                    config.nlprules = Some(crate::config::NlpRulesConfig::default());
-                }
-            } else {
-                config.nlprules = None;
-            }
-            if filter_set.contains(&CheckerType::LanguageTool) {
-                if config.languagetool.is_none() {
-                    warn!("Language tool must be explicitly configured via config file")
-                }
-            } else {
-                config.languagetool = None;
-            }
-            // reflow is a different subcommand, not relevant
-        }
 
-        Ok((config, config_path))
+        operate.overrides.apply(&mut config)?;
+
+        Ok((config, origin))
     }
 
     /// Evaluate the configuration flags, overwrite
     /// config values as needed and provide a new,
     /// unified config struct.
     pub fn unified(self) -> Result<(UnifiedArgs, Config)> {
-        let (config, config_path) = self.load_config()?;
-
-        let unified = match self.action() {
-            Action::Config => {
-                let dest_config = match self.flag_cfg {
-                    None if self.flag_stdout => ConfigWriteDestination::Stdout,
+        let action = self.action();
+        match self.command {
+            Some(Sub::Config(ConfigArgs {
+                command: Some(ConfigSub::Show(_)),
+                ..
+            })) => {
+                let (config, file) = self.load_config()?;
+                let unified = UnifiedArgs::ShowConfig {
+                    file,
+                    overrides: self.sources,
+                };
+                return Ok((unified, config));
+            }
+            Some(Sub::Doctor(_)) => {
+                // a broken configuration file is one of the findings, so do not bail
+                let file = self.locate_config()?;
+                let mut config = file
+                    .path()
+                    .filter(|path| path.is_file())
+                    .and_then(|path| Config::load_from(path).ok())
+                    .unwrap_or_default();
+                self.operate().overrides.apply(&mut config)?;
+                return Ok((UnifiedArgs::Doctor { file }, config));
+            }
+            Some(Sub::Completions { shell }) => {
+                return Ok((UnifiedArgs::Completions { shell }, Config::default()))
+            }
+            Some(Sub::Manpage) => return Ok((UnifiedArgs::Manpage, Config::default())),
+            Some(Sub::Config(config_args)) => {
+                let dest_config = match config_args.cfg {
+                    None if config_args.stdout => ConfigWriteDestination::Stdout,
                     Some(path) => ConfigWriteDestination::File {
-                        overwrite: self.flag_force,
-                        path: path.to_owned(),
+                        overwrite: config_args.force,
+                        path,
                     },
-                    None if self.flag_user => ConfigWriteDestination::File {
-                        overwrite: self.flag_force,
+                    None if config_args.user => ConfigWriteDestination::File {
+                        overwrite: config_args.force,
                         path: Config::default_path()?,
                     },
                     _ => bail!("Neither --user or --stdout are given, invalid flags passed."),
                 };
-                UnifiedArgs::Config {
-                    dest_config,
-                    checker_filter_set: self.flag_checkers,
-                }
+                let mut config = Config::full();
+                config_args.overrides.apply(&mut config)?;
+                return Ok((UnifiedArgs::Config { dest_config }, config));
             }
-            action => UnifiedArgs::Operate {
-                action,
-                config_path,
-                dev_comments: self.flag_dev_comments.unwrap_or(config.dev_comments),
-                skip_readme: self.flag_skip_readme.unwrap_or(config.skip_readme),
-                recursive: self.flag_recursive,
-                paths: self.arg_paths,
-                exit_code_override: self.flag_code,
-            },
+            _ => {}
+        }
+
+        let (config, origin) = self.load_config()?;
+        let operate = self.operate().clone();
+        let unified = UnifiedArgs::Operate {
+            action,
+            config_path: origin
+                .as_ref()
+                .and_then(Provenance::path)
+                .map(ToOwned::to_owned),
+            dev_comments: config.dev_comments,
+            skip_readme: config.skip_readme,
+            recursive: operate.recursive,
+            paths: operate.paths,
+            exit_code_override: operate.code,
         };
 
         Ok((unified, config))
@@ -404,7 +1042,6 @@ pub enum ConfigWriteDestination {
 pub enum UnifiedArgs {
     Config {
         dest_config: ConfigWriteDestination,
-        checker_filter_set: Option<Vec<CheckerType>>,
     },
     Operate {
         action: Action,
@@ -415,14 +1052,28 @@ pub enum UnifiedArgs {
         paths: Vec<PathBuf>,
         exit_code_override: u8,
     },
+    ShowConfig {
+        file: Option<Provenance>,
+        overrides: Vec<(&'static str, Provenance)>,
+    },
+    Doctor {
+        file: Provenance,
+    },
+    Completions {
+        shell: clap_complete::Shell,
+    },
+    Manpage,
 }
 
 impl UnifiedArgs {
     /// Extract the action.
     pub fn action(&self) -> Action {
         match self {
-            Self::Config { .. } => Action::Config,
+            Self::Config { .. } | Self::ShowConfig { .. } => Action::Config,
             Self::Operate { action, .. } => *action,
+            Self::Doctor { .. } => Action::Doctor,
+            Self::Completions { .. } => Action::Completions,
+            Self::Manpage => Action::Manpage,
         }
     }
 }
@@ -439,9 +1090,8 @@ mod tests {
     lazy_static::lazy_static!(
         static ref SAMPLES: std::collections::HashMap<&'static str, Action> = maplit::hashmap!{
             "cargo spellcheck" => Action::Check,
-            "cargo-spellcheck --version" => Action::Version,
-            "cargo spellcheck --version" => Action::Version,
             "cargo spellcheck reflow" => Action::Reflow,
+            "cargo spellcheck reflow --check src" => Action::ReflowCheck,
             "cargo spellcheck -vvvv" => Action::Check,
             "cargo spellcheck --fix" => Action::Fix,
             "cargo spellcheck fix" => Action::Fix,
@@ -452,23 +1102,54 @@ mod tests {
             "cargo-spellcheck fix -r file.rs" => Action::Fix,
             "cargo-spellcheck -q fix Cargo.toml" => Action::Fix,
             "cargo spellcheck -v fix Cargo.toml" => Action::Fix,
-            "cargo spellcheck -m 11 check" => Action::Check,
+            "cargo spellcheck check -m 11" => Action::Check,
+            "cargo spellcheck check --fix" => Action::Fix,
+            "cargo spellcheck -v check --fix src" => Action::Fix,
+            "cargo spellcheck --lang=de_DE --max-line-length 99 src" => Action::Check,
+            "cargo spellcheck config --stdout --checkers=hunspell" => Action::Config,
             "cargo-spellcheck reflow" => Action::Reflow,
+            "cargo spellcheck config show --lang=de_DE src" => Action::Config,
+            "cargo spellcheck doctor --search-dir=/usr/share/myspell" => Action::Doctor,
+            "cargo spellcheck completions bash" => Action::Completions,
+            "cargo-spellcheck manpage" => Action::Manpage,
+            "cargo spellcheck check --deny=warnings" => Action::Check,
         };
     );
 
     #[test]
-    fn docopt() {
-        for command in SAMPLES.keys() {
-            assert!(Args::parse(commandline_to_iter(command))
-                .map_err(|e| {
-                    println!("Processing > {:?}", command);
-                    e
-                })
-                .is_ok());
+    fn args() {
+        for (command, action) in SAMPLES.iter() {
+            let args = Args::parse(commandline_to_iter(command)).unwrap_or_else(|e| {
+                panic!("Processing > {:?}: {}", command, e);
+            });
+            assert_eq!(args.action(), *action, "Processing > {:?}", command);
         }
     }
 
+    #[test]
+    fn reflow_is_no_checker() {
+        let e = Args::parse(commandline_to_iter(
+            "cargo spellcheck check --checkers=hunspell,reflow",
+        ))
+        .unwrap_err();
+        assert!(e.to_string().contains("`reflow` subcommand"), "{}", e);
+    }
+
+    #[test]
+    fn cli_is_sane() {
+        Args::command().debug_assert();
+    }
+
+    #[test]
+    fn completions_and_manpage() {
+        let mut buf = Vec::new();
+        Args::write_completions(clap_complete::Shell::Bash, &mut buf).unwrap();
+        assert!(String::from_utf8_lossy(&buf).contains("--extra-dictionary"));
+        let mut buf = Vec::new();
+        Args::write_manpage(&mut buf).unwrap();
+        assert!(String::from_utf8_lossy(&buf).contains("cargo\\-spellcheck"));
+    }
+
     #[test]
     fn unify_ops_check() {
         let args = Args::parse(
@@ -486,6 +1167,7 @@ mod tests {
             .map(ToOwned::to_owned),
         )
         .unwrap();
+        assert_eq!(args.verbose, 5);
         let (unified, _config) = args.unified().unwrap();
         assert_matches!(unified,
             UnifiedArgs::Operate {
@@ -526,10 +1208,8 @@ mod tests {
         assert_matches!(unified,
             UnifiedArgs::Config {
                 dest_config: ConfigWriteDestination::File { overwrite, path },
-                checker_filter_set,
             } => {
                 assert_eq!(path, PathBuf::from(".config/spellcheck.toml"));
-                assert_eq!(checker_filter_set, Some(vec![CheckerType::NlpRules]));
                 assert_eq!(overwrite, true);
             }
         );
@@ -541,4 +1221,121 @@ mod tests {
         });
         assert_matches!(config.languagetool, None => {});
     }
+
+    #[test]
+    fn overrides() {
+        let args = Args::parse(
+            &mut [
+                "cargo-spellcheck",
+                "reflow",
+                "--lang",
+                "de_DE",
+                "--extra-dictionary=/tmp/a.dic,/tmp/b.dic",
+                "--transform-regex",
+                "^[0-9]+x$",
+                "--allow-dashes",
+                "--max-line-length",
+                "99",
+                "--reflow-strategy=knuth-plass",
+                "--variant-checkers=dev=hunspell;macro_doc_eq=",
+                "--languagetool-url",
+                "http://127.0.0.1:8010",
+                "--languagetool-language=en-GB",
+                "--languagetool-disabled-rules=EN_QUOTES,WHITESPACE_RULE",
+                "--languagetool-retries=1",
+                "--nlprules-disable-rules=A_AN",
+                "--nlprules-enable-only=grammar",
+                "--tab-width=8",
+                "--comment-width",
+                "72",
+                "--rustfmt",
+                "--reflow-dev-comments=false",
+                "--deny=warnings",
+            ]
+            .iter()
+            .map(ToOwned::to_owned)
+            .map(ToOwned::to_owned),
+        )
+        .unwrap();
+
+        let mut config = Config {
+            nlprules: Some(NlpRulesConfig::default()),
+            ..Default::default()
+        };
+        args.operate().overrides.apply(&mut config).unwrap();
+
+        let hunspell = config.hunspell.expect("Hunspell is enabled by default");
+        assert_eq!(hunspell.lang(), "de_DE");
+        assert_eq!(
+            hunspell.extra_dictionaries(),
+            &[PathBuf::from("/tmp/a.dic"), PathBuf::from("/tmp/b.dic")]
+        );
+        assert_eq!(hunspell.quirks.transform_regex().len(), 1);
+        assert!(hunspell.quirks.allow_dashed());
+        assert!(!hunspell.quirks.allow_concatenated());
+        let reflow = config.reflow.unwrap();
+        assert_eq!(reflow.max_line_length, 99);
+        assert_eq!(reflow.strategy, ReflowStrategy::Optimal);
+        assert_eq!(reflow.tab_width, 8);
+        assert_eq!(reflow.comment_width, Some(72));
+        assert!(reflow.rustfmt);
+        assert!(!reflow.dev_comments);
+        assert_eq!(config.variant_checkers.dev, Some(vec![Detector::Hunspell]));
+        assert_eq!(config.variant_checkers.macro_doc_eq, Some(vec![]));
+        let languagetool = config.languagetool.unwrap();
+        assert_eq!(languagetool.url.as_str(), "http://127.0.0.1:8010/");
+        assert_eq!(languagetool.language, "en-GB");
+        assert_eq!(
+            languagetool.disabled_rules,
+            vec!["EN_QUOTES".to_owned(), "WHITESPACE_RULE".to_owned()]
+        );
+        assert_eq!(languagetool.retries, 1);
+        let nlprules = config.nlprules.unwrap();
+        assert_eq!(nlprules.disable_rules, vec!["A_AN".to_owned()]);
+        assert_eq!(nlprules.enable_only, Some(vec!["grammar".to_owned()]));
+        assert_eq!(config.severity.fail_on, Some(Severity::Warning));
+    }
+
+    #[test]
+    fn override_sources() {
+        let args = Args::parse(commandline_to_iter(
+            "cargo spellcheck config show --lang=de_DE --max-line-length=77",
+        ))
+        .unwrap();
+        assert_eq!(
+            args.sources,
+            vec![
+                ("hunspell.lang", Provenance::Cli("--lang".to_owned())),
+                (
+                    "reflow.max_line_length",
+                    Provenance::Cli("--max-line-length".to_owned())
+                ),
+            ]
+        );
+        let (unified, config) = args.unified().unwrap();
+        assert_matches!(unified, UnifiedArgs::ShowConfig { overrides, .. } => {
+            assert_eq!(overrides.len(), 2);
+        });
+        assert_eq!(config.reflow.unwrap().max_line_length, 77);
+    }
+
+    #[test]
+    fn invalid_overrides() {
+        for command in &[
+            "cargo spellcheck --checkers=foo",
+            "cargo spellcheck --variant-checkers=nope=hunspell",
+            "cargo spellcheck --transform-regex=(",
+            "cargo spellcheck --max-line-length=-1",
+            "cargo spellcheck config --user --stdout",
+            "cargo spellcheck config",
+            "cargo spellcheck -m 11 check",
+            "cargo spellcheck check --deny=fatal",
+        ] {
+            assert!(
+                Args::parse(commandline_to_iter(command)).is_err(),
+                "Processing > {:?}",
+                command
+            );
+        }
+    }
 }
@@ -0,0 +1,81 @@
+//! Project specific style and terminology rules, expressed as regular
+//! expressions.
+
+use super::WrappedRegex;
+use crate::Severity;
+
+use serde::{Deserialize, Serialize};
+
+/// A single rule, every match of `pattern` in the plain text is reported.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRule {
+    /// Identifies the rule in the output and in the `[Severity]` section.
+    pub id: String,
+    /// Groups rules, i.e. `STYLE` or `TERMINOLOGY`.
+    #[serde(default = "default_category")]
+    pub category: String,
+    pub pattern: WrappedRegex,
+    /// Shown with each match, may refer to capture groups as `$1` or `${name}`.
+    pub message: String,
+    /// Offered as fix, may refer to capture groups like the message. An empty
+    /// replacement removes the match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+    /// Severity of the matches, unless configured in the `[Severity]` section.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+}
+
+fn default_category() -> String {
+    "CUSTOM".to_owned()
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CustomRulesConfig {
+    #[serde(default)]
+    #[serde(alias = "rule")]
+    pub rules: Vec<CustomRule>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let config: CustomRulesConfig = toml::from_str(
+            r#"
+[[rule]]
+id = "GITHUB"
+category = "TERMINOLOGY"
+pattern = "\\bGithub\\b"
+message = "The name is spelled `GitHub`"
+replacement = "GitHub"
+
+[[rule]]
+id = "SIMPLY"
+pattern = "(?i)\\bsimply\\s+"
+message = "Avoid `simply`"
+replacement = ""
+severity = "warn"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.rules.len(), 2);
+        let github = &config.rules[0];
+        assert_eq!(github.category, "TERMINOLOGY");
+        assert_eq!(github.severity, None);
+        assert!(github.pattern.is_match("on Github").unwrap());
+        let simply = &config.rules[1];
+        assert_eq!(simply.category, "CUSTOM");
+        assert_eq!(simply.replacement.as_deref(), Some(""));
+        assert_eq!(simply.severity, Some(Severity::Warning));
+
+        assert!(toml::from_str::<CustomRulesConfig>(
+            "[[rule]]\nid = \"X\"\npattern = \"(\"\nmessage = \"\"\n"
+        )
+        .is_err());
+    }
+}
@@ -1,9 +1,12 @@
 //! Hunspell checker configuration.
 
 use super::{SearchDirs, WrappedRegex};
+use fs_err as fs;
+use std::fmt;
+use std::io::{self, BufRead};
 use std::path::{Path, PathBuf};
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 
 use serde::{Deserialize, Serialize};
 
@@ -151,3 +154,270 @@ impl HunspellConfig {
         Ok(())
     }
 }
+
+/// Language of the dictionary embedded with the `bundled-dictionary` feature.
+pub const BUNDLED_LANG: &str = "en_US";
+
+/// Outcome of the lookup of the `.dic` and `.aff` files in the search dirs.
+#[derive(Debug, Clone, Default)]
+pub struct DictionaryLookup {
+    /// The `.dic` and `.aff` file of the first search dir that contains both.
+    pub found: Option<(PathBuf, PathBuf)>,
+    /// Search dirs inspected before, with the reason why they were rejected.
+    pub rejected: Vec<(PathBuf, String)>,
+}
+
+impl HunspellConfig {
+    /// Find the `.dic` and `.aff` files for the configured language.
+    ///
+    /// Search dirs are inspected in order, the first one containing
+    /// both files is used.
+    pub fn lookup_dictionary(&self) -> DictionaryLookup {
+        let lang = self.lang();
+        let mut lookup = DictionaryLookup::default();
+        for search_dir in self.search_dirs() {
+            if !search_dir.is_dir() {
+                // search_dir also contains the default paths, so just silently ignore these
+                log::debug!(
+                    "Dictionary search path is not a directory {}",
+                    search_dir.display()
+                );
+                lookup
+                    .rejected
+                    .push((search_dir.clone(), "not a directory".to_owned()));
+                continue;
+            }
+            log::debug!("Found dictionary search path {}", search_dir.display());
+            let dic = search_dir.join(lang).with_extension("dic");
+            if !dic.is_file() {
+                log::debug!(
+                    "Dictionary path dervied from search dir is not a file {}",
+                    dic.display()
+                );
+                lookup.rejected.push((
+                    search_dir.clone(),
+                    format!("{} is not a file", dic.display()),
+                ));
+                continue;
+            }
+            let aff = search_dir.join(lang).with_extension("aff");
+            if !aff.is_file() {
+                log::debug!(
+                    "Affixes path dervied from search dir is not a file {}",
+                    aff.display()
+                );
+                lookup.rejected.push((
+                    search_dir.clone(),
+                    format!("{} is not a file", aff.display()),
+                ));
+                continue;
+            }
+            log::debug!("Using dic {} and aff {}", dic.display(), aff.display());
+            lookup.found = Some((dic, aff));
+            break;
+        }
+        lookup
+    }
+
+    /// Check if the bundled dictionary is used, if none is found in the search dirs.
+    pub fn has_bundled_fallback(&self) -> bool {
+        cfg!(feature = "bundled-dictionary") && self.lang() == BUNDLED_LANG
+    }
+}
+
+/// A format violation in a hunspell `.dic` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DicFormatError {
+    /// The offending line, starting at `1`.
+    pub lineno: usize,
+    /// What is wrong with the line.
+    pub reason: String,
+}
+
+impl fmt::Display for DicFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.lineno, self.reason)
+    }
+}
+
+/// Collect the format errors of the first `limit` lines of a hunspell `.dic` file.
+///
+/// Refer to `man 5 hunspell` for the format, the first line
+/// is the number of entries, followed by one word per line.
+pub fn hunspell_dic_format_errors(
+    reader: impl BufRead,
+    limit: usize,
+) -> Result<Vec<DicFormatError>> {
+    let mut errors = Vec::new();
+    let mut iter = reader.lines().take(limit).enumerate();
+    match iter.next() {
+        Some((_, first)) => {
+            let first = first?;
+            if first.trim().parse::<u64>().is_err() {
+                errors.push(DicFormatError {
+                    lineno: 1,
+                    reason: format!("must be the number of entries, but is: >{}<", first),
+                });
+            }
+        }
+        None => errors.push(DicFormatError {
+            lineno: 1,
+            reason: "dictionary is empty, expected the number of entries".to_owned(),
+        }),
+    }
+    for (idx, line) in iter {
+        let line = line?;
+        let word = line.trim();
+        let reason = if word.parse::<i64>().is_ok() {
+            format!("must be a word, but is a number: >{}<", word)
+        } else if word.starts_with('/') {
+            format!("word before the affix flags is missing: >{}<", word)
+        } else {
+            continue;
+        };
+        errors.push(DicFormatError {
+            lineno: idx + 1,
+            reason,
+        });
+    }
+    Ok(errors)
+}
+
+/// Check if provided path has valid dictionary format.
+///
+/// This is a YOLO check.
+pub fn is_valid_hunspell_dic_path(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let reader = io::BufReader::new(fs::File::open(path)?);
+    is_valid_hunspell_dic(reader).map_err(|e| e.context(format!("{}", path.display())))
+}
+
+/// Check a reader for correct hunspell format.
+pub fn is_valid_hunspell_dic(reader: impl BufRead) -> Result<()> {
+    // Just check the first 10 lines, don't waste much time here
+    // the first two are the most important ones.
+    match hunspell_dic_format_errors(reader, 11)?.first() {
+        Some(error) => Err(anyhow!("Malformed dictionary, {}", error)),
+        None => Ok(()),
+    }
+}
+
+/// Append the `words` not present yet to the content of a hunspell `.dic`
+/// file and update the number of entries in the first line.
+///
+/// Returns the new content and the number of added words.
+pub fn extend_hunspell_dic<S: AsRef<str>>(content: &str, words: &[S]) -> (String, usize) {
+    let newline = if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut lines = content.lines();
+    // the number of entries is recalculated anyways
+    let _ = lines.next();
+    let mut entries = lines.collect::<Vec<_>>();
+    let mut added = 0usize;
+    for word in words {
+        let word = word.as_ref();
+        let known = entries
+            .iter()
+            .any(|entry| entry.split('/').next().map(str::trim) == Some(word));
+        if !known {
+            entries.push(word);
+            added += 1;
+        }
+    }
+    let count = entries
+        .iter()
+        .filter(|entry| !entry.trim().is_empty())
+        .count();
+    let mut extended = count.to_string();
+    for entry in entries {
+        extended.push_str(newline);
+        extended.push_str(entry);
+    }
+    extended.push_str(newline);
+    (extended, added)
+}
+
+/// Add `words` to the hunspell `.dic` file at `path`, which is created if
+/// it does not exist yet.
+///
+/// Returns the number of added words.
+pub fn extend_hunspell_dic_path<S: AsRef<str>>(path: &Path, words: &[S]) -> Result<usize> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let (extended, added) = extend_hunspell_dic(&content, words);
+    if added > 0 {
+        fs::write(path, extended)?;
+    }
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hunspell_dic_format() {
+        const GOOD: &str = "2
+whitespazes
+catsndogs
+";
+        const BAD_1: &str = "foo
+12349
+bar
+";
+        const BAD_2: &str = "2
+12349
+bar
+";
+        const BAD_3: &str = "foo
+xxx
+bar
+";
+        assert!(is_valid_hunspell_dic(&mut GOOD.as_bytes()).is_ok());
+        assert!(is_valid_hunspell_dic(&mut BAD_1.as_bytes()).is_err());
+        assert!(is_valid_hunspell_dic(&mut BAD_2.as_bytes()).is_err());
+        assert!(is_valid_hunspell_dic(&mut BAD_3.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn hunspell_dic_format_errors_per_line() {
+        const BAD: &str = "two
+word
+42
+/XY
+other/XY
+";
+        let errors = hunspell_dic_format_errors(BAD.as_bytes(), usize::MAX).unwrap();
+        assert_eq!(
+            errors.iter().map(|e| e.lineno).collect::<Vec<_>>(),
+            vec![1, 3, 4]
+        );
+        assert!(hunspell_dic_format_errors(&b""[..], usize::MAX)
+            .unwrap()
+            .first()
+            .is_some());
+    }
+
+    #[test]
+    fn extend_dic() {
+        const DIC: &str = "2\nwhitespazes\ncatsndogs/XY\n";
+        let (extended, added) =
+            extend_hunspell_dic(DIC, &["catsndogs", "spellcheck", "spellcheck"]);
+        assert_eq!(added, 1);
+        assert_eq!(extended, "3\nwhitespazes\ncatsndogs/XY\nspellcheck\n");
+        assert!(is_valid_hunspell_dic(extended.as_bytes()).is_ok());
+
+        let (created, added) = extend_hunspell_dic("", &["foo"]);
+        assert_eq!(added, 1);
+        assert_eq!(created, "1\nfoo\n");
+
+        let (crlf, _) = extend_hunspell_dic("1\r\nfoo\r\n", &["bar"]);
+        assert_eq!(crlf, "2\r\nfoo\r\nbar\r\n");
+    }
+}
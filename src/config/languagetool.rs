@@ -0,0 +1,219 @@
+//! LanguageTool checker configuration.
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageToolConfig {
+    /// Base url of the server, the `v2/check` endpoint is appended unless
+    /// already present.
+    pub url: url::Url,
+    /// The language code of the checked text.
+    #[serde(default = "default_language")]
+    #[serde(alias = "lang")]
+    pub language: String,
+    /// The native language of the author, enables false friends checks.
+    #[serde(default)]
+    #[serde(alias = "mother-tongue")]
+    #[serde(alias = "motherTongue")]
+    pub mother_tongue: Option<String>,
+    /// Rule ids to use in addition to the ones the server enables by default.
+    #[serde(default)]
+    #[serde(alias = "enabled-rules")]
+    pub enabled_rules: Vec<String>,
+    /// Rule ids to not use, in addition to the ones disabled by default.
+    #[serde(default)]
+    #[serde(alias = "disabled-rules")]
+    pub disabled_rules: Vec<String>,
+    /// Categories to use in addition to the ones enabled by default.
+    #[serde(default)]
+    #[serde(alias = "enabled-categories")]
+    pub enabled_categories: Vec<String>,
+    /// Categories to not use.
+    #[serde(default)]
+    #[serde(alias = "disabled-categories")]
+    pub disabled_categories: Vec<String>,
+    /// Only use the enabled rules and categories.
+    #[serde(default)]
+    #[serde(alias = "enabled-only")]
+    pub enabled_only: bool,
+    /// Use additional rules, which are more likely to yield false positives.
+    #[serde(default)]
+    pub picky: bool,
+    /// Maximum number of characters sent with a single request, as many
+    /// chunks as fit are batched into one request.
+    #[serde(default = "default_max_batch_length")]
+    #[serde(alias = "max-batch-length")]
+    pub max_batch_length: usize,
+    /// Upper limit of requests sent per second, unlimited if not set.
+    #[serde(default)]
+    #[serde(alias = "requests-per-second")]
+    pub requests_per_second: Option<f64>,
+    /// How often a request is retried after a network error or a response
+    /// indicating a temporary failure, such as `429 Too Many Requests`.
+    #[serde(default = "default_retries")]
+    pub retries: usize,
+    /// Delay before the first retry in milliseconds, doubled for each
+    /// subsequent retry.
+    #[serde(default = "default_backoff_ms")]
+    #[serde(alias = "backoff-ms")]
+    pub backoff_ms: u64,
+    /// Time in milliseconds after which a request is considered failed.
+    #[serde(default = "default_timeout_ms")]
+    #[serde(alias = "timeout-ms")]
+    pub timeout_ms: u64,
+}
+
+/// Rules which are not used unless enabled explicitly.
+///
+/// Quotes are really annoying and pointless in code related documentation.
+const DEFAULT_DISABLED_RULES: &[&str] = &["EN_QUOTES"];
+
+fn default_language() -> String {
+    "en-US".to_owned()
+}
+
+fn default_max_batch_length() -> usize {
+    10_000
+}
+
+fn default_retries() -> usize {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    500
+}
+
+fn default_timeout_ms() -> u64 {
+    30_000
+}
+
+impl LanguageToolConfig {
+    /// The default configuration for the server at `url`.
+    pub fn from_url(url: url::Url) -> Self {
+        Self {
+            url,
+            language: default_language(),
+            mother_tongue: None,
+            enabled_rules: Vec::new(),
+            disabled_rules: Vec::new(),
+            enabled_categories: Vec::new(),
+            disabled_categories: Vec::new(),
+            enabled_only: false,
+            picky: false,
+            max_batch_length: default_max_batch_length(),
+            requests_per_second: None,
+            retries: default_retries(),
+            backoff_ms: default_backoff_ms(),
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+
+    pub fn url(&self) -> &url::Url {
+        &self.url
+    }
+
+    /// The url of the `v2/check` endpoint.
+    pub fn check_url(&self) -> Result<url::Url, url::ParseError> {
+        let path = self.url.path().trim_end_matches('/');
+        if path.ends_with("/v2/check") {
+            Ok(self.url.clone())
+        } else if path.ends_with("/v2") {
+            self.url.join(&format!("{}/check", path))
+        } else {
+            self.url.join(&format!("{}/v2/check", path))
+        }
+    }
+
+    /// The rules to disable, including the ones disabled by default unless
+    /// they are enabled explicitly.
+    pub fn effective_disabled_rules(&self) -> Vec<String> {
+        let mut disabled = DEFAULT_DISABLED_RULES
+            .iter()
+            .filter(|id| !self.enabled_rules.iter().any(|enabled| enabled == *id))
+            .map(|id| (*id).to_owned())
+            .collect::<Vec<_>>();
+        for id in &self.disabled_rules {
+            if !disabled.contains(id) {
+                disabled.push(id.clone());
+            }
+        }
+        disabled
+    }
+
+    /// Minimum time between two requests.
+    pub fn request_interval(&self) -> Option<Duration> {
+        self.requests_per_second
+            .filter(|rps| rps.is_finite() && *rps > 0.)
+            .map(|rps| Duration::from_secs_f64(1. / rps))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> LanguageToolConfig {
+        toml::from_str(s).expect("Valid languagetool config. qed")
+    }
+
+    #[test]
+    fn defaults() {
+        let config = parse(r#"url = "http://127.0.0.1:8010""#);
+        assert_eq!(config.language, "en-US");
+        assert_eq!(config.max_batch_length, 10_000);
+        assert_eq!(config.retries, 3);
+        assert_eq!(config.request_interval(), None);
+        assert_eq!(config.effective_disabled_rules(), vec!["EN_QUOTES"]);
+    }
+
+    #[test]
+    fn full() {
+        let config = parse(
+            r#"
+url = "https://api.languagetool.org/v2"
+lang = "en-GB"
+mother_tongue = "de-DE"
+enabled_rules = ["EN_QUOTES"]
+disabled_rules = ["WHITESPACE_RULE"]
+disabled_categories = ["TYPOGRAPHY"]
+picky = true
+requests_per_second = 0.5
+retries = 0
+backoff_ms = 10
+"#,
+        );
+        assert_eq!(config.language, "en-GB");
+        assert_eq!(config.mother_tongue.as_deref(), Some("de-DE"));
+        assert!(config.picky);
+        assert_eq!(config.request_interval(), Some(Duration::from_secs(2)));
+        assert_eq!(config.effective_disabled_rules(), vec!["WHITESPACE_RULE"]);
+        assert_eq!(
+            config.check_url().unwrap().as_str(),
+            "https://api.languagetool.org/v2/check"
+        );
+    }
+
+    #[test]
+    fn check_url() {
+        let check_url = |url: &str| {
+            LanguageToolConfig::from_url(url.parse().unwrap())
+                .check_url()
+                .unwrap()
+                .to_string()
+        };
+        assert_eq!(
+            check_url("http://127.0.0.1:8010"),
+            "http://127.0.0.1:8010/v2/check"
+        );
+        assert_eq!(
+            check_url("http://127.0.0.1:8010/lt/"),
+            "http://127.0.0.1:8010/lt/v2/check"
+        );
+        assert_eq!(
+            check_url("http://127.0.0.1:8010/v2/check"),
+            "http://127.0.0.1:8010/v2/check"
+        );
+    }
+}
@@ -22,9 +22,33 @@ pub use self::hunspell::*;
 mod nlprules;
 pub use self::nlprules::*;
 
+mod languagetool;
+pub use self::languagetool::*;
+
+mod custom_rules;
+pub use self::custom_rules::*;
+
+mod terminology;
+pub use self::terminology::*;
+
+mod rustdoc;
+pub use self::rustdoc::*;
+
+mod typos;
+pub use self::typos::*;
+
+mod severity;
+pub use self::severity::*;
+
 mod search_dirs;
 pub use search_dirs::*;
 
+mod variants;
+pub use self::variants::*;
+
+mod provenance;
+pub use self::provenance::*;
+
 use crate::Detector;
 use anyhow::{anyhow, bail, Error, Result};
 use fancy_regex::Regex;
@@ -62,6 +86,11 @@ pub struct Config {
     #[serde(alias = "skipreadme")]
     pub skip_readme: bool,
 
+    /// Keep a copy of each file modified by `fix` or `reflow` with an
+    /// additional `.orig` extension.
+    #[serde(default)]
+    pub backup: bool,
+
     #[serde(alias = "Hunspell")]
     #[serde(default = "default_hunspell")]
     pub hunspell: Option<HunspellConfig>,
@@ -79,6 +108,36 @@ pub struct Config {
     #[serde(alias = "ReFlow")]
     #[serde(alias = "Reflow")]
     pub reflow: Option<ReflowConfig>,
+    /// Project specific rules, matched against the plain text.
+    #[serde(alias = "CustomRules")]
+    #[serde(alias = "custom-rules")]
+    pub custom_rules: Option<CustomRulesConfig>,
+    /// Consistent use of terms across all documentation.
+    #[serde(alias = "Terminology")]
+    pub terminology: Option<TerminologyConfig>,
+    /// Structure of `///` comments, i.e. the summary line and section names.
+    #[serde(alias = "Rustdoc")]
+    #[serde(alias = "RustDoc")]
+    pub rustdoc: Option<RustdocConfig>,
+    /// Doubled words, `a` and `an` and common misspellings, without a
+    /// dictionary.
+    #[serde(alias = "Typos")]
+    pub typos: Option<TyposConfig>,
+    /// Hunspell dictionaries, checked without `libhunspell`.
+    #[serde(alias = "NativeHunspell")]
+    #[serde(alias = "native-hunspell")]
+    pub native_hunspell: Option<HunspellConfig>,
+
+    /// Restrict the checkers run per comment variant.
+    #[serde(default)]
+    #[serde(alias = "VariantCheckers")]
+    #[serde(alias = "variant-checkers")]
+    pub variant_checkers: VariantCheckers,
+
+    /// Severity of the findings and which of them fail a run.
+    #[serde(default)]
+    #[serde(alias = "Severity")]
+    pub severity: SeverityConfig,
 }
 
 impl Config {
@@ -92,6 +151,9 @@ impl Config {
         if let Some(ref mut hunspell) = self.hunspell {
             hunspell.sanitize_paths(base)?;
         }
+        if let Some(ref mut native_hunspell) = self.native_hunspell {
+            native_hunspell.sanitize_paths(base)?;
+        }
         Ok(())
     }
 
@@ -185,6 +247,18 @@ impl Config {
         }
     }
 
+    /// Directory for data derived at runtime, i.e. the extracted bundled dictionary.
+    #[cfg(feature = "bundled-dictionary")]
+    pub fn cache_dir() -> Result<PathBuf> {
+        if let Some(base) =
+            directories::ProjectDirs::from(Self::QUALIFIER, Self::ORGANIZATION, Self::APPLICATION)
+        {
+            Ok(base.cache_dir().to_owned())
+        } else {
+            bail!("No idea where your cache directory is located. `$HOME` must be set.")
+        }
+    }
+
     /// Obtain a project specific config file.
     pub fn project_config(manifest_dir: impl AsRef<Path>) -> Result<PathBuf> {
         let path = manifest_dir
@@ -216,15 +290,20 @@ impl Config {
             Detector::NlpRules => self.nlprules.is_some(),
             Detector::LanguageTool => self.languagetool.is_some(),
             Detector::Reflow => self.reflow.is_some(),
+            Detector::CustomRules => self.custom_rules.is_some(),
+            Detector::Terminology => self.terminology.is_some(),
+            Detector::Rustdoc => self.rustdoc.is_some(),
+            Detector::Typos => self.typos.is_some(),
+            Detector::NativeHunspell => self.native_hunspell.is_some(),
             #[cfg(test)]
             Detector::Dummy => true,
         }
     }
 
     pub fn full() -> Self {
-        let languagetool = LanguageToolConfig {
-            url: url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
-        };
+        let languagetool = LanguageToolConfig::from_url(
+            url::Url::parse("http://127.0.0.1:8010").expect("Default ip must be ok"),
+        );
         Self {
             languagetool: Some(languagetool),
             ..Default::default()
@@ -254,6 +333,7 @@ impl Default for Config {
         Self {
             dev_comments: false,
             skip_readme: false,
+            backup: false,
             hunspell: default_hunspell(),
             nlprules: default_nlprules(),
             // disabled by default, it
@@ -261,6 +341,13 @@ impl Default for Config {
             // deprecated now
             languagetool: None,
             reflow: Some(ReflowConfig::default()),
+            custom_rules: None,
+            terminology: None,
+            rustdoc: None,
+            typos: None,
+            native_hunspell: None,
+            variant_checkers: VariantCheckers::default(),
+            severity: SeverityConfig::default(),
         }
     }
 }
@@ -430,4 +517,71 @@ max_line_length = 42
             42
         );
     }
+
+    #[test]
+    fn partial_10() {
+        let cfg = Config::parse(
+            r#"
+[VariantCheckers]
+dev = ["Hunspell"]
+doc = ["Hunspell", "NlpRules"]
+macro_doc_eq = []
+"#,
+        )
+        .unwrap();
+        assert_eq!(cfg.variant_checkers.dev, Some(vec![Detector::Hunspell]));
+        assert_eq!(cfg.variant_checkers.macro_doc_eq, Some(vec![]));
+        assert_eq!(cfg.variant_checkers.commonmark, None);
+    }
+
+    #[test]
+    fn partial_11() {
+        let cfg = Config::parse(
+            r#"
+[Reflow]
+max_line_length = 42
+strategy = "knuth-plass"
+"#,
+        )
+        .unwrap();
+        let reflow = cfg.reflow.expect("Must contain reflow cfg");
+        assert_eq!(reflow.strategy, ReflowStrategy::Optimal);
+
+        let reflow: ReflowConfig = toml::from_str(r#"strategy = "sentence-per-line""#).unwrap();
+        assert_eq!(reflow.strategy, ReflowStrategy::SentencePerLine);
+    }
+
+    #[test]
+    fn reflow_widths_from_rustfmt() {
+        let reflow: ReflowConfig = toml::from_str(
+            r#"
+max_line_length = 80
+tab_spaces = 8
+rustfmt = true
+"#,
+        )
+        .unwrap();
+        assert_eq!(reflow.tab_width, 8);
+        assert_eq!(reflow.comment_width, None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        let file = src.join("lib.rs");
+
+        // no rustfmt.toml in any parent, so the defaults of rustfmt apply
+        let effective = reflow.for_rust_file(&file).unwrap();
+        assert_eq!(effective.max_line_length, 100);
+        assert_eq!(effective.comment_width, Some(80));
+        assert_eq!(effective.tab_width, 4);
+
+        fs::write(
+            dir.path().join("rustfmt.toml"),
+            "comment_width = 72\nwrap_comments = true\n",
+        )
+        .unwrap();
+        let effective = reflow.for_rust_file(&file).unwrap();
+        assert_eq!(effective.max_line_length, 100);
+        assert_eq!(effective.comment_width, Some(72));
+    }
 }
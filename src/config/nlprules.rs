@@ -2,17 +2,6 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
-#[serde(deny_unknown_fields)]
-pub struct LanguageToolConfig {
-    pub url: url::Url,
-}
-
-impl LanguageToolConfig {
-    pub fn url(&self) -> &url::Url {
-        &self.url
-    }
-}
 #[derive(Deserialize, Serialize, Debug, Clone, Default)]
 #[serde(deny_unknown_fields)]
 pub struct NlpRulesConfig {
@@ -20,4 +9,127 @@ pub struct NlpRulesConfig {
     /// of alternate tokenizer and rules data.
     pub override_rules: Option<PathBuf>,
     pub override_tokenizer: Option<PathBuf>,
+    /// Categories to use even though they are disabled by default,
+    /// i.e. `misspelling` or `typography`.
+    #[serde(default)]
+    #[serde(alias = "enable-categories")]
+    pub enable_categories: Vec<String>,
+    /// Categories of rules to not use, in addition to the ones
+    /// disabled by default.
+    #[serde(default)]
+    #[serde(alias = "disable-categories")]
+    pub disable_categories: Vec<String>,
+    /// Rule ids to not use. The id of a rule group, such as `A_AN`,
+    /// covers all rules of that group, i.e. `A_AN.1`.
+    #[serde(default)]
+    #[serde(alias = "disable-rules")]
+    pub disable_rules: Vec<String>,
+    /// Only use the rules with one of these ids or categories,
+    /// the enabled and disabled categories are ignored then.
+    #[serde(default)]
+    #[serde(alias = "enable-only")]
+    pub enable_only: Option<Vec<String>>,
+}
+
+/// Categories which are not used unless enabled explicitly.
+///
+/// The hunspell backend is aware of custom lingo, which `nlprule` is not, so
+/// `misspelling` would yield a lot of false positives. Anything quotes related
+/// in `typography` is not relevant for code documentation.
+const DEFAULT_DISABLED_CATEGORIES: &[&str] = &["misspelling", "typography"];
+
+/// Whether `pattern` is the rule `id` itself or the id of its rule group.
+pub(crate) fn matches_rule(pattern: &str, id: &str) -> bool {
+    if id.len() < pattern.len() || !id.is_char_boundary(pattern.len()) {
+        return false;
+    }
+    let (head, tail) = id.split_at(pattern.len());
+    head.eq_ignore_ascii_case(pattern) && (tail.is_empty() || tail.starts_with('.'))
+}
+
+fn contains_category<S: AsRef<str>>(categories: &[S], category: &str) -> bool {
+    categories
+        .iter()
+        .any(|c| c.as_ref().eq_ignore_ascii_case(category))
+}
+
+impl NlpRulesConfig {
+    /// Whether the rule `id` of `category` should be used.
+    pub fn is_rule_enabled(&self, id: &str, category: &str) -> bool {
+        if self
+            .disable_rules
+            .iter()
+            .any(|pattern| matches_rule(pattern, id))
+        {
+            return false;
+        }
+        if let Some(ref only) = self.enable_only {
+            return contains_category(only, category)
+                || only.iter().any(|pattern| matches_rule(pattern, id));
+        }
+        if contains_category(&self.disable_categories, category) {
+            return false;
+        }
+        !contains_category(DEFAULT_DISABLED_CATEGORIES, category)
+            || contains_category(&self.enable_categories, category)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(s: &str) -> NlpRulesConfig {
+        toml::from_str(s).expect("Valid nlprules config. qed")
+    }
+
+    #[test]
+    fn default_selection() {
+        let config = NlpRulesConfig::default();
+        assert!(config.is_rule_enabled("A_AN.1", "GRAMMAR"));
+        assert!(!config.is_rule_enabled("MORFOLOGIK_RULE_EN_US", "MISSPELLING"));
+        assert!(!config.is_rule_enabled("EN_QUOTES", "TYPOGRAPHY"));
+    }
+
+    #[test]
+    fn categories() {
+        let config = parse(
+            r#"
+enable_categories = ["typography"]
+disable_categories = ["Grammar"]
+"#,
+        );
+        assert!(!config.is_rule_enabled("A_AN.1", "GRAMMAR"));
+        assert!(config.is_rule_enabled("EN_QUOTES", "TYPOGRAPHY"));
+        assert!(!config.is_rule_enabled("MORFOLOGIK_RULE_EN_US", "MISSPELLING"));
+        assert!(config.is_rule_enabled("PASSIVE_VOICE", "STYLE"));
+    }
+
+    #[test]
+    fn rules() {
+        let config = parse(
+            r#"
+disable_rules = ["a_an", "WHITESPACE_RULE.2"]
+"#,
+        );
+        assert!(!config.is_rule_enabled("A_AN", "GRAMMAR"));
+        assert!(!config.is_rule_enabled("A_AN.3", "GRAMMAR"));
+        assert!(config.is_rule_enabled("A_ANY", "GRAMMAR"));
+        assert!(config.is_rule_enabled("WHITESPACE_RULE.1", "STYLE"));
+        assert!(!config.is_rule_enabled("WHITESPACE_RULE.2", "STYLE"));
+    }
+
+    #[test]
+    fn enable_only() {
+        let config = parse(
+            r#"
+enable_only = ["A_AN", "typography"]
+disable_rules = ["EN_QUOTES"]
+"#,
+        );
+        assert!(config.is_rule_enabled("A_AN.2", "GRAMMAR"));
+        assert!(!config.is_rule_enabled("PASSIVE_VOICE", "STYLE"));
+        assert!(config.is_rule_enabled("DASH_RULE", "TYPOGRAPHY"));
+        assert!(!config.is_rule_enabled("EN_QUOTES", "TYPOGRAPHY"));
+    }
 }
@@ -0,0 +1,308 @@
+//! Track where the effective configuration values originate from.
+
+use super::Config;
+
+use anyhow::{anyhow, Result};
+use fs_err as fs;
+
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Origin of a configuration value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Provenance {
+    /// Builtin default value.
+    Default,
+    /// The user configuration file.
+    User(PathBuf),
+    /// The project specific `.config/spellcheck.toml`.
+    Project(PathBuf),
+    /// A configuration file passed with `--cfg`.
+    Explicit(PathBuf),
+    /// An environment variable.
+    Env(String),
+    /// A command line flag.
+    Cli(String),
+}
+
+impl Provenance {
+    /// The path of the configuration file, if the value originates from one.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::User(path) | Self::Project(path) | Self::Explicit(path) => Some(path.as_path()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => write!(f, "default"),
+            Self::User(path) => write!(f, "user config {}", path.display()),
+            Self::Project(path) => write!(f, "project config {}", path.display()),
+            Self::Explicit(path) => write!(f, "config {}", path.display()),
+            Self::Env(var) => write!(f, "env {}", var),
+            Self::Cli(flag) => write!(f, "flag {}", flag),
+        }
+    }
+}
+
+/// Normalize a key of the configuration file, so all aliases
+/// of a field compare equal.
+fn normalize_key(key: &str) -> String {
+    let key = key.replace(['_', '-'], "").to_lowercase();
+    match key.as_str() {
+        "nlp" => "nlprules".to_owned(),
+        "maxlinewidth" => "maxlinelength".to_owned(),
+        "rule" => "rules".to_owned(),
+        _ => key,
+    }
+}
+
+/// Look up the dotted `key` in the parsed configuration file, ignoring aliases.
+pub(crate) fn find_key<'t>(table: &'t toml::value::Table, key: &str) -> Option<&'t toml::Value> {
+    let mut current = table;
+    let mut segments = key.split('.').peekable();
+    while let Some(segment) = segments.next() {
+        let segment = normalize_key(segment);
+        let value = current
+            .iter()
+            .find(|(key, _)| normalize_key(key) == segment)
+            .map(|(_, value)| value);
+        match (value, segments.peek()) {
+            (Some(value), None) => return Some(value),
+            (Some(toml::Value::Table(table)), Some(_)) => current = table,
+            // the elements of an array of tables can't be told apart
+            (Some(value @ toml::Value::Array(_)), Some(_)) => return Some(value),
+            _ => return None,
+        }
+    }
+    None
+}
+
+/// Quote a key of the configuration, unless it is a bare key.
+fn quote_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_owned()
+    } else {
+        toml::Value::String(key.to_owned()).to_string()
+    }
+}
+
+/// Check if the dotted `key` is present in the parsed configuration file.
+fn contains_key(table: &toml::value::Table, key: &str) -> bool {
+    find_key(table, key).is_some()
+}
+
+/// The effective configuration together with the origin of its values.
+#[derive(Debug, Clone)]
+pub struct EffectiveConfig<'a> {
+    /// The merged configuration.
+    pub config: &'a Config,
+    /// The configuration file that was loaded, if any.
+    pub file: Option<Provenance>,
+    /// Dotted keys, which were overridden by flags or environment variables.
+    /// A trailing `.*` covers all children of a key.
+    pub overrides: Vec<(&'static str, Provenance)>,
+}
+
+impl EffectiveConfig<'_> {
+    /// Determine the origin of the value identified by the dotted `key`.
+    fn provenance(&self, key: &str, file: Option<&toml::value::Table>) -> Provenance {
+        let overridden = self.overrides.iter().find(|(pattern, _)| {
+            if let Some(parent) = pattern.strip_suffix(".*") {
+                key.strip_prefix(parent)
+                    .is_some_and(|rest| rest.starts_with('.'))
+            } else {
+                *pattern == key
+            }
+        });
+        if let Some((_, provenance)) = overridden {
+            return provenance.clone();
+        }
+        match (file, &self.file) {
+            (Some(table), Some(provenance)) if contains_key(table, key) => provenance.clone(),
+            _ => Provenance::Default,
+        }
+    }
+
+    /// Write the values of `table`, followed by its child tables, each
+    /// annotated with its origin. `path` are the keys leading to `table`.
+    fn write_table(
+        &self,
+        writer: &mut impl Write,
+        path: &[&str],
+        table: &toml::value::Table,
+        file: Option<&toml::value::Table>,
+    ) -> Result<()> {
+        let is_table_array = |value: &toml::Value| match value {
+            toml::Value::Array(array) => array.first().map_or(false, toml::Value::is_table),
+            _ => false,
+        };
+        for (key, value) in table {
+            let path = [path, &[key.as_str()]].concat();
+            let provenance = self.provenance(&path.join("."), file);
+            if !value.is_table() && !is_table_array(value) {
+                writeln!(writer, "{} = {} # {}", quote_key(key), value, provenance)?;
+            }
+        }
+        for (key, value) in table {
+            let path = [path, &[key.as_str()]].concat();
+            let provenance = self.provenance(&path.join("."), file);
+            let header = path
+                .iter()
+                .map(|key| quote_key(key))
+                .collect::<Vec<_>>()
+                .join(".");
+            match value {
+                toml::Value::Table(table) => {
+                    writeln!(writer, "\n[{}] # {}", header, provenance)?;
+                    self.write_table(writer, &path, table, file)?;
+                }
+                toml::Value::Array(array) if is_table_array(value) => {
+                    for table in array.iter().filter_map(toml::Value::as_table) {
+                        writeln!(writer, "\n[[{}]] # {}", header, provenance)?;
+                        self.write_table(writer, &path, table, file)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the configuration as `toml`, every value is annotated
+    /// with its origin.
+    pub fn write_to(&self, mut writer: impl Write) -> Result<()> {
+        let file = match self.file.as_ref().and_then(Provenance::path) {
+            Some(path) => {
+                let content = fs::read_to_string(path)?;
+                Some(
+                    toml::from_str::<toml::value::Table>(content.as_str()).map_err(|e| {
+                        anyhow!("Failed to parse config file {}", path.display()).context(e)
+                    })?,
+                )
+            }
+            None => None,
+        };
+        let file = file.as_ref();
+
+        match &self.file {
+            Some(provenance) => writeln!(writer, "# Loaded {}", provenance)?,
+            None => writeln!(writer, "# No configuration file found, using defaults")?,
+        }
+
+        let values = toml::Value::try_from(self.config)
+            .map_err(|e| anyhow!("Failed to convert to toml").context(e))?;
+        if let toml::Value::Table(table) = values {
+            self.write_table(&mut writer, &[], &table, file)?;
+        }
+
+        if let Some(hunspell) = &self.config.hunspell {
+            let lang = hunspell.lang();
+            let lookup = hunspell.lookup_dictionary();
+            writeln!(writer)?;
+            match lookup.found {
+                Some((dic, aff)) => {
+                    writeln!(writer, "# Hunspell dictionary for {}", lang)?;
+                    writeln!(writer, "#   dic: {}", dic.display())?;
+                    writeln!(writer, "#   aff: {}", aff.display())?;
+                }
+                None if hunspell.has_bundled_fallback() => writeln!(
+                    writer,
+                    "# No {lang}.dic / {lang}.aff found in any search dir, using the bundled one",
+                    lang = lang
+                )?,
+                None => writeln!(
+                    writer,
+                    "# No {lang}.dic / {lang}.aff found in any search dir",
+                    lang = lang
+                )?,
+            }
+            if !lookup.rejected.is_empty() {
+                writeln!(writer, "# Rejected search dirs")?;
+                for (search_dir, reason) in lookup.rejected {
+                    writeln!(writer, "#   {}: {}", search_dir.display(), reason)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliased_keys() {
+        let table = toml::from_str::<toml::value::Table>(
+            r#"
+skip-readme = true
+
+[Hunspell]
+lang = "en_US"
+
+[Hunspell.quirks]
+allow_concatenation = true
+
+[NLP]
+override_rules = "/tmp/rules.bin"
+
+[[CustomRules.rule]]
+id = "GITHUB"
+"#,
+        )
+        .unwrap();
+        assert!(contains_key(&table, "skip_readme"));
+        assert!(contains_key(&table, "hunspell.lang"));
+        assert!(contains_key(&table, "hunspell.quirks.allow_concatenation"));
+        assert!(contains_key(&table, "nlprules.override_rules"));
+        assert!(contains_key(&table, "custom_rules.rules.id"));
+        assert!(!contains_key(&table, "hunspell.search_dirs"));
+        assert!(!contains_key(&table, "dev_comments"));
+    }
+
+    #[test]
+    fn quoted_keys() {
+        assert_eq!(quote_key("max_line_length"), "max_line_length");
+        assert_eq!(quote_key("skip-readme"), "skip-readme");
+        assert_eq!(quote_key("GRAMMAR/A_AN"), "\"GRAMMAR/A_AN\"");
+        assert_eq!(quote_key(""), "\"\"");
+    }
+
+    #[test]
+    fn annotated() {
+        let config = Config::default();
+        let effective = EffectiveConfig {
+            config: &config,
+            file: None,
+            overrides: vec![
+                ("hunspell.lang", Provenance::Cli("--lang".to_owned())),
+                (
+                    "variant_checkers.*",
+                    Provenance::Env("CARGO_SPELLCHECK_VARIANT_CHECKERS".to_owned()),
+                ),
+            ],
+        };
+        let mut buf = Vec::new();
+        effective.write_to(&mut buf).unwrap();
+        let annotated = String::from_utf8(buf).unwrap();
+        assert!(annotated.contains("dev_comments = false # default\n"));
+        assert!(annotated.contains("lang = \"en_US\" # flag --lang\n"));
+        assert_eq!(
+            effective.provenance("variant_checkers.dev", None),
+            Provenance::Env("CARGO_SPELLCHECK_VARIANT_CHECKERS".to_owned())
+        );
+        assert_eq!(
+            effective.provenance("variant_checkers", None),
+            Provenance::Default
+        );
+    }
+}
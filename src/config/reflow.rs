@@ -1,5 +1,33 @@
 //! Reflow configuration.
+use anyhow::{anyhow, Result};
+use fs_err as fs;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// How the words of a paragraph are distributed onto lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReflowStrategy {
+    /// Fill each line with as many words as fit, before starting the next.
+    #[value(name = "greedy")]
+    Greedy,
+    /// Minimize the raggedness of all lines of a paragraph as a whole,
+    /// similar to the Knuth-Plass line breaking of TeX.
+    #[serde(alias = "knuth-plass", alias = "knuth_plass")]
+    #[value(name = "optimal", alias = "knuth-plass")]
+    Optimal,
+    /// Start each sentence on a new line, sentences exceeding the line width
+    /// are wrapped greedily.
+    #[serde(alias = "sentence_per_line", alias = "semantic")]
+    #[value(name = "sentence-per-line", alias = "semantic")]
+    SentencePerLine,
+}
+
+impl Default for ReflowStrategy {
+    fn default() -> Self {
+        Self::Greedy
+    }
+}
 
 /// Parameters for wrapping doc comments
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -8,12 +36,95 @@ pub struct ReflowConfig {
     #[serde(default)]
     #[serde(alias = "max_line_width")]
     pub(crate) max_line_length: usize,
+    /// The line breaking strategy.
+    #[serde(default)]
+    pub(crate) strategy: ReflowStrategy,
+    /// Number of columns between two tab stops, used to measure the
+    /// indentation of lines.
+    #[serde(default = "default_tab_width")]
+    #[serde(alias = "tab_spaces")]
+    pub(crate) tab_width: usize,
+    /// Limit for the width of doc comments, measured from the comment prefix
+    /// onwards, so excluding the indentation, like rustfmt's `comment_width`.
+    #[serde(default)]
+    pub(crate) comment_width: Option<usize>,
+    /// Use `max_width`, `comment_width` and `tab_spaces` of the `rustfmt.toml`
+    /// closest to each rust file, or the rustfmt defaults if there is none.
+    #[serde(default)]
+    #[serde(alias = "match_rustfmt")]
+    pub(crate) rustfmt: bool,
+    /// Also reflow `//` and `/* */` developer comments, given `dev_comments` are checked.
+    #[serde(default)]
+    #[serde(alias = "dev-comments")]
+    pub(crate) dev_comments: bool,
 }
 
 impl Default for ReflowConfig {
     fn default() -> Self {
         Self {
             max_line_length: 80,
+            strategy: ReflowStrategy::default(),
+            tab_width: default_tab_width(),
+            comment_width: None,
+            rustfmt: false,
+            dev_comments: false,
+        }
+    }
+}
+
+fn default_tab_width() -> usize {
+    4
+}
+
+/// The subset of the rustfmt configuration concerning comment widths.
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+struct RustfmtConfig {
+    max_width: usize,
+    comment_width: usize,
+    tab_spaces: usize,
+}
+
+impl Default for RustfmtConfig {
+    fn default() -> Self {
+        Self {
+            max_width: 100,
+            comment_width: 80,
+            tab_spaces: 4,
+        }
+    }
+}
+
+impl ReflowConfig {
+    /// The effective configuration for the rust file at `path`, which only
+    /// differs if the widths are taken from rustfmt.
+    pub(crate) fn for_rust_file(&self, path: &Path) -> Result<Self> {
+        if !self.rustfmt {
+            return Ok(self.clone());
         }
+        let rustfmt = path
+            .ancestors()
+            .skip(1)
+            .flat_map(|dir| {
+                ["rustfmt.toml", ".rustfmt.toml"]
+                    .iter()
+                    .map(move |name| dir.join(name))
+            })
+            .find(|candidate| candidate.is_file());
+        let rustfmt = if let Some(rustfmt) = rustfmt {
+            log::debug!("Using widths of {}", rustfmt.display());
+            let content = fs::read_to_string(&rustfmt)?;
+            toml::from_str::<RustfmtConfig>(&content).map_err(|e| {
+                anyhow!("Failed to parse rustfmt config {}", rustfmt.display()).context(e)
+            })?
+        } else {
+            RustfmtConfig::default()
+        };
+        Ok(Self {
+            max_line_length: rustfmt.max_width,
+            tab_width: rustfmt.tab_spaces,
+            comment_width: Some(rustfmt.comment_width),
+            ..self.clone()
+        })
     }
 }
@@ -0,0 +1,40 @@
+//! Structure of rustdoc comments, i.e. the summary line and section headings.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RustdocConfig {
+    /// The first paragraph must be a single sentence, ending in a period.
+    #[serde(default = "default_true")]
+    #[serde(alias = "summary-sentence")]
+    pub summary_sentence: bool,
+    /// Doc comments without any content.
+    #[serde(default = "default_true")]
+    #[serde(alias = "empty-docs")]
+    pub empty_docs: bool,
+    /// Misspelled or differently named conventional sections, i.e. `# Example`
+    /// instead of `# Examples`.
+    #[serde(default = "default_true")]
+    #[serde(alias = "section-names")]
+    pub section_names: bool,
+    /// Documentation of an `unsafe fn` must have a `# Safety` section.
+    #[serde(default = "default_true")]
+    #[serde(alias = "safety-section")]
+    pub safety_section: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for RustdocConfig {
+    fn default() -> Self {
+        Self {
+            summary_sentence: true,
+            empty_docs: true,
+            section_names: true,
+            safety_section: true,
+        }
+    }
+}
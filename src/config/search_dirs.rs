@@ -1,7 +1,7 @@
 use super::*;
 
 /// Obtain OS specific search directories.
-fn os_specific_search_dirs() -> &'static [PathBuf] {
+pub fn os_specific_search_dirs() -> &'static [PathBuf] {
     lazy_static::lazy_static! {
         static ref OS_SPECIFIC_LOOKUP_DIRS: Vec<PathBuf> =
             if cfg!(target_os = "macos") {
@@ -0,0 +1,161 @@
+//! Severity of findings per detector and rule, and which ones fail a run.
+
+use super::nlprules::matches_rule;
+use crate::{Detector, RuleId, Severity};
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+/// The severity of the findings of each detector, optionally refined per rule.
+///
+/// All findings are errors unless configured otherwise.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct SeverityConfig {
+    /// Fail a run with `check` or `reflow --check` if there is a finding at
+    /// least this severe, instead of only using the `--code` override.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "fail-on")]
+    pub fail_on: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "Hunspell")]
+    pub hunspell: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "NlpRules")]
+    pub nlprules: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "LanguageTool")]
+    pub languagetool: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "Reflow")]
+    pub reflow: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "CustomRules")]
+    #[serde(alias = "custom-rules")]
+    pub custom_rules: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "Terminology")]
+    pub terminology: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "Rustdoc")]
+    pub rustdoc: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "Typos")]
+    pub typos: Option<Severity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "NativeHunspell")]
+    #[serde(alias = "native-hunspell")]
+    pub native_hunspell: Option<Severity>,
+    /// Severity by rule id, category or both as `CATEGORY/RULE_ID`, which
+    /// take precedence over the one of the detector.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub rules: IndexMap<String, Severity>,
+}
+
+impl SeverityConfig {
+    /// The configured severity of the detector, if any.
+    fn detector(&self, detector: Detector) -> Option<Severity> {
+        match detector {
+            Detector::Hunspell => self.hunspell,
+            Detector::NlpRules => self.nlprules,
+            Detector::LanguageTool => self.languagetool,
+            Detector::Reflow => self.reflow,
+            Detector::CustomRules => self.custom_rules,
+            Detector::Terminology => self.terminology,
+            Detector::Rustdoc => self.rustdoc,
+            Detector::Typos => self.typos,
+            Detector::NativeHunspell => self.native_hunspell,
+            #[cfg(test)]
+            Detector::Dummy => None,
+        }
+    }
+
+    /// The configured severity of a finding of `detector` by `rule`, if any.
+    ///
+    /// The most specific entry of `rules` wins, a combined `CATEGORY/RULE_ID`
+    /// over a rule id over a category, and longer rule ids over the id of
+    /// their group.
+    pub fn severity(&self, detector: Detector, rule: Option<&RuleId>) -> Option<Severity> {
+        let specificity = |key: &str, rule: &RuleId| -> Option<(u8, usize)> {
+            if let Some((category, id)) = key.split_once('/') {
+                if category.eq_ignore_ascii_case(&rule.category) && matches_rule(id, &rule.id) {
+                    return Some((3, id.len()));
+                }
+                None
+            } else if matches_rule(key, &rule.id) {
+                Some((2, key.len()))
+            } else if key.eq_ignore_ascii_case(&rule.category) {
+                Some((1, 0))
+            } else {
+                None
+            }
+        };
+        rule.and_then(|rule| {
+            self.rules
+                .iter()
+                .filter_map(|(key, severity)| Some((specificity(key, rule)?, *severity)))
+                .max_by_key(|(specificity, _severity)| *specificity)
+                .map(|(_specificity, severity)| severity)
+        })
+        .or_else(|| self.detector(detector))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(category: &str, id: &str) -> RuleId {
+        RuleId {
+            category: category.to_owned(),
+            id: id.to_owned(),
+        }
+    }
+
+    #[test]
+    fn unconfigured() {
+        let config = SeverityConfig::default();
+        assert_eq!(config.severity(Detector::Hunspell, None), None);
+        assert_eq!(
+            config.severity(Detector::NlpRules, Some(&rule("GRAMMAR", "A_AN"))),
+            None
+        );
+    }
+
+    #[test]
+    fn most_specific_wins() {
+        let config: SeverityConfig = toml::from_str(
+            r#"
+fail_on = "warnings"
+NlpRules = "warning"
+Reflow = "off"
+
+[rules]
+STYLE = "info"
+"A_AN" = "warning"
+"A_AN.2" = "error"
+"grammar/COMMA" = "off"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.fail_on, Some(Severity::Warning));
+        assert_eq!(config.severity(Detector::Reflow, None), Some(Severity::Off));
+        assert_eq!(config.severity(Detector::Hunspell, None), None);
+
+        let severity =
+            |category, id| config.severity(Detector::NlpRules, Some(&rule(category, id)));
+        assert_eq!(severity("GRAMMAR", "SOME_RULE"), Some(Severity::Warning));
+        assert_eq!(severity("STYLE", "SOME_RULE"), Some(Severity::Info));
+        assert_eq!(severity("STYLE", "A_AN.1"), Some(Severity::Warning));
+        assert_eq!(severity("STYLE", "A_AN.2"), Some(Severity::Error));
+        assert_eq!(severity("GRAMMAR", "COMMA.4"), Some(Severity::Off));
+        assert_eq!(severity("STYLE", "COMMA.4"), Some(Severity::Info));
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(Severity::Off < Severity::Info);
+        assert!(Severity::Info < Severity::Warning);
+        assert!(Severity::Warning < Severity::Error);
+    }
+}
@@ -0,0 +1,35 @@
+//! Consistent use of terms across all documentation.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TerminologyConfig {
+    /// Preferred spelling of terms, every other variant of them is reported,
+    /// even if it is the dominant one.
+    #[serde(default)]
+    #[serde(alias = "preferred")]
+    pub glossary: Vec<String>,
+    /// Terms whose variants are never reported.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Treat British and American spelling, i.e. `serialise` and `serialize`,
+    /// as variants of the same term.
+    #[serde(default = "default_spelling_variants")]
+    #[serde(alias = "spelling-variants")]
+    pub spelling_variants: bool,
+}
+
+fn default_spelling_variants() -> bool {
+    true
+}
+
+impl Default for TerminologyConfig {
+    fn default() -> Self {
+        Self {
+            glossary: Vec::new(),
+            ignore: Vec::new(),
+            spelling_variants: default_spelling_variants(),
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Mistakes which can be found without any dictionary.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TyposConfig {
+    /// The same word twice in a row, also across lines.
+    #[serde(default = "default_true")]
+    #[serde(alias = "doubled-words")]
+    pub doubled_words: bool,
+    /// `a` before a vowel sound or `an` before a consonant sound.
+    #[serde(default = "default_true")]
+    #[serde(alias = "a-an")]
+    pub a_an: bool,
+    /// Common misspellings of English words from a bundled list.
+    #[serde(default = "default_true")]
+    pub misspellings: bool,
+    /// Words never reported as misspelling, i.e. names which are on the list.
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for TyposConfig {
+    fn default() -> Self {
+        Self {
+            doubled_words: true,
+            a_an: true,
+            misspellings: true,
+            ignore: Vec::new(),
+        }
+    }
+}
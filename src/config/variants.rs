@@ -0,0 +1,137 @@
+//! Selection of checkers per comment variant.
+
+use crate::documentation::CommentVariant;
+use crate::Detector;
+
+use serde::{Deserialize, Serialize};
+
+/// Restricts the checkers run on a particular comment variant.
+///
+/// A variant that is not listed is checked by all enabled checkers,
+/// an empty list disables all checks for that variant. The specific
+/// variants take precedence over the `doc` and `dev` groups.
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct VariantCheckers {
+    /// All documentation comments, `///`, `//!`, `/** */`, `/*! */` and `#[doc = ".."]`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<Vec<Detector>>,
+    /// All developer comments, `//` and `/* */`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dev: Option<Vec<Detector>>,
+    /// `///`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "triple-slash")]
+    pub triple_slash: Option<Vec<Detector>>,
+    /// `//!`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "double-slash-em")]
+    pub double_slash_em: Option<Vec<Detector>>,
+    /// `/** */`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "slash-star-star")]
+    pub slash_star_star: Option<Vec<Detector>>,
+    /// `/*! */`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "slash-star-em")]
+    pub slash_star_em: Option<Vec<Detector>>,
+    /// `#[doc = ".."]`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "macro-doc-eq")]
+    pub macro_doc_eq: Option<Vec<Detector>>,
+    /// Common mark files, i.e. `README.md`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "common_mark")]
+    pub commonmark: Option<Vec<Detector>>,
+    /// `//`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "double-slash")]
+    pub double_slash: Option<Vec<Detector>>,
+    /// `/* */`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "slash-star")]
+    pub slash_star: Option<Vec<Detector>>,
+}
+
+impl VariantCheckers {
+    /// Obtain the selected detectors for a comment variant,
+    /// `None` if there is no restriction.
+    pub fn detectors(&self, variant: &CommentVariant) -> Option<&[Detector]> {
+        let (specific, group) = match variant {
+            CommentVariant::TripleSlash => (&self.triple_slash, &self.doc),
+            CommentVariant::DoubleSlashEM => (&self.double_slash_em, &self.doc),
+            CommentVariant::MacroDocEq(..) => (&self.macro_doc_eq, &self.doc),
+            CommentVariant::CommonMark => (&self.commonmark, &None),
+            CommentVariant::DoubleSlash => (&self.double_slash, &self.dev),
+            CommentVariant::SlashStar { .. } => (&self.slash_star, &self.dev),
+            CommentVariant::SlashStarStar { .. } => (&self.slash_star_star, &self.doc),
+            CommentVariant::SlashStarEM { .. } => (&self.slash_star_em, &self.doc),
+            _ => (&None, &None),
+        };
+        specific.as_ref().or(group.as_ref()).map(Vec::as_slice)
+    }
+
+    /// Obtain the selection of a variant by its name as used in the configuration file.
+    pub fn slot_mut(&mut self, name: &str) -> Option<&mut Option<Vec<Detector>>> {
+        Some(match name {
+            "doc" => &mut self.doc,
+            "dev" => &mut self.dev,
+            "triple_slash" | "triple-slash" => &mut self.triple_slash,
+            "double_slash_em" | "double-slash-em" => &mut self.double_slash_em,
+            "slash_star_star" | "slash-star-star" => &mut self.slash_star_star,
+            "slash_star_em" | "slash-star-em" => &mut self.slash_star_em,
+            "macro_doc_eq" | "macro-doc-eq" => &mut self.macro_doc_eq,
+            "commonmark" | "common_mark" => &mut self.commonmark,
+            "double_slash" | "double-slash" => &mut self.double_slash,
+            "slash_star" | "slash-star" => &mut self.slash_star,
+            _ => return None,
+        })
+    }
+
+    /// Check if the `detector` shall run on the given comment variant.
+    pub fn is_selected(&self, variant: &CommentVariant, detector: Detector) -> bool {
+        match self.detectors(variant) {
+            Some(detectors) => detectors.contains(&detector),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn precedence() {
+        let variants = VariantCheckers {
+            doc: Some(vec![Detector::Hunspell, Detector::NlpRules]),
+            dev: Some(vec![Detector::Hunspell]),
+            macro_doc_eq: Some(vec![]),
+            slash_star_em: Some(vec![Detector::Reflow]),
+            ..Default::default()
+        };
+        assert!(variants.is_selected(&CommentVariant::TripleSlash, Detector::NlpRules));
+        assert!(variants.is_selected(&CommentVariant::DoubleSlash, Detector::Hunspell));
+        assert!(!variants.is_selected(
+            &CommentVariant::SlashStar { gutter: true },
+            Detector::NlpRules
+        ));
+        assert!(!variants.is_selected(
+            &CommentVariant::MacroDocEq("#[doc=".to_owned(), 0),
+            Detector::Hunspell
+        ));
+        assert!(variants.is_selected(&CommentVariant::CommonMark, Detector::LanguageTool));
+        assert!(variants.is_selected(
+            &CommentVariant::SlashStarStar { gutter: true },
+            Detector::NlpRules
+        ));
+        assert!(!variants.is_selected(
+            &CommentVariant::SlashStarEM { gutter: false },
+            Detector::Hunspell
+        ));
+        assert!(variants.is_selected(
+            &CommentVariant::SlashStarEM { gutter: false },
+            Detector::Reflow
+        ));
+    }
+}
@@ -0,0 +1,394 @@
+//! Diagnose the setup, so misconfigurations surface with a cause.
+//!
+//! Inspects the compiled in checkers, the configuration file,
+//! the dictionary search dirs and the available dictionaries,
+//! the extra dictionaries and the `transform_regex` quirks.
+
+use crate::config::{
+    find_key, hunspell_dic_format_errors, os_specific_search_dirs, HunspellConfig, Provenance,
+};
+use crate::{Config, Detector};
+
+use anyhow::Result;
+use fancy_regex::Regex;
+use fs_err as fs;
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Tokens of varying shape, a regex that matches all of them
+/// matches virtually every word.
+const CATCH_ALL_PROBES: &[&str] = &[
+    "", "a", "Word", "x86_64", "0x1F", "foo-bar", "'s'", "über", "1.0",
+];
+
+/// Collects the findings and counts the problems.
+struct Report<W: Write> {
+    writer: W,
+    sections: usize,
+    problems: usize,
+}
+
+impl<W: Write> Report<W> {
+    fn section(&mut self, title: &str) -> io::Result<()> {
+        if self.sections > 0 {
+            writeln!(self.writer)?;
+        }
+        self.sections += 1;
+        writeln!(self.writer, "{}", title)
+    }
+
+    fn ok(&mut self, msg: impl AsRef<str>) -> io::Result<()> {
+        writeln!(self.writer, "  [ok] {}", msg.as_ref())
+    }
+
+    fn note(&mut self, msg: impl AsRef<str>) -> io::Result<()> {
+        writeln!(self.writer, "  [--] {}", msg.as_ref())
+    }
+
+    fn problem(&mut self, msg: impl AsRef<str>) -> io::Result<()> {
+        self.problems += 1;
+        writeln!(self.writer, "  [!!] {}", msg.as_ref())
+    }
+}
+
+/// Run all diagnostics against `config`, which was derived from
+/// the configuration `file`, and write the findings to `writer`.
+///
+/// Returns the number of problems found.
+pub fn diagnose(config: &Config, file: &Provenance, writer: impl Write) -> Result<usize> {
+    let mut report = Report {
+        writer,
+        sections: 0,
+        problems: 0,
+    };
+
+    let raw = check_config_file(&mut report, file)?;
+
+    report.section("Checkers")?;
+    for (detector, compiled) in Detector::list_available() {
+        let enabled = config.is_enabled(detector);
+        // the defaults enable checkers regardless of the compiled in features
+        let configured = raw
+            .as_ref()
+            .and_then(|raw| find_key(raw, detector.as_str()))
+            .is_some();
+        match (compiled, enabled) {
+            (true, true) => report.ok(format!("{} compiled in and enabled", detector))?,
+            (true, false) => report.note(format!("{} compiled in, but disabled", detector))?,
+            (false, true) if configured => report.problem(format!(
+                "{} is configured, but not compiled in, enable feature `{}`",
+                detector,
+                detector.as_str().to_lowercase()
+            ))?,
+            (false, _) => report.note(format!("{} not compiled in", detector))?,
+        }
+    }
+
+    if let Some(nlprules) = &config.nlprules {
+        let overrides = [
+            ("override_rules", &nlprules.override_rules),
+            ("override_tokenizer", &nlprules.override_tokenizer),
+        ];
+        if overrides.iter().any(|(_, path)| path.is_some()) {
+            report.section("NlpRules")?;
+            for (key, path) in &overrides {
+                if let Some(path) = path {
+                    if path.is_file() {
+                        report.ok(format!("{} {}", key, path.display()))?;
+                    } else {
+                        report.problem(format!("{} {} is not a file", key, path.display()))?;
+                    }
+                }
+            }
+        }
+    }
+
+    match &config.hunspell {
+        Some(hunspell) => check_hunspell(&mut report, hunspell, file, raw.as_ref())?,
+        None => {
+            report.section("Hunspell")?;
+            report.note("disabled, skipping dictionary checks")?;
+        }
+    }
+
+    writeln!(report.writer)?;
+    match report.problems {
+        0 => writeln!(report.writer, "No problems found.")?,
+        1 => writeln!(report.writer, "Found 1 problem.")?,
+        n => writeln!(report.writer, "Found {} problems.", n)?,
+    }
+    Ok(report.problems)
+}
+
+/// Check the configuration file can be loaded, returns the raw content for further inspection.
+fn check_config_file<W: Write>(
+    report: &mut Report<W>,
+    file: &Provenance,
+) -> Result<Option<toml::value::Table>> {
+    report.section("Configuration")?;
+    let path = file
+        .path()
+        .expect("Provenance of a config file always has a path. qed");
+    if !path.is_file() {
+        if let Provenance::Explicit(_) = file {
+            report.problem(format!("{} does not exist", file))?;
+        } else {
+            report.note("No config file found, using defaults")?;
+        }
+        return Ok(None);
+    }
+
+    match Config::load_from(path) {
+        Ok(_) => report.ok(format!("Loaded {}", file))?,
+        Err(e) => report.problem(format!("Failed to load {}: {:?}", file, e))?,
+    }
+    let raw = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| toml::from_str::<toml::value::Table>(&content).ok());
+
+    // report the individual regular expressions, since loading
+    // the config bails on the first one that does not compile
+    let transform_regex = raw
+        .as_ref()
+        .and_then(|raw| find_key(raw, "hunspell.quirks.transform_regex"))
+        .and_then(toml::Value::as_array);
+    for (idx, pattern) in transform_regex.into_iter().flatten().enumerate() {
+        if let Some(pattern) = pattern.as_str() {
+            if let Err(e) = Regex::new(pattern) {
+                report.problem(format!(
+                    "transform_regex[{}] `{}` does not compile: {}",
+                    idx, pattern, e
+                ))?;
+            }
+        }
+    }
+    Ok(raw)
+}
+
+/// Search dirs as given in the configuration file, resolved against the file location.
+///
+/// Search dirs that do not exist are dropped when loading the configuration,
+/// so they have to be obtained from the raw file content.
+fn configured_search_dirs(file: &Provenance, raw: Option<&toml::value::Table>) -> Vec<PathBuf> {
+    let base = file.path().and_then(Path::parent);
+    raw.and_then(|raw| find_key(raw, "hunspell.search_dirs"))
+        .and_then(toml::Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(toml::Value::as_str)
+        .map(|dir| match base {
+            Some(base) => base.join(dir),
+            None => PathBuf::from(dir),
+        })
+        .collect()
+}
+
+/// All `.dic` and `.aff` pairs in `dir`, keyed by language.
+fn dictionary_pairs(dir: &Path) -> BTreeMap<String, PathBuf> {
+    let mut pairs = BTreeMap::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return pairs,
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let dic = entry.path();
+        if dic.extension().map_or(false, |ext| ext == "dic") && dic.with_extension("aff").is_file()
+        {
+            if let Some(lang) = dic.file_stem().and_then(|stem| stem.to_str()) {
+                pairs.insert(lang.to_owned(), dic.with_extension(""));
+            }
+        }
+    }
+    pairs
+}
+
+/// Validate a `.dic` file, every violation is reported with its line number.
+fn check_dic<W: Write>(report: &mut Report<W>, dic: &Path) -> Result<bool> {
+    let reader = match fs::File::open(dic) {
+        Ok(file) => io::BufReader::new(file),
+        Err(e) => {
+            report.problem(format!("{}", e))?;
+            return Ok(false);
+        }
+    };
+    let errors = hunspell_dic_format_errors(reader, usize::MAX)?;
+    for error in errors.iter() {
+        report.problem(format!(
+            "{}:{}: {}",
+            dic.display(),
+            error.lineno,
+            error.reason
+        ))?;
+    }
+    Ok(errors.is_empty())
+}
+
+/// Index of the first regex that matches everything `regex` matches, if any.
+fn shadowed_by(earlier: &[(usize, &Regex)], regex: &Regex) -> Option<usize> {
+    earlier.iter().find_map(|(idx, candidate)| {
+        let identical = candidate.as_str() == regex.as_str();
+        let catch_all = CATCH_ALL_PROBES
+            .iter()
+            .all(|probe| candidate.is_match(probe).unwrap_or(false));
+        if identical || catch_all {
+            Some(*idx)
+        } else {
+            None
+        }
+    })
+}
+
+fn check_hunspell<W: Write>(
+    report: &mut Report<W>,
+    hunspell: &HunspellConfig,
+    file: &Provenance,
+    raw: Option<&toml::value::Table>,
+) -> Result<()> {
+    report.section("Search dirs")?;
+    let os_specific = os_specific_search_dirs();
+    let mut search_dirs = hunspell
+        .search_dirs()
+        .iter()
+        .filter(|dir| !os_specific.contains(dir))
+        .cloned()
+        .collect::<Vec<_>>();
+    for dir in configured_search_dirs(file, raw) {
+        if !search_dirs
+            .iter()
+            .any(|known| known == &dir || dir.canonicalize().map_or(false, |dir| known == &dir))
+        {
+            search_dirs.push(dir);
+        }
+    }
+    for dir in search_dirs.iter() {
+        if dir.is_dir() {
+            report.ok(format!("{} (configured)", dir.display()))?;
+        } else {
+            report.problem(format!("{} (configured) is not a directory", dir.display()))?;
+        }
+    }
+    for dir in os_specific {
+        if dir.is_dir() {
+            report.ok(format!("{} (os specific)", dir.display()))?;
+        } else {
+            report.note(format!("{} (os specific) does not exist", dir.display()))?;
+        }
+    }
+
+    report.section("Dictionaries")?;
+    let mut found_any = false;
+    for dir in search_dirs.iter().chain(os_specific.iter()) {
+        for (lang, base) in dictionary_pairs(dir) {
+            found_any = true;
+            report.note(format!("{} {}.{{dic,aff}}", lang, base.display()))?;
+        }
+    }
+    if !found_any {
+        report.problem("No .dic / .aff pairs found in any search dir")?;
+    }
+
+    let lang = hunspell.lang();
+    report.section(&format!("Dictionary for {}", lang))?;
+    let lookup = hunspell.lookup_dictionary();
+    match lookup.found {
+        Some((dic, aff)) => {
+            report.ok(format!("dic: {}", dic.display()))?;
+            report.ok(format!("aff: {}", aff.display()))?;
+            check_dic(report, &dic)?;
+        }
+        None if hunspell.has_bundled_fallback() => {
+            report.ok(format!(
+                "No {lang}.dic / {lang}.aff found in any search dir, using the bundled one",
+                lang = lang
+            ))?;
+        }
+        None => {
+            report.problem(format!(
+                "No {lang}.dic / {lang}.aff found in any search dir",
+                lang = lang
+            ))?;
+            for (dir, reason) in lookup.rejected {
+                report.note(format!("{}: {}", dir.display(), reason))?;
+            }
+        }
+    }
+
+    if !hunspell.extra_dictionaries().is_empty() {
+        report.section("Extra dictionaries")?;
+        for extra_dic in hunspell.extra_dictionaries() {
+            if !extra_dic.is_file() {
+                report.problem(format!("{} is not a file", extra_dic.display()))?;
+            } else if check_dic(report, extra_dic)? {
+                report.ok(format!("{}", extra_dic.display()))?;
+            }
+        }
+    }
+
+    let transform_regex = hunspell.quirks.transform_regex();
+    if !transform_regex.is_empty() {
+        report.section("Transform regex")?;
+        let mut earlier = Vec::with_capacity(transform_regex.len());
+        for (idx, regex) in transform_regex.iter().enumerate() {
+            match shadowed_by(&earlier, regex) {
+                Some(first) => report.problem(format!(
+                    "transform_regex[{}] `{}` is shadowed by transform_regex[{}] `{}`",
+                    idx,
+                    regex.as_str(),
+                    first,
+                    transform_regex[first].as_str()
+                ))?,
+                None => report.ok(format!("transform_regex[{}] `{}`", idx, regex.as_str()))?,
+            }
+            earlier.push((idx, &regex.0));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WrappedRegex;
+
+    #[test]
+    fn shadowed_transform_regex() {
+        let regexes = vec!["^[0-9]+x$", ".*", "^'([^\\s])'$", "^[0-9]+x$"]
+            .into_iter()
+            .map(|pattern| Regex::new(pattern).unwrap())
+            .collect::<Vec<_>>();
+        let earlier = regexes.iter().enumerate().collect::<Vec<_>>();
+        assert_eq!(shadowed_by(&earlier[..1], &regexes[1]), None);
+        assert_eq!(shadowed_by(&earlier[..2], &regexes[2]), Some(1));
+        assert_eq!(shadowed_by(&earlier[..1], &regexes[3]), Some(0));
+    }
+
+    #[test]
+    fn problems_are_counted() {
+        let dir = tempfile::tempdir().unwrap();
+        let extra = dir.path().join("lingo.dic");
+        fs::write(&extra, "2\nfoo\n42\n").unwrap();
+        fs::write(dir.path().join("xx_YY.dic"), "1\nfoo\n").unwrap();
+        fs::write(dir.path().join("xx_YY.aff"), "SET UTF-8\n").unwrap();
+
+        let mut config = Config::default();
+        let hunspell = config.hunspell.as_mut().unwrap();
+        hunspell.lang = Some("xx_YY".to_owned());
+        hunspell.search_dirs = vec![dir.path().to_owned(), dir.path().join("missing")].into();
+        hunspell.extra_dictionaries = vec![extra];
+        hunspell.quirks.transform_regex = vec![
+            WrappedRegex(Regex::new("^[0-9]+x$").unwrap()),
+            WrappedRegex(Regex::new("^[0-9]+x$").unwrap()),
+        ];
+
+        let file = Provenance::User(dir.path().join("config.toml"));
+        let mut out = Vec::new();
+        let problems = diagnose(&config, &file, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("xx_YY"), "{}", out);
+        assert!(out.contains("lingo.dic:3:"), "{}", out);
+        // missing search dir, malformed extra dictionary, shadowed regex
+        assert_eq!(problems, 3, "{}", out);
+    }
+}
@@ -78,6 +78,8 @@ pub struct CheckableChunk {
     source_mapping: IndexMap<Range, Span>,
     /// Track what kind of comment the chunk is.
     variant: CommentVariant,
+    /// The chunk documents an `unsafe fn`.
+    unsafe_fn: bool,
 }
 
 impl std::hash::Hash for CheckableChunk {
@@ -116,9 +118,16 @@ impl CheckableChunk {
             content,
             source_mapping,
             variant,
+            unsafe_fn: false,
         }
     }
 
+    /// Mark the chunk as documentation of an `unsafe fn`.
+    pub(crate) fn with_unsafe_fn(mut self, unsafe_fn: bool) -> Self {
+        self.unsafe_fn = unsafe_fn;
+        self
+    }
+
     /// Find which part of the range maps to which span.
     /// Note that Range can very well be split into multiple fragments
     /// where each of them can be mapped to a potentially non-continuous
@@ -384,6 +393,11 @@ impl CheckableChunk {
     pub fn variant(&self) -> CommentVariant {
         self.variant.clone()
     }
+
+    /// Check if the chunk documents an `unsafe fn`.
+    pub fn is_unsafe_fn(&self) -> bool {
+        self.unsafe_fn
+    }
 }
 
 /// Convert the clusters of one file into a source description as well
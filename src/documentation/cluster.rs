@@ -11,13 +11,28 @@ use std::convert::TryFrom;
 #[derive(Debug)]
 pub struct Clusters {
     pub(super) set: Vec<LiteralSet>,
+    /// The number of literals added so far.
+    count: usize,
 }
 
 impl Clusters {
     /// Only works if the file is processed line by line, otherwise
     /// requires a adjacency list.
     fn process_literal(&mut self, source: &str, literal: proc_macro2::Literal) -> Result<()> {
-        let literal = TrimmedLiteral::try_from((source, literal))?;
+        // block doc comments span multiple lines, which are split up
+        let literals = match TrimmedLiteral::from_block_doc(source, &literal)? {
+            Some(literals) => literals,
+            None => vec![TrimmedLiteral::try_from((source, literal))?],
+        };
+        for literal in literals {
+            self.add_literal(literal);
+        }
+        Ok(())
+    }
+
+    /// Append the literal to the last set, or start a new one if not adjacent.
+    fn add_literal(&mut self, literal: TrimmedLiteral) {
+        self.count += 1;
         if let Some(cls) = self.set.last_mut() {
             if let Err(literal) = cls.add_adjacent(literal) {
                 trace!(target: "documentation",
@@ -32,15 +47,29 @@ impl Clusters {
         } else {
             self.set.push(LiteralSet::from(literal));
         }
-        Ok(())
     }
 
     /// Helper function to parse a stream and associated the found literals
     fn parse_token_tree(&mut self, source: &str, stream: proc_macro2::TokenStream) -> Result<()> {
+        // the set of the preceding outer doc attributes and if the item they
+        // document is `unsafe` so far
+        let mut documented: Option<(usize, bool)> = None;
+        let mut inner_attribute = false;
+        let mut previous_hash = false;
         let mut iter = stream.into_iter();
         while let Some(tree) = iter.next() {
+            let hash = matches!(&tree, TokenTree::Punct(punct) if punct.as_char() == '#');
+            let after_hash = std::mem::replace(&mut previous_hash, hash);
             match tree {
                 TokenTree::Ident(ident) => {
+                    if let Some((idx, is_unsafe)) = documented.as_mut() {
+                        if ident == "unsafe" {
+                            *is_unsafe = true;
+                        } else if ident == "fn" {
+                            self.set[*idx].unsafe_fn = *is_unsafe;
+                            documented = None;
+                        }
+                    }
                     // if we find an identifier
                     // which is doc
                     if ident != "doc" {
@@ -87,7 +116,25 @@ impl Clusters {
                     }
                 }
                 TokenTree::Group(group) => {
+                    let count = self.count;
                     self.parse_token_tree(source, group.stream())?;
+                    match group.delimiter() {
+                        proc_macro2::Delimiter::Bracket => {
+                            if self.count > count && !inner_attribute {
+                                documented = Some((self.set.len() - 1, false));
+                            }
+                            inner_attribute = false;
+                        }
+                        proc_macro2::Delimiter::Brace => documented = None,
+                        _ => {}
+                    }
+                }
+                TokenTree::Punct(punct) => {
+                    if punct.as_char() == '!' && after_hash {
+                        inner_attribute = true;
+                    } else if punct.as_char() == ';' {
+                        documented = None;
+                    }
                 }
                 _ => {}
             };
@@ -114,6 +161,7 @@ impl Clusters {
     pub(crate) fn load_from_str(source: &str, dev_comments: bool) -> Result<Self> {
         let mut chunk = Self {
             set: Vec::with_capacity(64),
+            count: 0,
         };
         let stream = syn::parse_str::<proc_macro2::TokenStream>(source)
             .map_err(|e| anyhow!("Failed to parse content to stream").context(e))?;
@@ -119,7 +119,14 @@ impl TokenWithType {
     /// Convert a `TokenWithLineColumn` to a `TokenWithType`. The kind is worked out from the content
     /// by checking against the developer block comment & line comment regexps.
     fn from(token: TokenWithLineColumn) -> Self {
-        let kind = if BLOCK_COMMENT.is_match(&token.content) {
+        // `/** */` and `/*! */` are doc comments, unlike `/***` and `/**/`
+        let block_doc = (token.content.starts_with("/**")
+            && !token.content.starts_with("/***")
+            && token.content != "/**/")
+            || token.content.starts_with("/*!");
+        let kind = if block_doc {
+            TokenType::Other
+        } else if BLOCK_COMMENT.is_match(&token.content) {
             TokenType::BlockComment
         } else if LINE_COMMENT.is_match(&token.content) {
             TokenType::LineComment
@@ -243,82 +250,38 @@ fn literal_set_from_block_comment(token: &TokenWithType) -> Result<LiteralSet, S
             token.content
         ));
     }
-    let number_of_lines = token.content.split("\n").count();
-    let mut lines = token.content.split("\n");
-    if number_of_lines == 1 {
-        let literal = match TrimmedLiteral::from(
-        CommentVariant::SlashStar, &token.content, token.kind.pre_in_chars(),
-        token.kind.post_in_chars(), token.line, token.column) {
-      Err(s) => return Err(format!(
-          "Failed to create literal from single line block comment, content \"{}\" - caused by \"{}\"",
-          token.content, s)),
-      Ok(l) => l
+    let literals = TrimmedLiteral::from_block_comment(
+        &token.content,
+        token.kind.pre_in_chars(),
+        token.line,
+        token.column,
+        |gutter| CommentVariant::SlashStar { gutter },
+    )
+    .map_err(|s| {
+        format!(
+            "Failed to create literals from block comment with content \"{}\" due to error \"{}\"",
+            token.content, s
+        )
+    })?;
+    let mut literals = literals.into_iter();
+    let mut literal_set = match literals.next() {
+        None => {
+            return Err(format!(
+                "BUG! Expected block comment \"{}\" to have at least one line",
+                token.content
+            ))
+        }
+        Some(literal) => LiteralSet::from(literal),
     };
-        Ok(LiteralSet::from(literal))
-    } else {
-        let next_line = match lines.next() {
-            None => {
-                return Err(format!(
-                    "BUG! Expected block comment \"{}\" to have at least two lines",
-                    token.content
-                ))
-            }
-            Some(l) => l,
-        };
-        let literal = match TrimmedLiteral::from(
-            CommentVariant::SlashStar,
-            next_line,
-            token.kind.pre_in_chars(),
-            0,
-            token.line,
-            token.column,
-        ) {
-            Err(s) => {
-                return Err(format!(
-                    "Failed to create literal from block comment with content \"{}\" \
-          due to error \"{}\"",
-                    next_line, s
-                ))
-            }
-            Ok(l) => l,
-        };
-        let mut literal_set = LiteralSet::from(literal);
-        let mut line_number = token.line;
-        while let Some(next_line) = lines.next() {
-            line_number += 1;
-            let post = if next_line.ends_with(BLOCK_COMMENT_POSTFIX) {
-                TokenType::BlockComment.post_in_chars()
-            } else {
-                0
-            };
-            let literal = match TrimmedLiteral::from(
-                CommentVariant::SlashStar,
-                next_line,
-                0,
-                post,
-                line_number,
-                0,
-            ) {
-                Err(s) => {
-                    return Err(format!(
-                        "Failed to create literal from content \"{}\" due to error \"{}\"",
-                        next_line, s
-                    ))
-                }
-                Ok(l) => l,
-            };
-            match literal_set.add_adjacent(literal) {
-                Ok(_) => (),
-                Err(_) => {
-                    return Err(format!(
-                        "Failed to add line with content {} to literal set",
-                        next_line
-                    ))
-                }
-            }
+    for literal in literals {
+        if let Err(literal) = literal_set.add_adjacent(literal) {
+            return Err(format!(
+                "Failed to add line with content {} to literal set",
+                literal.as_str()
+            ));
         }
-        Ok(literal_set)
     }
+    Ok(literal_set)
 }
 
 /// Attempt to create a literal from a developer line comment token. Returns `None` if the token's
@@ -729,6 +692,45 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_multi_line_block_comment_gutter_is_part_of_prefix() {
+        let source = "/* block\n   * 种\n   *\n   */";
+        let tokens = source_to_tokens_with_type(source);
+        let token = tokens.into_iter().last().unwrap();
+        let literal_set = literal_set_from_block_comment(&token).unwrap();
+        assert_eq!(literal_set.len(), 4);
+        let literals = literal_set.literals();
+        assert_eq!(
+            literals[0].variant(),
+            CommentVariant::SlashStar { gutter: true }
+        );
+        {
+            let literal = literals.get(1).unwrap();
+            assert_eq!(literal.pre(), "   *".len());
+            assert_eq!(literal.as_str(), " 种");
+            let span = &literal.span();
+            assert_eq!(span.start.line, 2);
+            assert_eq!(span.start.column, 4);
+            assert_eq!(span.end.column, 5);
+        }
+        assert_eq!(literals[2].as_str(), "");
+        {
+            // the closing delimiter is not a gutter
+            let literal = literals.get(3).unwrap();
+            assert_eq!(literal.pre(), 0);
+            assert_eq!(literal.post(), TokenType::BlockComment.post_in_chars());
+            assert_eq!(literal.as_str(), "   ");
+        }
+    }
+
+    #[test]
+    fn test_block_doc_comments_are_not_developer_comments() {
+        let source = "/** doc */\n/*! inner doc */\n/*** dev */\n/**/";
+        let tokens = source_to_tokens_with_type(source);
+        let literal_sets = literal_sets_from_block_comments(tokens.iter().collect());
+        assert_eq!(literal_sets.len(), 2);
+    }
+
     #[test]
     fn test_not_developer_comments_block_comment_converter_does_not_create_literals() {
         let source = "// line comment\n/// Outer documentation\nfn test(){\n \
@@ -1,6 +1,6 @@
 use crate::util::{self, sub_chars};
 use crate::{Range, Span};
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 
 use fancy_regex::Regex;
 use proc_macro2::LineColumn;
@@ -23,8 +23,22 @@ pub enum CommentVariant {
     CommonMark,
     /// Developer line comment
     DoubleSlash,
-    /// Developer block comment
-    SlashStar,
+    /// Developer block comment `/* */`, where `gutter` tracks if the lines
+    /// continue with a leading `*`
+    SlashStar {
+        /// Lines continue with a leading `*`.
+        gutter: bool,
+    },
+    /// Block doc comment `/** */`, with or without a `*` gutter
+    SlashStarStar {
+        /// Lines continue with a leading `*`.
+        gutter: bool,
+    },
+    /// Inner block doc comment `/*! */`, with or without a `*` gutter
+    SlashStarEM {
+        /// Lines continue with a leading `*`.
+        gutter: bool,
+    },
     /// Unknown Variant
     Unknown,
 }
@@ -54,7 +68,17 @@ impl CommentVariant {
             }
             CommentVariant::CommonMark => "".to_string(),
             CommentVariant::DoubleSlash => "//".to_string(),
-            CommentVariant::SlashStar => "/*".to_string(),
+            // the opening delimiter is only present in the first line,
+            // all following lines are prefixed by the gutter if any
+            CommentVariant::SlashStar { gutter }
+            | CommentVariant::SlashStarStar { gutter }
+            | CommentVariant::SlashStarEM { gutter } => {
+                if *gutter {
+                    "*".to_string()
+                } else {
+                    "".to_string()
+                }
+            }
             unhandled => unreachable!(
                 "String representation for comment variant {:?} exists. qed",
                 unhandled
@@ -340,6 +364,93 @@ impl TrimmedLiteral {
 }
 
 impl TrimmedLiteral {
+    /// Creates a literal for each line of the block comment `content`, which
+    /// starts at `line` and `column` with an opening delimiter of `pre` bytes
+    /// and ends with `*/`. The variant is created from the gutter style.
+    ///
+    /// If all lines after the first continue with a `*` gutter, the leading
+    /// whitespace and the `*` are part of the prefix of those lines, except
+    /// for a line which only closes the comment.
+    pub fn from_block_comment(
+        content: &str,
+        pre: usize,
+        line: usize,
+        column: usize,
+        variant: impl Fn(bool) -> CommentVariant,
+    ) -> Result<Vec<TrimmedLiteral>, String> {
+        if !content.ends_with("*/") {
+            return Err(format!("Block comment is not closed: {}", content));
+        }
+        // a line which only closes the comment does not tell about the style
+        let mut continued = content
+            .split('\n')
+            .skip(1)
+            .filter(|line| line.trim() != "*/")
+            .peekable();
+        let gutter =
+            continued.peek().is_some() && continued.all(|line| line.trim_start().starts_with('*'));
+        let variant = variant(gutter);
+        let n = content.split('\n').count();
+        content
+            .split('\n')
+            .enumerate()
+            // a closing delimiter at the very start of a line has no content at all
+            .filter(|&(idx, text)| idx == 0 || text != "*/")
+            .map(|(idx, text)| {
+                let pre = if idx == 0 {
+                    pre
+                } else if gutter && !text.trim_start().starts_with("*/") {
+                    text.len() - text.trim_start().len() + 1
+                } else {
+                    0
+                };
+                let post = if idx + 1 == n {
+                    2
+                } else if text.ends_with('\r') {
+                    1
+                } else {
+                    0
+                };
+                let column = if idx == 0 { column } else { 0 };
+                TrimmedLiteral::from(variant.clone(), text, pre, post, line + idx, column)
+            })
+            .collect()
+    }
+
+    /// Creates the literals of a `/** */` or `/*! */` block doc comment, one
+    /// per line, and `None` for all other doc comments.
+    pub(crate) fn from_block_doc(
+        source: &str,
+        literal: &proc_macro2::Literal,
+    ) -> Result<Option<Vec<TrimmedLiteral>>> {
+        let mut span = Span::from(literal.span());
+        // same as for all other literals, the span covers one trailing char
+        span.end.column = span.end.column.saturating_sub(1);
+        let rendered = util::load_span_from(source.as_bytes(), span.clone())?;
+        let literals = if rendered.starts_with("/**") {
+            TrimmedLiteral::from_block_comment(
+                &rendered,
+                3,
+                span.start.line,
+                span.start.column,
+                |gutter| CommentVariant::SlashStarStar { gutter },
+            )
+        } else if rendered.starts_with("/*!") {
+            TrimmedLiteral::from_block_comment(
+                &rendered,
+                3,
+                span.start.line,
+                span.start.column,
+                |gutter| CommentVariant::SlashStarEM { gutter },
+            )
+        } else {
+            return Ok(None);
+        };
+        literals
+            .map(Some)
+            .map_err(|e| anyhow!("Failed to split block doc comment: {}", e))
+    }
+
     /// Represent the rendered content as `str`.
     ///
     /// Does not contain `pre` and `post` characters.
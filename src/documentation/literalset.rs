@@ -11,6 +11,8 @@ pub struct LiteralSet {
     pub coverage: (usize, usize),
     /// Track what kind of comment the literals are
     variant: CommentVariant,
+    /// The literals document an `unsafe fn`.
+    pub unsafe_fn: bool,
 }
 
 impl LiteralSet {
@@ -20,6 +22,7 @@ impl LiteralSet {
             coverage: (literal.span().start.line, literal.span().end.line),
             variant: literal.variant(),
             literals: vec![literal],
+            unsafe_fn: false,
         }
     }
 
@@ -106,7 +109,7 @@ impl LiteralSet {
         } else {
             crate::CommentVariant::Unknown
         };
-        CheckableChunk::from_string(content, source_mapping, variant)
+        CheckableChunk::from_string(content, source_mapping, variant).with_unsafe_fn(self.unsafe_fn)
     }
 }
 
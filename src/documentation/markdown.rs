@@ -12,6 +12,18 @@ use crate::documentation::{CheckableChunk, Range};
 use crate::util::sub_chars;
 use crate::Span;
 
+lazy_static::lazy_static! {
+    /// mdBook preprocessor directives such as `{{#include file.rs:2:10}}` or
+    /// `{{#rustdoc_include file.rs}}`, which are substituted before rendering.
+    static ref MDBOOK_DIRECTIVE: regex::Regex = regex::Regex::new(r#"\{\{\s*#[^}]*\}\}"#)
+        .expect("mdBook directive regex compiles. qed");
+}
+
+/// Find all mdBook directives in `s`, the returned ranges are byte ranges.
+pub(crate) fn find_mdbook_directives(s: &str) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+    MDBOOK_DIRECTIVE.find_iter(s).map(|m| m.range())
+}
+
 /// A plain representation of cmark riddled chunk.
 #[derive(Clone)]
 pub struct PlainOverlay<'a> {
@@ -49,6 +61,41 @@ impl<'a> PlainOverlay<'a> {
         plain_acc.push_str(&s);
     }
 
+    /// Track like [`Self::track`], but omit all mdBook directives contained in `s`.
+    ///
+    /// The directives are substituted with other content at render time,
+    /// so there is no point in checking them.
+    fn track_without_directives(
+        s: &str,
+        cmark_range: Range,
+        plain_acc: &mut String,
+        mapping: &mut IndexMap<Range, Range>,
+    ) {
+        let mut directives = find_mdbook_directives(s).peekable();
+        if directives.peek().is_none() {
+            Self::track(s, cmark_range, plain_acc, mapping);
+            return;
+        }
+        // the text might differ from the raw content, i.e. due to escapes,
+        // in which case the offsets can not be trusted, so drop it altogether
+        if s.chars().count() != cmark_range.len() {
+            trace!("Dropping text with mdBook directive {:?}", s);
+            return;
+        }
+        let mut byte_cursor = 0usize;
+        let mut char_cursor = cmark_range.start;
+        for directive in directives.chain(std::iter::once(s.len()..s.len())) {
+            let fragment = &s[byte_cursor..directive.start];
+            let fragment_len = fragment.chars().count();
+            if !fragment.trim().is_empty() {
+                let range = char_cursor..(char_cursor + fragment_len);
+                Self::track(fragment, range, plain_acc, mapping);
+            }
+            char_cursor += fragment_len + s[directive.clone()].chars().count();
+            byte_cursor = directive.end;
+        }
+    }
+
     /// Append n newlines to the current state string `plain`.
     fn newlines(plain: &mut String, n: usize) {
         for _ in 0..n {
@@ -185,7 +232,7 @@ impl<'a> PlainOverlay<'a> {
                     } else if skip_link_text {
                         skip_link_text = false
                     } else if !skip_table_text {
-                        Self::track(&s, char_range, &mut plain, &mut mapping);
+                        Self::track_without_directives(&s, char_range, &mut plain, &mut mapping);
                     }
                 }
                 Event::Code(_s) => {
@@ -246,7 +293,6 @@ impl<'a> PlainOverlay<'a> {
     /// back to raw ranges, which are then mapped back to `Span`s.
     /// The returned key `Ranges` are in the condensed domain.
     pub fn find_spans(&self, condensed_range: Range) -> IndexMap<Range, Span> {
-        let mut active = false;
         let Range { start, end } = condensed_range;
         self.mapping
             .iter()
@@ -260,51 +306,24 @@ impl<'a> PlainOverlay<'a> {
                 sub.len() > 0
             })
             .fold(IndexMap::<_, _>::new(), |mut acc, (sub, raw)| {
-                fn recombine(range: Range, offset: usize, len: usize) -> Range {
-                    Range {
-                        start: range.start + offset,
-                        end: range.start + offset + len,
-                    }
+                // the part of the condensed range covered by this entry of
+                // the mapping, which spans multiple entries i.e. for
+                // multiple lines or emphasized words
+                let covered = std::cmp::max(sub.start, start)..std::cmp::min(sub.end, end);
+                let offset = covered.start - sub.start;
+                let raw = Range {
+                    start: raw.start + offset,
+                    end: std::cmp::min(raw.start + offset + covered.len(), raw.end),
                 };
-                let _ = if sub.contains(&start) {
-                    // calculate the offset between our `condensed_range.start` and
-                    // the `sub` which is one entry in the mappings
-                    let offset = start - sub.start;
-                    if sub.contains(&(end - 1)) {
-                        // complete start to end
-                        active = false;
-                        let raw = recombine(raw.clone(), offset, end - start);
-                        Some((start..end, raw))
-                    } else {
-                        // only start, continue taking until end
-                        active = true;
-                        let raw = recombine(raw.clone(), offset, sub.end - start);
-                        Some((start..sub.end, raw))
-                    }
-                // TODO must be implemented properly
-                // } else if active {
-                //     let offset = sub.end - end;
-                //     if sub.contains(&(end - 1)) {
-                //         active = false;
-                //         Some((sub.start..end, offset))
-                //     } else {
-                //         Some((sub.clone(), offset))
-                //     }
+                trace!("convert:  cmark-erased={:?} -> raw={:?}", covered, raw);
+
+                if raw.len() > 0 {
+                    let resolved = self.raw.find_spans(raw.clone());
+                    trace!("cmark-erased range to spans: {:?} -> {:?}", raw, resolved);
+                    acc.extend(resolved.into_iter());
                 } else {
-                    None
+                    warn!("linear range to spans: {:?} empty!", raw);
                 }
-                .and_then(|(sub, raw)| {
-                    trace!("convert:  cmark-erased={:?} -> raw={:?}", sub, raw);
-
-                    if raw.len() > 0 {
-                        let resolved = self.raw.find_spans(raw.clone());
-                        trace!("cmark-erased range to spans: {:?} -> {:?}", raw, resolved);
-                        acc.extend(resolved.into_iter());
-                    } else {
-                        warn!("linear range to spans: {:?} empty!", raw);
-                    }
-                    Some(())
-                });
                 acc
             })
     }
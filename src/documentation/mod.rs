@@ -22,7 +22,7 @@ pub use proc_macro2::LineColumn;
 use proc_macro2::{Spacing, TokenTree};
 use rayon::prelude::*;
 use std::convert::TryInto;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Range based on `usize`, simplification.
 pub type Range = core::ops::Range<usize>;
@@ -44,6 +44,8 @@ pub use markdown::*;
 pub struct Documentation {
     /// Mapping of a path to documentation literals
     index: IndexMap<ContentOrigin, Vec<CheckableChunk>>,
+    /// Digests of the file contents the chunks were extracted from.
+    digests: IndexMap<PathBuf, u64>,
 }
 
 impl Documentation {
@@ -51,6 +53,7 @@ impl Documentation {
     pub fn new() -> Self {
         Self {
             index: IndexMap::with_capacity(64),
+            digests: IndexMap::with_capacity(64),
         }
     }
 
@@ -86,7 +89,9 @@ impl Documentation {
 
     /// Join `self` with another doc to form a new one.
     pub fn join(&mut self, other: Documentation) -> &mut Self {
+        self.digests.extend(other.digests);
         other
+            .index
             .into_iter()
             .for_each(|(origin, chunks): (_, Vec<CheckableChunk>)| {
                 let _ = self.add_inner(origin, chunks);
@@ -116,6 +121,22 @@ impl Documentation {
         // Ok(()) TODO make this failable
     }
 
+    /// Record the digest of the full content of a file.
+    fn add_digest(&mut self, origin: &ContentOrigin, content: &str) {
+        match origin {
+            ContentOrigin::RustSourceFile(path) | ContentOrigin::CommonMarkFile(path) => {
+                self.digests
+                    .insert(path.to_owned(), crate::util::content_digest(content));
+            }
+            _ => {}
+        }
+    }
+
+    /// The digest of the file content at `path` at the time it was added.
+    pub fn digest(&self, path: &Path) -> Option<u64> {
+        self.digests.get(path).copied()
+    }
+
     /// Adds a rust content str to the documentation.
     pub fn add_rust(
         &mut self,
@@ -123,6 +144,7 @@ impl Documentation {
         content: &str,
         dev_comments: bool,
     ) -> Result<()> {
+        self.add_digest(&origin, content);
         let cluster = Clusters::load_from_str(content, dev_comments)?;
 
         let chunks = Vec::<CheckableChunk>::from(cluster);
@@ -132,6 +154,7 @@ impl Documentation {
 
     /// Adds a common mark content str to the documentation.
     pub fn add_commonmark(&mut self, origin: ContentOrigin, content: &str) -> Result<()> {
+        self.add_digest(&origin, content);
         // extract the full content span and range
         let start = LineColumn { line: 1, column: 0 };
         let end = content
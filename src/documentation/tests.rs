@@ -88,8 +88,9 @@ macro_rules! end2end {
         let _plain = chunk.erase_cmark();
 
         let cfg = Default::default();
-        let suggestion_set =
-            <$checker>::check(&docs, &cfg).expect("Must not fail to extract suggestions");
+        let checker = <$checker>::new(&cfg).expect("Must create checker");
+        let suggestion_set = crate::checker::check_with(&checker, &docs, &Default::default())
+            .expect("Must not fail to extract suggestions");
         let (_, suggestions) = suggestion_set
             .iter()
             .next()
@@ -176,8 +177,8 @@ mod e2e {
             let origin: ContentOrigin = $origin;
 
             let docs = Documentation::load_from_str(origin.clone(), $source, false);
-            let suggestion_set =
-                dbg!(DummyChecker::check(&docs, &())).expect("Dummy checker never fails. qed");
+            let suggestion_set = dbg!(crate::checker::check_with(&DummyChecker, &docs, &Default::default()))
+                .expect("Dummy checker never fails. qed");
 
             let (origin2, chunks) = docs
                 .iter()
@@ -1063,6 +1064,26 @@ fn link_shortcut_ref() {
         1,
     );
 }
+#[test]
+fn mdbook_directives() {
+    cmark_reduction_test(
+        r#"Prefix {{#include ../listings/main.rs:2:10}} postfix"#,
+        r#"Prefix  postfix"#,
+        2,
+    );
+    cmark_reduction_test(r#"{{#rustdoc_include file.rs}}"#, r#""#, 0);
+    cmark_reduction_test(
+        r#"
+```rust
+{{#include file.rs}}
+```
+
+text"#,
+        r#"text"#,
+        1,
+    );
+}
+
 // Nested links as well as nested code blocks are
 // impossible according to the common mark spec.
 
@@ -1084,6 +1105,51 @@ d"#,
     );
 }
 
+#[test]
+fn block_doc_comment_gutter() {
+    const SOURCE: &'static str = "/** Alpha beta\n * gamma delta */\nstruct X;";
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, SOURCE, true);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    // not picked up a second time as developer comment
+    assert_eq!(chunks.len(), 1);
+    let chunk = &chunks[0];
+    assert_eq!(
+        chunk.variant(),
+        CommentVariant::SlashStarStar { gutter: true }
+    );
+    assert_eq!(chunk.as_str(), " Alpha beta\n gamma delta ");
+
+    let range = 13..18;
+    assert_eq!(&chunk.as_str()[range.clone()], "gamma");
+    let range2span = chunk.find_spans(range);
+    let span = range2span.values().next().expect("Has a span. qed");
+    assert_eq!(load_span_from(SOURCE.as_bytes(), *span).unwrap(), "gamma");
+}
+
+#[test]
+fn block_doc_comment_without_gutter() {
+    const SOURCE: &'static str = "/*! Alpha beta\n    gamma delta\n*/\nstruct X;";
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, SOURCE, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    assert_eq!(chunks.len(), 1);
+    let chunk = &chunks[0];
+    assert_eq!(
+        chunk.variant(),
+        CommentVariant::SlashStarEM { gutter: false }
+    );
+    assert_eq!(chunk.as_str(), " Alpha beta\n    gamma delta");
+
+    let range = 16..21;
+    assert_eq!(&chunk.as_str()[range.clone()], "gamma");
+    let range2span = chunk.find_spans(range);
+    let span = range2span.values().next().expect("Has a span. qed");
+    assert_eq!(load_span_from(SOURCE.as_bytes(), *span).unwrap(), "gamma");
+}
+
 #[test]
 fn table_ignore() {
     // TODO FIXME it would be better to transform this into
@@ -1564,6 +1630,9 @@ fn variant_consistency() {
         CommentVariant::MacroDocEq("#[doc = ".to_string(), 1),
         CommentVariant::MacroDocEq("#[doc = ".to_string(), 2),
         CommentVariant::MacroDocEq("#[ doc     =".to_string(), 3),
+        CommentVariant::SlashStar { gutter: false },
+        CommentVariant::SlashStarStar { gutter: true },
+        CommentVariant::SlashStarEM { gutter: false },
     ];
 
     for variant in variants {
@@ -1578,3 +1647,71 @@ fn variant_consistency() {
         );
     }
 }
+
+#[test]
+fn unsafe_fn_documentation() {
+    let docs = Documentation::load_from_str(
+        ContentOrigin::TestEntityRust,
+        r#"//! Module docs.
+
+/// Unsafe.
+#[inline]
+pub(crate) unsafe fn a() {}
+
+/// Safe.
+pub fn b() {
+    /// Nested unsafe.
+    unsafe fn c() {}
+}
+
+/// Unsafe impl, not a fn.
+unsafe impl Send for X {}
+
+/// Constant.
+const D: u8 = 0;
+
+#[doc = "Unsafe extern."]
+pub unsafe extern "C" fn e() {}
+"#,
+        false,
+    );
+    let (_origin, chunks) = docs.iter().next().unwrap();
+    let flags = chunks
+        .iter()
+        .map(|chunk| (chunk.as_str().trim(), chunk.is_unsafe_fn()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flags,
+        vec![
+            ("Module docs.", false),
+            ("Unsafe.", true),
+            ("Safe.", false),
+            ("Nested unsafe.", true),
+            ("Unsafe impl, not a fn.", false),
+            ("Constant.", false),
+            ("Unsafe extern.", true),
+        ]
+    );
+}
+
+#[test]
+fn find_spans_plain_across_lines_and_emphasis() {
+    const SOURCE: &str = "/// Some _very\n/// long_ words.\nstruct X;";
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, SOURCE, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    assert_eq!(chunks.len(), 1);
+    let plain = chunks[0].erase_cmark();
+    assert_eq!(plain.as_str(), "Some very\nlong words.");
+
+    // covers the emphasized part on both lines and the trailing word
+    let range = 5..20;
+    assert_eq!(sub_chars(plain.as_str(), range.clone()), "very\nlong words");
+    let range2span = plain.find_spans(range);
+    let covered = range2span
+        .values()
+        .map(|span| load_span_from(SOURCE.as_bytes(), *span).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(covered, vec!["very", "long", " words"]);
+}
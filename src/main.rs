@@ -10,6 +10,7 @@
 mod action;
 mod checker;
 mod config;
+mod doctor;
 mod documentation;
 mod reflow;
 mod span;
@@ -37,6 +38,10 @@ use signal_hook::{
 #[cfg(target_os = "windows")]
 use signal_hook as _;
 
+// only used by the mock languagetool server
+#[cfg(all(test, not(feature = "languagetool")))]
+use {serde_json as _, tiny_http as _};
+
 use checker::Checker;
 
 /// A simple exit code representation.
@@ -94,8 +99,8 @@ fn run() -> anyhow::Result<ExitCode> {
 
     let args = Args::parse(std::env::args()).unwrap_or_else(|e| e.exit());
 
-    let verbosity = match args.flag_verbose {
-        _ if args.flag_quiet => log::LevelFilter::Off,
+    let verbosity = match args.verbose {
+        _ if args.quiet => log::LevelFilter::Off,
         n if n > 4 => log::LevelFilter::Trace,
         4 => log::LevelFilter::Debug,
         3 => log::LevelFilter::Info,
@@ -109,37 +114,39 @@ fn run() -> anyhow::Result<ExitCode> {
         .filter_module("mio", log::LevelFilter::Error)
         .init();
 
-    // handle the simple variants right away
-    match args.action() {
-        Action::Version => {
-            println!("cargo-spellcheck {}", env!("CARGO_PKG_VERSION"));
-            return Ok(ExitCode::Success);
-        }
-        Action::Help => {
-            println!("{}", Args::USAGE);
-            return Ok(ExitCode::Success);
-        }
-        _ => {}
-    }
-
     #[cfg(not(target_os = "windows"))]
     let _signalthread = std::thread::spawn(move || signal_handler());
 
     let (unified, config) = args.unified()?;
 
     match unified {
-        // must unify first, for the proper paths
-        UnifiedArgs::Config {
-            dest_config,
-            checker_filter_set,
-        } => {
+        UnifiedArgs::Completions { shell } => {
+            Args::write_completions(shell, &mut std::io::stdout())?;
+            Ok(ExitCode::Success)
+        }
+        UnifiedArgs::ShowConfig { file, overrides } => {
+            let effective = config::EffectiveConfig {
+                config: &config,
+                file,
+                overrides,
+            };
+            effective.write_to(std::io::stdout().lock())?;
+            Ok(ExitCode::Success)
+        }
+        UnifiedArgs::Doctor { file } => {
+            let problems = doctor::diagnose(&config, &file, std::io::stdout().lock())?;
+            if problems > 0 {
+                Ok(ExitCode::Custom(1))
+            } else {
+                Ok(ExitCode::Success)
+            }
+        }
+        UnifiedArgs::Manpage => {
+            Args::write_manpage(&mut std::io::stdout())?;
+            Ok(ExitCode::Success)
+        }
+        UnifiedArgs::Config { dest_config } => {
             trace!("Configuration chore");
-            let mut config = Config::full();
-            Args::checker_selection_override(
-                checker_filter_set.as_ref().map(|x| x.as_slice()),
-                &mut config,
-            )?;
-
             match dest_config {
                 ConfigWriteDestination::Stdout => {
                     println!("{}", config.to_toml()?);
@@ -176,18 +183,36 @@ fn run() -> anyhow::Result<ExitCode> {
             let combined = traverse::extract(paths, recursive, skip_readme, dev_comments, &config)?;
 
             // TODO move this into action `fn run()`
-            let suggestion_set = match action {
-                Action::Reflow => {
-                    reflow::Reflow::check(&combined, &config.reflow.clone().unwrap_or_default())?
+            let mut suggestion_set = match action {
+                Action::Reflow | Action::ReflowCheck => {
+                    let reflow = reflow::Reflow::new(&config.reflow.clone().unwrap_or_default())?;
+                    checker::check_with(&reflow, &combined, &config.variant_checkers)?
                 }
                 Action::Check | Action::Fix => checker::check(&combined, &config)?,
                 _ => unreachable!("Should never be reached, handled earlier"),
             };
 
-            let finish = action.run(suggestion_set, &config)?;
+            suggestion_set.apply_severity(&config.severity);
+            let worst = suggestion_set.max_severity();
+
+            let finish = action.run(suggestion_set, &combined, &config)?;
 
             match finish {
+                Finish::MistakeCount(_n)
+                    if matches!(action, Action::Check | Action::ReflowCheck)
+                        && config.severity.fail_on.is_some() =>
+                {
+                    if worst >= config.severity.fail_on {
+                        Ok(ExitCode::Custom(exit_code_override.max(1)))
+                    } else {
+                        Ok(ExitCode::Success)
+                    }
+                }
                 Finish::MistakeCount(0) => Ok(ExitCode::Success),
+                // like `rustfmt --check`, fail unless another code is requested
+                Finish::MistakeCount(_n) if action == Action::ReflowCheck => {
+                    Ok(ExitCode::Custom(exit_code_override.max(1)))
+                }
                 Finish::MistakeCount(_n) => Ok(ExitCode::Custom(exit_code_override)),
                 Finish::Abort => Ok(ExitCode::Signal),
             }
@@ -12,6 +12,8 @@ use super::*;
 use std::borrow::Cow;
 use std::collections::VecDeque;
 
+use unicode_width::UnicodeWidthStr;
+
 #[derive(Debug, Clone)]
 /// Tokenizes a section which is delimited by untokenizable content.
 pub struct Tokeneer<'s> {
@@ -29,6 +31,11 @@ pub struct Tokeneer<'s> {
     unbreakable_ranges: Vec<Range>,
     unbreakable_idx: usize,
 
+    /// Characters which are treated as whitespace and dropped, i.e. the block
+    /// quote markers at the start of lines.
+    /// Ranges must be in characters!
+    skip_ranges: Vec<Range>,
+
     /// Iterator over chars to keep track of position.
     inner: std::iter::Peekable<std::iter::Enumerate<std::str::CharIndices<'s>>>,
     /// Offset from previous word in bytes
@@ -46,6 +53,7 @@ impl<'s> Tokeneer<'s> {
             spans: Default::default(),
             unbreakable_ranges,
             unbreakable_idx: 0usize,
+            skip_ranges: Vec::new(),
             inner,
             previous_byte_offset: 0usize,
             previous_char_offset: 0usize,
@@ -57,6 +65,16 @@ impl<'s> Tokeneer<'s> {
         self.unbreakable_ranges.extend(unbreakable_ranges);
     }
 
+    /// Add ranges of characters which are treated as whitespace.
+    pub fn add_skips(&mut self, skip_ranges: impl IntoIterator<Item = Range>) {
+        self.skip_ranges.extend(skip_ranges);
+    }
+
+    /// If the char at `char_idx` is not part of any token.
+    fn is_separator(&self, char_idx: usize, c: char) -> bool {
+        c.is_whitespace() || self.skip_ranges.iter().any(|skip| skip.contains(&char_idx))
+    }
+
     pub fn craft_token(
         &mut self,
         char_idx: usize,
@@ -133,18 +151,18 @@ impl<'s> Iterator for Tokeneer<'s> {
                 break 'unbreakable;
             }
 
-            if c.is_whitespace() {
+            if self.is_separator(char_idx, c) {
                 // track the last whitespace, that plus one marks the start of the next word
-                self.previous_byte_offset = byte_offset + 1;
+                self.previous_byte_offset = byte_offset + c.len_utf8();
                 self.previous_char_offset = char_idx + 1;
                 continue 'outer;
             }
 
             // only relevent if we can peek, otherwise craft a token anyways, we are at the
             // end of our data.
-            if let Some((_char_idx_next, (_byte_offset_next, c_next))) = self.inner.peek() {
+            if let Some(&(char_idx_next, (_byte_offset_next, c_next))) = self.inner.peek() {
                 // assure the next one is whitespace
-                if !c_next.is_whitespace() {
+                if !self.is_separator(char_idx_next, c_next) {
                     continue 'outer;
                 }
             }
@@ -173,6 +191,17 @@ pub struct Gluon<'s> {
     indentations: &'s [Indentation<'s>],
     /// The inner iterator which first tokenizes the string into undividable items.
     inner: Tokeneer<'s>,
+    /// The line breaking strategy.
+    strategy: ReflowStrategy,
+    /// For all strategies but the greedy one, all tokens are collected upfront
+    /// and the number of tokens per line is determined, in line order.
+    planned: Option<VecDeque<usize>>,
+    /// Char ranges of all lines crafted so far, which exceed the maximum
+    /// line width, since they contain an overly long unbreakable sequence.
+    overlong: Vec<Range>,
+    /// Maximum width in columns of the content of a line, regardless of
+    /// the indentation.
+    content_limit: Option<usize>,
 }
 
 impl<'s> Gluon<'s> {
@@ -187,6 +216,71 @@ impl<'s> Gluon<'s> {
             indentations,
             line_counter: 0usize,
             inner: Tokeneer::<'s>::new(s, vec![]),
+            strategy: ReflowStrategy::default(),
+            planned: None,
+            overlong: Vec::new(),
+            content_limit: None,
+        }
+    }
+
+    /// Char ranges of the lines crafted so far, which exceed the maximum
+    /// line width.
+    pub(crate) fn overlong(&self) -> &[Range] {
+        &self.overlong
+    }
+
+    /// Use a different line breaking strategy than the default greedy one.
+    pub(crate) fn set_strategy(&mut self, strategy: ReflowStrategy) {
+        self.strategy = strategy;
+    }
+
+    /// Limit the width of the content of every line in addition to the
+    /// maximum line width.
+    pub(crate) fn set_content_limit(&mut self, content_limit: Option<usize>) {
+        self.content_limit = content_limit;
+    }
+
+    /// The columns available for content in line `idx` (base 0), which is
+    /// the maximum line width reduced by the indentation of that line.
+    fn capacity(&self, idx: usize) -> usize {
+        let indentation = self
+            .indentations
+            .get(idx + 1)
+            .or_else(|| self.indentations.last())
+            .copied()
+            .unwrap_or_default();
+        let capacity = self.max_line_width.saturating_sub(indentation.columns());
+        self.content_limit
+            .map_or(capacity, |limit| std::cmp::min(capacity, limit))
+    }
+
+    /// Collect all tokens into the queue and determine the number of tokens
+    /// per line according to the strategy.
+    fn plan(&mut self) -> VecDeque<usize> {
+        self.queue.extend(
+            (&mut self.inner).map(|(char_range, _byte_range, cow_str)| (char_range, cow_str)),
+        );
+        let lengths = self
+            .queue
+            .iter()
+            .map(|(_, s)| s.width())
+            .collect::<Vec<_>>();
+        match self.strategy {
+            ReflowStrategy::Greedy => greedy(&lengths, 0, |idx| self.capacity(idx)),
+            ReflowStrategy::Optimal => optimal(&lengths, self.indentations.len().max(1), |idx| {
+                self.capacity(idx)
+            }),
+            ReflowStrategy::SentencePerLine => {
+                let mut plan = VecDeque::new();
+                let mut start = 0;
+                for end in sentence_ends(&self.queue) {
+                    plan.extend(greedy(&lengths[start..end], plan.len(), |idx| {
+                        self.capacity(idx)
+                    }));
+                    start = end;
+                }
+                plan
+            }
         }
     }
 
@@ -196,14 +290,26 @@ impl<'s> Gluon<'s> {
         self.inner.add_unbreakables(unbreakable_ranges);
     }
 
+    /// Characters which are dropped, as if they were whitespace.
+    pub(crate) fn add_skips(&mut self, skip_ranges: impl IntoIterator<Item = Range>) {
+        self.inner.add_skips(skip_ranges);
+    }
+
     /// Create a new line based on the processing queue
     fn craft_line(&mut self) -> (usize, String, Range) {
+        let n = self.queue.len();
+        self.craft_line_of(n)
+    }
+
+    /// Create a new line from the first `n` items of the processing queue
+    fn craft_line_of(&mut self, n: usize) -> (usize, String, Range) {
         use itertools::Itertools;
+        let capacity = self.capacity(self.line_counter);
         self.line_counter += 1;
         let mut char_range = usize::MAX..0;
         let line_content = self
             .queue
-            .drain(..)
+            .drain(..n)
             .map(|(range, s)| {
                 char_range.start = std::cmp::min(range.start, char_range.start);
                 char_range.end = std::cmp::max(range.end, char_range.end);
@@ -211,6 +317,14 @@ impl<'s> Gluon<'s> {
             })
             .inspect(|x| log::trace!("Gluing together: {:?} with > <", x))
             .join(" ");
+        if line_content.width() > capacity {
+            log::debug!(
+                "Line {} exceeds the maximum line width: {:?}",
+                self.line_counter,
+                line_content
+            );
+            self.overlong.push(char_range.clone());
+        }
         (self.line_counter, line_content, char_range)
     }
 }
@@ -220,40 +334,44 @@ impl<'s> Iterator for Gluon<'s> {
     type Item = (usize, String, Range);
 
     fn next(&mut self) -> Option<Self::Item> {
-        // obtain the indentation level for this line
+        if self.strategy != ReflowStrategy::Greedy {
+            if self.planned.is_none() {
+                self.planned = Some(self.plan());
+            }
+            let n = self.planned.as_mut().and_then(|plan| plan.pop_front())?;
+            return Some(self.craft_line_of(n));
+        }
+
+        // obtain the columns available for the next not yet crafted line
         // we do keep the indentation unless there are
         // no sufficient lines which causes us to use
         // the last used one
         // Iff the whole thing was empty to begin with, `0`
         // is the default fallback.
-        let indentation = self
-            .indentations
-            .get(
-                // the next not yet crafted line
-                self.line_counter + 1,
-            )
-            .map(|x| *x)
-            .unwrap_or_else(|| self.indentations.last().map(|x| *x).unwrap_or_default());
+        let capacity = self.capacity(self.line_counter);
 
         while let Some((char_range, _byte_range, cow_str)) = self.inner.next() {
-            // calculate the current characters that are already in that line
+            // calculate the current columns that are already in that line
             // and assume one whitespace inbetween each of them
             let mut qiter = self.queue.iter();
-            let acc_len = if let Some((first, _)) = qiter.next() {
+            let acc_len = if let Some((_, first)) = qiter.next() {
                 qiter
-                    .map(|(r, _)| r.len())
-                    .fold(first.len(), |acc, len| acc + 1 + len)
+                    .map(|(_, s)| s.width())
+                    .fold(first.width(), |acc, len| acc + 1 + len)
             } else {
                 0usize
             };
 
-            let offset = indentation.offset();
-            let item_len = char_range.len();
+            let item_len = cow_str.width();
             let item = (char_range.clone(), cow_str);
-            let ret = if offset + acc_len <= self.max_line_width {
+            let ret = if self.queue.is_empty() {
+                // the first word always goes into the line
+                self.queue.push_back(item);
+                continue;
+            } else if acc_len <= capacity {
                 // calculate the sum if we would add the word
-                let sum = offset + acc_len + 1 + item_len;
-                if sum > self.max_line_width {
+                let sum = acc_len + 1 + item_len;
+                if sum > capacity {
                     // if the line length would be exceeded
                     let ret = self.craft_line();
                     self.queue.push_back(item);
@@ -262,11 +380,11 @@ impl<'s> Iterator for Gluon<'s> {
                     self.queue.push_back(item);
                     continue;
                 }
-            } else if item_len > self.max_line_width {
+            } else if item_len > capacity {
                 log::warn!(
                     "A unbreakable chunk is larger than the max line width {} vs {}",
                     item_len,
-                    self.max_line_width
+                    capacity
                 );
                 if acc_len > 0 {
                     // craft a line before inserting
@@ -298,6 +416,111 @@ impl<'s> Iterator for Gluon<'s> {
     }
 }
 
+/// Width of a line consisting of the tokens with the given lengths,
+/// separated by a single space each.
+fn line_width(lengths: &[usize]) -> usize {
+    lengths.iter().sum::<usize>() + lengths.len().saturating_sub(1)
+}
+
+/// Fill lines one after another, starting with line `first` and
+/// returns the number of tokens per line.
+///
+/// A token exceeding the capacity of a line gets a line of its own.
+fn greedy(lengths: &[usize], first: usize, capacity: impl Fn(usize) -> usize) -> VecDeque<usize> {
+    let mut plan = VecDeque::new();
+    let mut start = 0;
+    while start < lengths.len() {
+        let cap = capacity(first + plan.len());
+        let mut end = start + 1;
+        while end < lengths.len() && line_width(&lengths[start..=end]) <= cap {
+            end += 1;
+        }
+        plan.push_back(end - start);
+        start = end;
+    }
+    plan
+}
+
+/// Minimize the sum of the squared number of unused columns of all lines
+/// but the last one, and returns the number of tokens per line.
+///
+/// Lines beyond line `distinct - 1` are assumed to have the same capacity.
+/// Tokens exceeding the capacity of a line get a line of their own.
+fn optimal(
+    lengths: &[usize],
+    distinct: usize,
+    capacity: impl Fn(usize) -> usize,
+) -> VecDeque<usize> {
+    let n = lengths.len();
+    // `cost[i][k]` is the minimal cost of putting tokens `i..` onto lines
+    // starting with line `k`, `next[i][k]` the end of that line
+    let mut cost = vec![vec![0u64; distinct]; n + 1];
+    let mut next = vec![vec![n; distinct]; n + 1];
+    for i in (0..n).rev() {
+        for k in 0..distinct {
+            let cap = capacity(k);
+            let k_next = std::cmp::min(k + 1, distinct - 1);
+            let mut best = (u64::MAX, i + 1);
+            for j in (i + 1)..=n {
+                let width = line_width(&lengths[i..j]);
+                if width > cap && j > i + 1 {
+                    break;
+                }
+                let slack = cap.saturating_sub(width) as u64;
+                let line_cost = if j == n { 0 } else { slack * slack };
+                let total = line_cost.saturating_add(cost[j][k_next]);
+                if total < best.0 {
+                    best = (total, j);
+                }
+            }
+            cost[i][k] = best.0;
+            next[i][k] = best.1;
+        }
+    }
+
+    let mut plan = VecDeque::new();
+    let (mut i, mut k) = (0, 0);
+    while i < n {
+        let j = next[i][k];
+        plan.push_back(j - i);
+        i = j;
+        k = std::cmp::min(k + 1, distinct - 1);
+    }
+    plan
+}
+
+/// Abbreviations which end with a period, but do not end a sentence.
+const ABBREVIATIONS: &[&str] = &[
+    "e.g.", "i.e.", "cf.", "vs.", "approx.", "resp.", "Dr.", "Mr.", "Mrs.",
+];
+
+/// Indices of the tokens which start a new sentence, including the number
+/// of tokens as the end of the last one.
+///
+/// A sentence ends with a token ending in `.`, `!` or `?`, optionally
+/// followed by closing quotes, brackets or emphasis, which is not a common
+/// abbreviation and is not followed by a lowercase word.
+fn sentence_ends(tokens: &VecDeque<(Range, Cow<'_, str>)>) -> Vec<usize> {
+    let mut ends = tokens
+        .iter()
+        .zip(tokens.iter().skip(1))
+        .enumerate()
+        .filter(|(_idx, ((_, token), (_, next)))| {
+            let trimmed =
+                token.trim_end_matches(|c| matches!(c, ')' | ']' | '"' | '\'' | '*' | '_'));
+            trimmed.ends_with(|c| matches!(c, '.' | '!' | '?'))
+                && !ABBREVIATIONS.contains(&trimmed)
+                && !next
+                    .chars()
+                    .find(|c| c.is_alphanumeric())
+                    .map_or(false, char::is_lowercase)
+        })
+        .map(|(idx, _)| idx + 1)
+        .collect::<Vec<_>>();
+    ends.push(tokens.len());
+    ends
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,6 +532,24 @@ mod tests {
         max_line_width: usize,
         unbreakables: Vec<Range>,
         indentations: Vec<usize>,
+    ) {
+        verify_reflow_with(
+            ReflowStrategy::Greedy,
+            content,
+            expected,
+            max_line_width,
+            unbreakables,
+            indentations,
+        )
+    }
+
+    fn verify_reflow_with(
+        strategy: ReflowStrategy,
+        content: &'static str,
+        expected: &'static str,
+        max_line_width: usize,
+        unbreakables: Vec<Range>,
+        indentations: Vec<usize>,
     ) {
         let indentations = indentations
             .into_iter()
@@ -322,6 +563,7 @@ mod tests {
         let mut gluon = Gluon::new(content, max_line_width, &indentations);
 
         gluon.add_unbreakables(unbreakables);
+        gluon.set_strategy(strategy);
 
         for ((line_no, line_content, _), (expected_no, expected_content)) in
             gluon.clone().zip(expected.clone())
@@ -442,5 +684,65 @@ indentation"#;
             // 22 > 21 = 4 + 1 + 11 + 5
             verify_reflow(CONTENT, EXPECTED, 22usize, vec![], vec![5, 5]);
         }
+
+        #[test]
+        fn optimal_balances_lines() {
+            const CONTENT: &'static str = "aaa bb cc ddddd";
+            verify_reflow(CONTENT, "aaa bb\ncc\nddddd", 6usize, vec![], vec![0]);
+            verify_reflow_with(
+                ReflowStrategy::Optimal,
+                CONTENT,
+                "aaa\nbb cc\nddddd",
+                6usize,
+                vec![],
+                vec![0],
+            );
+        }
+
+        #[test]
+        fn optimal_unbreakable_and_indentation() {
+            // the unbreakable `bb cc` exceeds the width of 4
+            const CONTENT: &'static str = "aa bb cc dd ee";
+            verify_reflow_with(
+                ReflowStrategy::Optimal,
+                CONTENT,
+                "aa\nbb cc\ndd ee",
+                9usize,
+                vec![3..8],
+                vec![4],
+            );
+        }
+
+        #[test]
+        fn optimal_fits_in_one_line() {
+            const CONTENT: &'static str = "just fine, no action required 🐱";
+            verify_reflow_with(
+                ReflowStrategy::Optimal,
+                CONTENT,
+                CONTENT,
+                40usize,
+                vec![],
+                vec![0],
+            );
+        }
+
+        #[test]
+        fn sentence_per_line() {
+            const CONTENT: &'static str = r#"First sentence. Second one, e.g. with an
+abbreviation and a `code.` block. lowercase? Fourth (sic!) One more."#;
+            const EXPECTED: &'static str = r#"First sentence.
+Second one, e.g. with an abbreviation
+and a `code.` block. lowercase?
+Fourth (sic!)
+One more."#;
+            verify_reflow_with(
+                ReflowStrategy::SentencePerLine,
+                CONTENT,
+                EXPECTED,
+                40usize,
+                vec![],
+                vec![0, 0],
+            );
+        }
     }
 }
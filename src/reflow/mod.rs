@@ -4,28 +4,30 @@
 //! The reflow is done based on the comments no matter the content.
 
 use anyhow::{anyhow, Result};
+use fs_err as fs;
+use unicode_width::UnicodeWidthChar;
 
 use crate::checker::Checker;
-use crate::documentation::{CheckableChunk, Documentation};
+use crate::documentation::{find_mdbook_directives, CheckableChunk};
 #[cfg(debug_assertions)]
 use crate::util::load_span_from;
 use crate::util::{byte_range_to_char_range, byte_range_to_char_range_many, sub_char_range};
 
-use crate::{CommentVariant, ContentOrigin, Detector, Range, Span, Suggestion, SuggestionSet};
+use crate::{CommentVariant, ContentOrigin, Detector, Range, Severity, Span, Suggestion};
 
 use indexmap::IndexMap;
 
 use pulldown_cmark::{Event, Options, Parser, Tag};
 
-pub use crate::config::ReflowConfig;
+pub use crate::config::{ReflowConfig, ReflowStrategy};
 
 mod iter;
-pub use iter::{Gluon, Tokeneer};
-
-use rayon::prelude::*;
+pub use iter::Gluon;
 
 #[derive(Debug)]
-pub struct Reflow;
+pub struct Reflow {
+    config: ReflowConfig,
+}
 
 impl Checker for Reflow {
     type Config = ReflowConfig;
@@ -34,30 +36,69 @@ impl Checker for Reflow {
         Detector::Reflow
     }
 
-    fn check<'a, 's>(docu: &'a Documentation, config: &Self::Config) -> Result<SuggestionSet<'s>>
+    fn new(config: &Self::Config) -> Result<Self> {
+        Ok(Self {
+            config: config.clone(),
+        })
+    }
+
+    fn check<'a, 's>(
+        &self,
+        origin: &ContentOrigin,
+        chunks: &'a [CheckableChunk],
+    ) -> Result<Vec<Suggestion<'s>>>
     where
         'a: 's,
     {
-        let suggestions = docu
-            .par_iter()
-            .try_fold::<SuggestionSet, Result<SuggestionSet>, _, _>(
-                || SuggestionSet::new(),
-                |mut acc, (origin, chunks)| {
-                    for chunk in chunks {
-                        let suggestions = reflow(origin, chunk, config)?;
-                        acc.extend(origin.clone(), suggestions);
-                    }
-                    Ok(acc)
-                },
-            )
-            .try_reduce(
-                || SuggestionSet::new(),
-                |mut a, b| {
-                    a.join(b);
-                    Ok(a)
-                },
-            )?;
-        Ok(suggestions)
+        let config = match origin {
+            ContentOrigin::CommonMarkFile(_) => self.config.clone(),
+            _ => self.config.for_rust_file(origin.as_path())?,
+        };
+        // the chunks lack the indentation in front of comments, which is
+        // required to measure tabs
+        let source = fs::read_to_string(origin.as_path()).ok();
+        let lines = source
+            .as_ref()
+            .map(|source| source.lines().collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        let mut acc = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let is_dev_comment = matches!(
+                chunk.variant(),
+                CommentVariant::DoubleSlash | CommentVariant::SlashStar { .. }
+            );
+            if is_dev_comment && !config.dev_comments {
+                continue;
+            }
+            acc.extend(reflow(origin, chunk, &lines, &config)?);
+        }
+        Ok(acc)
+    }
+}
+
+/// If the content of each line starts after the prefix and a single space,
+/// which is swallowed by the markdown parser, i.e. `/// ` or ` * `.
+fn spaced(variant: &CommentVariant) -> bool {
+    match variant {
+        CommentVariant::TripleSlash
+        | CommentVariant::DoubleSlashEM
+        | CommentVariant::DoubleSlash => true,
+        CommentVariant::SlashStar { gutter }
+        | CommentVariant::SlashStarStar { gutter }
+        | CommentVariant::SlashStarEM { gutter } => *gutter,
+        _ => false,
+    }
+}
+
+/// Block comments without a gutter keep the indentation of all lines but the
+/// first in their content.
+fn indented(variant: &CommentVariant) -> bool {
+    match variant {
+        CommentVariant::SlashStar { gutter }
+        | CommentVariant::SlashStarStar { gutter }
+        | CommentVariant::SlashStarEM { gutter } => !*gutter,
+        _ => false,
     }
 }
 
@@ -134,20 +175,28 @@ pub fn extract_delimiter<'s>(s: &'s str) -> Option<&'static str> {
 
 /// Reflows a parsed commonmark paragraph contained in `s`.
 ///
-/// Returns the `Some(replacement)` string if a reflow has been performed and `None` otherwise.
+/// Returns the `Some(replacement)` string if a reflow has been performed and `None` otherwise,
+/// together with the char ranges of all lines which still exceed `max_line_width` after the
+/// reflow, since they contain overly long unbreakable sequences.
 ///
 /// `range` denotes the range of the paragraph of interest in the top-level `CheckableChunk`.
 /// `unbreakable_ranges` contains all ranges of words/sequences which must not be split during
 /// the reflow. They are relative to the top-level `CheckableChunk` similar to `range`. The indentation
-/// vec contains the indentation for each line in `s`.
+/// vec contains the indentation for each line in `s`. `nesting` describes the list items and
+/// block quotes the paragraph is part of, which determine the start of each continuation line.
+/// Lines are limited to `max_line_width` display columns, and for comments optionally to
+/// `comment_width` columns from the comment prefix onwards.
 fn reflow_inner<'s>(
     s: &'s str,
     range: Range,
     unbreakable_ranges: &[Range],
     indentations: &[Indentation<'s>],
     max_line_width: usize,
+    comment_width: Option<usize>,
+    strategy: ReflowStrategy,
+    nesting: &Nesting,
     variant: &CommentVariant,
-) -> Result<Option<String>> {
+) -> Result<(Option<String>, Vec<Range>)> {
     // Get type of newline from current chunk, either plain \n or \r\n
     let line_delimiter = extract_delimiter(s).unwrap_or_else(|| {
         // TODO if ther is no newline in `s`, we assume `\n`
@@ -170,12 +219,32 @@ fn reflow_inner<'s>(
         .iter()
         .map(|r| (r.start.saturating_sub(range.start))..(r.end.saturating_sub(range.start)));
 
+    // avoid stray spaces after newlines due to a truely required indentation
+    // of 3 for `///` but practically, it's `/// ` (added space), which should be accounted for,
+    // since that is used for accounting for the skip covered by `///`,
+    // which is being removed by the transformation `s` to `s_absolute`
+    // that removes the leading space.
+    let (indentation_skip_n, extra_space) = if spaced(variant) {
+        (variant.prefix_len() + 1, " ")
+    } else {
+        (variant.prefix_len(), "")
+    };
+
     let mut gluon = Gluon::new(s_absolute, max_line_width, &indentations);
     gluon.add_unbreakables(unbreakables);
+    gluon.add_skips(nesting.quote_markers(s_absolute));
+    gluon.set_strategy(strategy);
+    if variant != &CommentVariant::CommonMark {
+        // everything from the comment prefix up to the content
+        gluon.set_content_limit(
+            comment_width.map(|width| width.saturating_sub(indentation_skip_n + nesting.width())),
+        );
+    }
 
     let mut reflow_applied = false;
     let mut lines = s_absolute.lines();
-    let mut indents_iter = indentations.iter();
+    // the first line is not prefixed, so the indentations of the following lines apply
+    let mut indents_iter = indentations.iter().skip(1);
     let last_indent = indentations
         .last()
         .copied()
@@ -185,7 +254,7 @@ fn reflow_inner<'s>(
     // If there is nothing to reflow, just pretend there was no reflow.
     let (_lineno, content, _range) = match gluon.next() {
         Some(c) => c,
-        None => return Ok(None),
+        None => return Ok((None, Vec::new())),
     };
     if lines.next() != Some(&content) {
         reflow_applied = true;
@@ -197,35 +266,24 @@ fn reflow_inner<'s>(
     }
 
     // construct replacement string from prefix and Gluon iterations
-    let content = gluon.fold(acc, |mut acc, (_lineno, content, _range)| {
+    let content = gluon.by_ref().fold(acc, |mut acc, (_lineno, content, _range)| {
         if lines.next() == Some(&content) {
             reflow_applied = true;
         }
 
-        // avoid stray spaces after newlines due to a truely required indentation
-        // of 3 for `///` but practically, it's `/// ` (added space), which should be accounted for,
-        // since that is used for accounting for the skip covered by `///`,
-        // which is being removed by the transformation `s` to `s_absolute`
-        // that removes the leading space.
-        let (indentation_skip_n, extra_space) = match variant {
-            CommentVariant::TripleSlash | CommentVariant::DoubleSlashEM => {
-                let n = variant.prefix_len();
-                (n + 1, " ")
-            }
-            _ => (variant.prefix_len(), ""),
-        };
         let pre = if let Some(indentation) = indents_iter.next() {
             indentation
         } else {
             &last_indent
         }
-        .to_string_but_skip_n(indentation_skip_n);
+        .to_string_but_skip_n(indentation_skip_n + nesting.width());
 
-        log::trace!(target: "glue", "glue[shift={}]: acc = {:?} + {:?} + {:?} + {:?} + {:?} + {:?}",
+        log::trace!(target: "glue", "glue[shift={}]: acc = {:?} + {:?} + {:?} + {:?} + {:?} + {:?} + {:?}",
                 indentation_skip_n,
                 &pre,
                 &variant.prefix_string(),
                 extra_space,
+                &nesting.continuation,
                 &content,
                 &variant.suffix_string(),
                 line_delimiter
@@ -233,20 +291,27 @@ fn reflow_inner<'s>(
         acc.push_str(&pre);
         acc.push_str(&variant.prefix_string());
         acc.push_str(extra_space);
+        acc.push_str(&nesting.continuation);
         acc.push_str(&content);
         acc.push_str(&variant.suffix_string());
         acc.push_str(line_delimiter);
         acc
     });
 
+    let overlong = gluon
+        .overlong()
+        .iter()
+        .map(|r| (r.start + range.start)..(r.end + range.start))
+        .collect::<Vec<_>>();
+
     // remove last new line
     let content = if let Some(c) = content.strip_suffix(line_delimiter) {
         c.to_string()
     } else {
-        return Ok(None);
+        return Ok((None, overlong));
     };
 
-    Ok(if reflow_applied {
+    let replacement = if reflow_applied {
         // for MacroDocEq comments, we also have to remove the last closing delimiter
         let mut content = content
             .strip_suffix(&variant.suffix_string())
@@ -268,12 +333,26 @@ fn reflow_inner<'s>(
         }
     } else {
         None
+    };
+    Ok((replacement, overlong))
+}
+
+/// Width of `s` in display columns, where tabs advance to the next multiple
+/// of `tab_width`.
+pub(crate) fn display_width(s: &str, tab_width: usize) -> usize {
+    s.chars().fold(0, |column, c| match c {
+        '\t' if tab_width > 0 => column + tab_width - column % tab_width,
+        c => column + c.width().unwrap_or_default(),
     })
 }
 
 #[derive(Default, Debug, Hash, Eq, PartialEq, Copy, Clone)]
 pub(crate) struct Indentation<'s> {
+    /// Number of chars in front of the content of a line.
     offset: usize,
+    /// Number of display columns in front of the content of a line.
+    columns: usize,
+    /// The literal whitespace the line starts with.
     s: Option<&'s str>,
 }
 
@@ -290,25 +369,39 @@ impl<'s> ToString for Indentation<'s> {
 impl<'s> Indentation<'s> {
     pub(crate) fn new(offset: usize) -> Self {
         log::trace!("New offset with indentation of {} ", offset);
-        Self { offset, s: None }
+        Self {
+            offset,
+            columns: offset,
+            s: None,
+        }
     }
 
-    #[allow(unused)]
-    pub(crate) fn with_str(offset: usize, s: &'s str) -> Self {
+    /// An indentation of `offset` chars, which start with the leading
+    /// whitespace `s`, that is kept as is and might contain tabs.
+    pub(crate) fn with_str(offset: usize, s: &'s str, tab_width: usize) -> Self {
         log::trace!("New offset with indentation of {} and {:?}", offset, s);
-        Self { offset, s: Some(s) }
+        let s = sub_char_range(s, 0..offset);
+        let n = s.chars().count();
+        Self {
+            offset,
+            columns: offset - n + display_width(s, tab_width),
+            s: Some(s),
+        }
     }
 
-    pub(crate) fn offset(&self) -> usize {
-        self.offset
+    /// The display columns in front of the content.
+    pub(crate) fn columns(&self) -> usize {
+        self.columns
     }
 
-    /// Convert to a string but skip `n` chars
+    /// Convert to a string but skip the last `n` chars
     pub(crate) fn to_string_but_skip_n(&self, n: usize) -> String {
+        let n = self.offset.saturating_sub(n);
         if let Some(s) = self.s {
-            sub_char_range(s, 0..n).to_owned()
+            let kept = sub_char_range(s, 0..n);
+            kept.to_owned() + &" ".repeat(n - kept.chars().count())
         } else {
-            " ".repeat(self.offset.saturating_sub(n))
+            " ".repeat(n)
         }
     }
 }
@@ -320,20 +413,28 @@ impl<'s> Indentation<'s> {
 /// line starting right after `/// ` (note the space here).
 ///
 ///
-/// Returns: end of processed range and Suggestion, if reflow happened.
+/// Returns: end of processed range and the Suggestion, if reflow happened,
+/// followed by one without replacements for each line, which exceeds the
+/// maximum line length even after the reflow.
 fn store_suggestion<'s>(
     chunk: &'s CheckableChunk,
     origin: &ContentOrigin,
+    lines: &[&str],
     bytes_paragraph: usize,
     bytes_end: usize,
     bytes_unbreakable_ranges: &[Range],
-    max_line_width: usize,
-) -> Result<(usize, Option<Suggestion<'s>>)> {
+    nesting: &Nesting,
+    cfg: &ReflowConfig,
+) -> Result<(usize, Vec<Suggestion<'s>>)> {
+    let s = chunk.as_str();
+    // trailing whitespace, i.e. in front of a closing `*/`, is not part of the paragraph
     let bytes_range = Range {
         start: bytes_paragraph,
-        end: bytes_end,
+        end: bytes_paragraph
+            + s[bytes_paragraph..bytes_end]
+                .trim_end_matches(|c| c == ' ' || c == '\t')
+                .len(),
     };
-    let s = chunk.as_str();
     #[cfg(debug_assertions)]
     let sb = s.as_bytes();
 
@@ -353,10 +454,7 @@ fn store_suggestion<'s>(
     // with markdown, the initial paragraph for `/// `
     // might be shifted, so the start in those cases must be shifted back
     // to right after `///`, which is done by substracting one.
-    let adjustment = match chunk.variant() {
-        CommentVariant::DoubleSlashEM | CommentVariant::TripleSlash => 1usize,
-        _ => 0usize,
-    };
+    let adjustment = spaced(&chunk.variant()) as usize;
 
     debug_assert_eq!(&s[bytes_range], sub_char_range(s, range.clone()));
 
@@ -370,7 +468,7 @@ fn store_suggestion<'s>(
         } = if let Some(first) = spans_iter.next() {
             first
         } else {
-            return Ok((bytes_paragraph, None));
+            return Ok((bytes_paragraph, Vec::new()));
         };
         let end = if let Some(last) = spans_iter.last() {
             last.end
@@ -388,12 +486,23 @@ fn store_suggestion<'s>(
         load_span_from(sb, span).unwrap()
     );
 
+    // Within list items and block quotes, all lines start with the continuation,
+    // but the first line starts with a bullet or a task list marker instead,
+    // so the indentation of the first line must not account for those.
+    let into_line = if nesting.width() > 0 {
+        let line_start = s[..bytes_paragraph].rfind('\n').map_or(0, |idx| idx + 1);
+        s[line_start..bytes_paragraph].chars().count() - adjustment
+    } else {
+        0
+    };
+
     // Get indentation for each span, if a span covers multiple
     // lines, use same indentation for all lines.
     let mut first = true;
+    let indented = indented(&chunk.variant());
     let indentations = range2span
         .iter()
-        .flat_map(|(_range, span)| {
+        .flat_map(|(range, span)| {
             debug_assert!(span.start.line <= span.end.line);
 
             // TODO use `sub_char_range(s, range.clone())`
@@ -407,49 +516,212 @@ fn store_suggestion<'s>(
             let col = span
                 .start
                 .column
-                .saturating_sub(adjustment * (first as usize))
-                + adjustment;
-            let indentation = Indentation::new(col);
+                .saturating_sub((adjustment + into_line) * (first as usize))
+                + adjustment
+                + nesting.width();
+            // the first line starts right after the opening delimiter
+            let col = if indented && !first {
+                col + sub_char_range(s, range.clone())
+                    .chars()
+                    .take_while(|c| c.is_whitespace())
+                    .count()
+            } else {
+                col
+            };
+            // the leading whitespace of the line in the source, if available
+            let indentation = if let Some(line) = lines.get(span.start.line.saturating_sub(1)) {
+                let n = line.len() - line.trim_start().len();
+                Indentation::with_str(col, &line[..n], cfg.tab_width)
+            } else {
+                Indentation::new(col)
+            };
             first = false;
             vec![indentation; span.end.line.saturating_sub(span.start.line) + 1]
         })
         .collect::<Vec<Indentation>>();
 
-    Ok((
-        bytes_end,
-        reflow_inner(
-            chunk.as_str(),
-            range.clone(),
-            unbreakable_ranges,
-            &indentations,
-            max_line_width,
-            &chunk.variant(),
-        )?
-        .map(|replacement| {
-            let suggestion = Suggestion {
-                chunk,
-                detector: Detector::Reflow,
-                origin: origin.clone(),
-                description: None,
-                range,
-                replacements: vec![replacement],
-                span,
-            };
-            suggestion
-        }),
-    ))
+    let (replacement, overlong) = reflow_inner(
+        chunk.as_str(),
+        range.clone(),
+        unbreakable_ranges,
+        &indentations,
+        cfg.max_line_length,
+        cfg.comment_width,
+        cfg.strategy,
+        nesting,
+        &chunk.variant(),
+    )?;
+
+    let mut suggestions = Vec::with_capacity(1 + overlong.len());
+    if let Some(replacement) = replacement {
+        suggestions.push(Suggestion {
+            chunk,
+            detector: Detector::Reflow,
+            origin: origin.clone(),
+            rule: None,
+            severity: Severity::Error,
+            description: Some(format!(
+                "Paragraph is not wrapped to {} columns",
+                cfg.max_line_length
+            )),
+            range,
+            replacements: vec![replacement],
+            span,
+        });
+    }
+    for range in overlong {
+        let range2span = chunk.find_spans(range.clone());
+        let mut spans = range2span.values();
+        let span = match (spans.next(), spans.last()) {
+            (Some(first), Some(last)) => Span {
+                start: first.start,
+                end: last.end,
+            },
+            (Some(first), None) => *first,
+            _ => continue,
+        };
+        suggestions.push(Suggestion {
+            chunk,
+            detector: Detector::Reflow,
+            origin: origin.clone(),
+            rule: None,
+            severity: Severity::Error,
+            description: Some(format!(
+                "Line exceeds {} columns, unbreakable content prevents a reflow",
+                cfg.max_line_length
+            )),
+            range,
+            replacements: Vec::new(),
+            span,
+        });
+    }
+    Ok((bytes_end, suggestions))
+}
+
+/// Markdown block the events of the parser are currently nested in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Paragraph,
+    /// A list item with the hanging indentation of its content.
+    Item(String),
+    /// A block quote with its marker.
+    Quote(String),
+    /// Blocks which are never reflowed, i.e. headings, code blocks or tables.
+    Other,
+}
+
+impl Block {
+    /// The list item starting with `s`, with the hanging indentation
+    /// covering the bullet or number.
+    fn item(s: &str) -> Self {
+        lazy_static::lazy_static! {
+            static ref MARKER: regex::Regex = regex::Regex::new(r#"^\s*([*+-]|[0-9]{1,9}[.)])( *)"#)
+                .expect("List item marker regex compiles. qed");
+        };
+        let width = MARKER.captures(s).map_or(2, |captures| {
+            let marker = captures.get(1).map_or(1, |marker| marker.as_str().len());
+            // with more than 4 spaces, the content is an indented code block
+            let spacing = captures
+                .get(2)
+                .map(|spacing| spacing.as_str().len())
+                .filter(|spacing| (1..=4).contains(spacing))
+                .unwrap_or(1);
+            marker + spacing
+        });
+        Self::Item(" ".repeat(width))
+    }
+
+    /// The block quote starting with `s`.
+    fn quote(s: &str) -> Self {
+        let s = s.trim_start();
+        if s.starts_with("> ") || !s.starts_with('>') {
+            Self::Quote("> ".to_owned())
+        } else {
+            Self::Quote(">".to_owned())
+        }
+    }
+}
+
+/// The list items and block quotes a paragraph is nested in.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct Nesting {
+    /// Prefix of all continuation lines following the comment prefix,
+    /// i.e. `> ` for block quotes or the hanging indentation of list items.
+    continuation: String,
+    /// Number of nested block quotes.
+    quotes: usize,
+}
+
+impl Nesting {
+    fn new(blocks: &[Block]) -> Self {
+        let mut nesting = Self::default();
+        for block in blocks {
+            match block {
+                Block::Item(hanging) => nesting.continuation.push_str(hanging),
+                Block::Quote(marker) => {
+                    nesting.continuation.push_str(marker);
+                    nesting.quotes += 1;
+                }
+                _ => {}
+            }
+        }
+        nesting
+    }
+
+    /// Width of the continuation in columns.
+    fn width(&self) -> usize {
+        self.continuation.chars().count()
+    }
+
+    /// Char ranges of the block quote markers at the start of all but the
+    /// first line of `s`, which are replaced by the continuation.
+    fn quote_markers(&self, s: &str) -> Vec<Range> {
+        let mut markers = Vec::new();
+        if self.quotes == 0 {
+            return markers;
+        }
+        let mut offset = 0usize;
+        for (idx, line) in s.split('\n').enumerate() {
+            let n = line.chars().count();
+            if idx > 0 {
+                let mut quotes = 0;
+                for (column, c) in line.chars().enumerate() {
+                    if c == '>' && quotes < self.quotes {
+                        markers.push((offset + column)..(offset + column + 1));
+                        quotes += 1;
+                    } else if !c.is_whitespace() {
+                        break;
+                    }
+                }
+            }
+            // account for the `\n`
+            offset += n + 1;
+        }
+        markers
+    }
 }
 
 /// Parses a `CheckableChunk` and performs the rewrapping on contained paragraphs.
+///
+/// Besides paragraphs, the content of list items is reflowed with a hanging
+/// indentation after the bullet, as well as paragraphs in block quotes.
+/// `lines` are the lines of the source file, if available, to measure the
+/// indentation in front of comments.
 fn reflow<'s>(
     origin: &ContentOrigin,
     chunk: &'s CheckableChunk,
+    lines: &[&str],
     cfg: &ReflowConfig,
 ) -> Result<Vec<Suggestion<'s>>> {
     log::debug!("Reflowing {:?}", origin);
-    let parser = Parser::new_ext(chunk.as_str(), Options::all());
+    let s = chunk.as_str();
+    let parser = Parser::new_ext(s, Options::all());
 
-    let mut paragraph = 0_usize;
+    // the byte range of the reflowable content seen so far
+    let mut paragraph: Option<usize> = None;
+    let mut end = 0_usize;
+    // the blocks the current event is nested in
+    let mut blocks: Vec<Block> = Vec::with_capacity(8);
     // nested unbreakables are tracked via a stack approach
     let mut unbreakable_stack: Vec<Range> = Vec::with_capacity(16); // no more than 16 items will be nested, commonly it's 2 or 3
                                                                     // the true unbreakables (without inner nested items)
@@ -458,86 +730,141 @@ fn reflow<'s>(
 
     let mut acc = Vec::with_capacity(128);
 
+    // store the content collected so far, if any
+    let mut flush = |paragraph: &mut Option<usize>,
+                     end: usize,
+                     blocks: &[Block],
+                     unbreakables: &[Range],
+                     unbreakable_stack: &mut Vec<Range>|
+     -> Result<()> {
+        if let Some(start) = paragraph.take() {
+            let (_, suggestions) = store_suggestion(
+                chunk,
+                origin,
+                lines,
+                start,
+                end,
+                unbreakables,
+                &Nesting::new(blocks),
+                cfg,
+            )?;
+            acc.extend(suggestions);
+        }
+        unbreakable_stack.clear();
+        Ok(())
+    };
+
+    // the content of a list item without paragraphs, which ends with
+    // the line instead of the last word
+    let with_newline = |end: usize| {
+        let rest = &s[end..];
+        if rest.starts_with("\r\n") {
+            end + 2
+        } else if rest.starts_with('\n') {
+            end + 1
+        } else {
+            end
+        }
+    };
+
     for (event, cover) in parser.into_offset_iter() {
         #[cfg(debug_assertions)]
         {
             log::trace!("CMark Token: {:?}", &event);
-            log::trace!(
-                "Current segment {:?}: {:?}",
-                cover,
-                &chunk.as_str()[cover.clone()]
-            );
+            log::trace!("Current segment {:?}: {:?}", cover, &s[cover.clone()]);
         }
-        match event {
-            Event::Start(tag) => {
-                match tag {
-                    Tag::Image(_, _, _)
-                    | Tag::Link(_, _, _)
-                    | Tag::Strong
-                    | Tag::Emphasis
-                    | Tag::Strikethrough => {
-                        unbreakable_stack.push(cover);
-                    }
-                    Tag::Paragraph => {
-                        paragraph = cover.start;
-                    }
-                    _ => {
-                        // all of these break a reflow-able chunk
-                        let (p, suggestion) = store_suggestion(
-                            chunk,
-                            origin,
-                            paragraph,
-                            paragraph,
-                            unbreakables.as_slice(),
-                            cfg.max_line_length,
-                        )?;
-                        paragraph = p;
-                        if let Some(suggestion) = suggestion {
-                            acc.push(suggestion);
-                        }
-                        unbreakable_stack.clear();
-                    }
+
+        // the content of list items is reflowable even without a paragraph
+        let inline = matches!(
+            event,
+            Event::Start(Tag::Image(_, _, _))
+                | Event::Start(Tag::Link(_, _, _))
+                | Event::Start(Tag::Strong)
+                | Event::Start(Tag::Emphasis)
+                | Event::Start(Tag::Strikethrough)
+                | Event::Text(_)
+                | Event::Code(_)
+                | Event::Html(_)
+                | Event::FootnoteReference(_)
+        );
+        if inline {
+            if let Some(Block::Item(_)) = blocks.last() {
+                if paragraph.is_none() {
+                    paragraph = Some(cover.start);
+                    end = 0;
                 }
+                // nested inline content ends before the enclosing one
+                end = std::cmp::max(end, with_newline(cover.end));
             }
-            Event::End(tag) => {
-                match tag {
-                    Tag::Image(_, _, _)
-                    | Tag::Link(_, _, _)
-                    | Tag::Strong
-                    | Tag::Emphasis
-                    | Tag::Strikethrough => {
-                        // technically we only need the bottom-most range, since all others - by def - are contained in there
-                        // so there
-                        if unbreakable_stack.len() == 1 {
-                            unbreakables.push(cover);
-                        } else if let Some(parent) = unbreakable_stack.last() {
-                            debug_assert!(parent.contains(&cover.start));
-                            debug_assert!(parent.contains(&(cover.end - 1)));
+        }
+
+        match event {
+            Event::Start(tag) => match tag {
+                Tag::Image(_, _, _)
+                | Tag::Link(_, _, _)
+                | Tag::Strong
+                | Tag::Emphasis
+                | Tag::Strikethrough => {
+                    unbreakable_stack.push(cover);
+                }
+                tag => {
+                    // all of these break a reflow-able chunk
+                    flush(
+                        &mut paragraph,
+                        end,
+                        &blocks,
+                        &unbreakables,
+                        &mut unbreakable_stack,
+                    )?;
+                    blocks.push(match tag {
+                        Tag::Paragraph => {
+                            paragraph = Some(cover.start);
+                            Block::Paragraph
                         }
-                        let _ = unbreakable_stack.pop();
+                        Tag::Item => Block::item(&s[cover]),
+                        Tag::BlockQuote => Block::quote(&s[cover]),
+                        _ => Block::Other,
+                    });
+                }
+            },
+            Event::End(tag) => match tag {
+                Tag::Image(_, _, _)
+                | Tag::Link(_, _, _)
+                | Tag::Strong
+                | Tag::Emphasis
+                | Tag::Strikethrough => {
+                    // technically we only need the bottom-most range, since all others - by def - are contained in there
+                    // so there
+                    if unbreakable_stack.len() == 1 {
+                        unbreakables.push(cover);
+                    } else if let Some(parent) = unbreakable_stack.last() {
+                        debug_assert!(parent.contains(&cover.start));
+                        debug_assert!(parent.contains(&(cover.end - 1)));
                     }
-                    Tag::Paragraph => {
+                    let _ = unbreakable_stack.pop();
+                }
+                tag => {
+                    if tag == Tag::Paragraph {
                         // regular end of paragraph
-                        let (p, suggestion) = store_suggestion(
-                            chunk,
-                            origin,
-                            paragraph,
-                            cover.end,
-                            unbreakables.as_slice(),
-                            cfg.max_line_length,
-                        )?;
-                        paragraph = p;
-                        if let Some(suggestion) = suggestion {
-                            acc.push(suggestion);
-                        }
-                        unbreakable_stack.clear();
-                    }
-                    _ => {
-                        paragraph = cover.end;
+                        end = cover.end;
                     }
+                    flush(
+                        &mut paragraph,
+                        end,
+                        &blocks,
+                        &unbreakables,
+                        &mut unbreakable_stack,
+                    )?;
+                    let _ = blocks.pop();
                 }
+            },
+            Event::Text(_s) => {
+                // mdBook directives are replaced at render time, never split them
+                let text = &s[cover.clone()];
+                unbreakables.extend(find_mdbook_directives(text).map(|directive| {
+                    (cover.start + directive.start)..(cover.start + directive.end)
+                }));
             }
-            Event::Text(_s) => {}
             Event::Code(_s) => {
                 // always make code unbreakable
                 unbreakables.push(cover);
@@ -553,19 +880,16 @@ fn reflow<'s>(
                 // ignored
             }
             Event::HardBreak => {
-                let (p, suggestion) = store_suggestion(
-                    chunk,
-                    origin,
-                    paragraph,
-                    cover.end,
-                    unbreakables.as_slice(),
-                    cfg.max_line_length,
-                )?;
-                paragraph = p;
-                if let Some(suggestion) = suggestion {
-                    acc.push(suggestion);
+                if paragraph.is_some() {
+                    flush(
+                        &mut paragraph,
+                        cover.end,
+                        &blocks,
+                        &unbreakables,
+                        &mut unbreakable_stack,
+                    )?;
+                    paragraph = Some(cover.end);
                 }
-                unbreakable_stack.clear();
             }
             Event::Rule => {
                 // paragraphs end before rules
@@ -1,4 +1,5 @@
 use super::*;
+use crate::documentation::Documentation;
 use crate::util::load_span_from;
 use crate::{chyrp_up, fluff_up};
 use crate::{LineColumn, Span};
@@ -30,10 +31,13 @@ macro_rules! verify_reflow_inner {
             &unbreakables,
             &indentation,
             $n,
+            None,
+            ReflowStrategy::Greedy,
+            &Nesting::default(),
             &chunk.variant()
         );
 
-        if let Ok(Some(repl)) = replacement {
+        if let Ok((Some(repl), _overlong)) = replacement {
             // TODO: check indentation
             assert_eq!(repl, $expected);
         } else {
@@ -54,8 +58,8 @@ test our rewrapping algorithm. With emojis: 🚤w🌴x🌋y🍈z🍉0",
     blanket text without any meaning"] =>
     r#"This module contains documentation that is too long for one line and
 /// moreover, it spans over mulitple lines such that we can test our rewrapping
-/// algorithm. With emojis: 🚤w🌴x🌋y🍈z🍉0 Smart, isn't it? Lorem ipsum and some more
-/// blanket text without any meaning"#);
+/// algorithm. With emojis: 🚤w🌴x🌋y🍈z🍉0 Smart, isn't it? Lorem ipsum and some
+/// more blanket text without any meaning"#);
 }
 
 #[test]
@@ -79,6 +83,11 @@ macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => applied $expected:literal) => {
         const CFG: ReflowConfig = ReflowConfig {
             max_line_length: $max_line_width,
+            strategy: ReflowStrategy::Greedy,
+            tab_width: 4,
+            comment_width: None,
+            rustfmt: false,
+            dev_comments: false,
         };
 
         let _ = env_logger::Builder::new()
@@ -93,7 +102,7 @@ macro_rules! reflow_content {
         assert_eq!(dbg!(chunks).len(), 1);
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &[], &CFG).expect("Reflow is working. qed");
 
         let patches = suggestions
             .into_iter()
@@ -113,7 +122,7 @@ macro_rules! reflow_content {
         let mut dest = Vec::with_capacity($content.len() * 3 / 2);
         crate::action::apply_patches(
             patches.into_iter(),
-            $content,
+            $content.as_bytes(),
             &mut dest,
         ).expect("Patches always apply nicely. qed");
         let s = std::string::String::from_utf8_lossy(&dest);
@@ -122,6 +131,11 @@ macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => ok) => {
         const CFG: ReflowConfig = ReflowConfig {
             max_line_length: $max_line_width,
+            strategy: ReflowStrategy::Greedy,
+            tab_width: 4,
+            comment_width: None,
+            rustfmt: false,
+            dev_comments: false,
         };
 
         let _ = env_logger::Builder::new()
@@ -136,10 +150,11 @@ macro_rules! reflow_content {
         assert_eq!(dbg!(chunks).len(), 1);
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &[], &CFG).expect("Reflow is working. qed");
 
+        // lines exceeding the limit due to unbreakables are reported, but cannot be reflowed
         assert_eq!(
-            dbg!(suggestions).len(),
+            dbg!(suggestions).iter().filter(|suggestion| !suggestion.replacements.is_empty()).count(),
             0,
             "The content is known to be ok, not in need of a reflow."
         );
@@ -147,6 +162,11 @@ macro_rules! reflow_content {
     ($max_line_width:literal break $content_type:expr, $content:expr => patches [ $( $expected:literal ),+ $(,)?]) => {
         const CFG: ReflowConfig = ReflowConfig {
             max_line_length: $max_line_width,
+            strategy: ReflowStrategy::Greedy,
+            tab_width: 4,
+            comment_width: None,
+            rustfmt: false,
+            dev_comments: false,
         };
 
         let _ = env_logger::Builder::new()
@@ -162,7 +182,7 @@ macro_rules! reflow_content {
         let chunk = &chunks[0];
         let _plain = chunk.erase_cmark();
         println!("reflow content:\n {:?}", $content);
-        let suggestions = reflow(&$content_type, chunk, &CFG).expect("Reflow is working. qed");
+        let suggestions = reflow(&$content_type, chunk, &[], &CFG).expect("Reflow is working. qed");
         let patches = suggestions
             .into_iter()
             .filter_map(|suggestion| {
@@ -285,6 +305,11 @@ fn reflow_indentations() {
 
     const CONFIG: ReflowConfig = ReflowConfig {
         max_line_length: 10,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
     };
 
     let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, false);
@@ -295,8 +320,8 @@ fn reflow_indentations() {
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = &chunks[0];
 
-    let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &CONFIG).expect("Reflow is wokring. qed");
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, chunk, &[], &CONFIG)
+        .expect("Reflow is wokring. qed");
 
     let suggestion = suggestion_set
         .iter()
@@ -335,9 +360,14 @@ fn reflow_doc_indentations() {
 
     let cfg = ReflowConfig {
         max_line_length: 45,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
     };
     let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        reflow(&ContentOrigin::TestEntityRust, chunk, &[], &cfg).expect("Reflow is working. qed");
 
     let suggestions = suggestion_set
         .iter()
@@ -369,13 +399,13 @@ fn reflow_two_paragraphs_not_required() {
 
 #[test]
 fn reflow_fold_two_to_one() {
-    reflow_fluff!(20 break ["A 🚤>", "<To 🌴/🍉&🍈"]
+    reflow_fluff!(22 break ["A 🚤>", "<To 🌴/🍉&🍈"]
             => "A 🚤> <To 🌴/🍉&🍈");
 }
 
 #[test]
 fn reflow_split_one_into_three() {
-    reflow_fluff!(9 break ["A 🌴xX 🍉yY 🍈zZ"]
+    reflow_fluff!(10 break ["A 🌴xX 🍉yY 🍈zZ"]
             => "A 🌴xX\n/// 🍉yY\n/// 🍈zZ");
 }
 
@@ -408,10 +438,15 @@ fn reflow_markdown_two_paragraphs() {
 
     let cfg = ReflowConfig {
         max_line_length: 60,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
     };
 
     let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, &chunk, &cfg).expect("Reflow is working. qed");
+        reflow(&ContentOrigin::TestEntityRust, &chunk, &[], &cfg).expect("Reflow is working. qed");
 
     for (sug, expected) in suggestion_set.iter().zip(expected) {
         assert_eq!(sug.replacements.len(), 1);
@@ -448,11 +483,16 @@ With a second part that is fine"#
 
     let cfg = ReflowConfig {
         max_line_length: 45,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
     };
 
     for (chunk, expect) in chunks.iter().zip(expected) {
-        let suggestion_set =
-            reflow(&ContentOrigin::TestEntityRust, chunk, &cfg).expect("Reflow is working. qed");
+        let suggestion_set = reflow(&ContentOrigin::TestEntityRust, chunk, &[], &cfg)
+            .expect("Reflow is working. qed");
         let sug = suggestion_set
             .iter()
             .next()
@@ -490,6 +530,11 @@ fn reflow_doc_long() {
 fn reflow_sole_markdown() {
     const CONFIG: ReflowConfig = ReflowConfig {
         max_line_length: 60,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
     };
 
     const CONTENT: &'static str =
@@ -547,7 +592,7 @@ multiline. Fullstop."#,
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = chunks.first().unwrap();
 
-    let suggestion_set = reflow(&ContentOrigin::TestEntityCommonMark, &chunk, &CONFIG)
+    let suggestion_set = reflow(&ContentOrigin::TestEntityCommonMark, &chunk, &[], &CONFIG)
         .expect("Reflow is working. qed");
     assert_eq!(suggestion_set.len(), 2);
 
@@ -593,6 +638,11 @@ fn reflow_line_delimiters() {
 fn reflow_check_span() {
     const CONFIG: ReflowConfig = ReflowConfig {
         max_line_length: 27,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
     };
 
     const CONTENT: &'static str = "/// A comment as we have many here and we will always
@@ -616,8 +666,8 @@ struct Fff;
     assert_eq!(dbg!(chunks).len(), 1);
     let chunk = chunks.first().unwrap();
 
-    let suggestion_set =
-        reflow(&ContentOrigin::TestEntityRust, &chunk, &CONFIG).expect("Reflow is working. qed");
+    let suggestion_set = reflow(&ContentOrigin::TestEntityRust, &chunk, &[], &CONFIG)
+        .expect("Reflow is working. qed");
     assert_eq!(suggestion_set.len(), 1);
     let suggestion = suggestion_set
         .first()
@@ -688,6 +738,9 @@ configured with `-m <code>` to
 return a non-zero return code
 if mistakes are found instead
 of `0`.
+"##,
+r##"Parse doc comments from
+  arbitrary files
 "##
     ]);
 }
@@ -714,7 +767,8 @@ of `0`.
 
 ## Implemented Features + Roadmap
 
-* [x] Parse doc comments from arbitrary files
+* [x] Parse doc comments from
+  arbitrary files
 * [x] Decent error printing
 
 "###
@@ -730,3 +784,283 @@ fn reflow_crlf() {
         "cargo spellcheck can be\r\n        /// configured with `-m <code>`\r\n        /// to return a non-zero return\r\n        /// code."
     ]);
 }
+
+#[test]
+fn reflow_mdbook_directive_unbreakable() {
+    reflow_content!(30usize break ContentOrigin::TestEntityCommonMark,
+        "Include it {{#include ../../listings/ch01/main.rs:2:10}} here.\n\nNext."
+    => patches [
+        "Include it\n{{#include ../../listings/ch01/main.rs:2:10}}\nhere."
+    ]);
+}
+
+#[test]
+fn reflow_strategies_keep_prefixes() {
+    const CONTENT: &'static str = r#"
+    /// A first sentence. And a second one, that is considerably longer and needs a few lines.
+    struct Fluffy {};"#;
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    assert_eq!(dbg!(chunks).len(), 1);
+    let chunk = &chunks[0];
+
+    for (strategy, expected) in [
+        (
+            ReflowStrategy::Greedy,
+            "A first sentence. And a\n    /// second one, that is\n    /// considerably longer and\n    /// needs a few lines.",
+        ),
+        (
+            ReflowStrategy::Optimal,
+            "A first sentence. And\n    /// a second one, that is\n    /// considerably longer and\n    /// needs a few lines.",
+        ),
+        (
+            ReflowStrategy::SentencePerLine,
+            "A first sentence.\n    /// And a second one, that is\n    /// considerably longer and\n    /// needs a few lines.",
+        ),
+    ]
+    .iter()
+    {
+        let cfg = ReflowConfig {
+            max_line_length: 33,
+            strategy: *strategy,
+            tab_width: 4,
+            comment_width: None,
+            rustfmt: false,
+            dev_comments: false,
+        };
+        let suggestions =
+            reflow(&ContentOrigin::TestEntityRust, chunk, &[], &cfg).expect("Reflow is working. qed");
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].replacements[0].as_str(), *expected);
+    }
+}
+
+#[test]
+fn reflow_list_items_doc() {
+    reflow_content!(40usize break ContentOrigin::TestEntityRust, r#"
+    /// Intro.
+    ///
+    /// * A bullet point that is way too long for a single line
+    ///   * nested with a continuation that is also too long
+    /// 10. ordered and short
+    struct Fluffy {};"#
+    => applied r#"
+    /// Intro.
+    ///
+    /// * A bullet point that is way too
+    ///   long for a single line
+    ///   * nested with a continuation
+    ///     that is also too long
+    /// 10. ordered and short
+    struct Fluffy {};"#);
+}
+
+#[test]
+fn reflow_block_quote_doc() {
+    reflow_content!(40usize break ContentOrigin::TestEntityRust, r#"
+    //! > A quote, that is way too long to fit in a line
+    //! > and keeps on going.
+    struct Fluffy {};"#
+    => applied r#"
+    //! > A quote, that is way too long
+    //! > to fit in a line and keeps on
+    //! > going.
+    struct Fluffy {};"#);
+}
+
+#[test]
+fn reflow_list_in_block_quote_cmark() {
+    reflow_content!(30usize break ContentOrigin::TestEntityCommonMark,
+        "> 1. An item within a quote that is too long\n>    for a line\n\nDone.\n"
+    => applied "> 1. An item within a quote\n>    that is too long for a\n>    line\n\nDone.\n");
+}
+
+#[test]
+fn reflow_reports_overlong_unbreakables() {
+    const CONTENT: &'static str = r#"
+    /// Short.
+    ///
+    /// See [a link](https://example.com/a/very/long/path/that/cannot/be/broken) and more.
+    struct Fluffy {};"#;
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    let chunk = &chunks[0];
+
+    const CFG: ReflowConfig = ReflowConfig {
+        max_line_length: 50,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
+    };
+    let suggestions =
+        reflow(&ContentOrigin::TestEntityRust, chunk, &[], &CFG).expect("Reflow is working. qed");
+    assert_eq!(dbg!(&suggestions).len(), 2);
+
+    assert_eq!(
+        suggestions[0].replacements,
+        vec![
+            "See\n    /// [a link](https://example.com/a/very/long/path/that/cannot/be/broken)\n    /// and more."
+                .to_owned()
+        ]
+    );
+
+    // the link on its own still exceeds the limit
+    let overlong = &suggestions[1];
+    assert!(overlong.replacements.is_empty());
+    assert_eq!(
+        overlong.span,
+        Span {
+            start: LineColumn {
+                line: 4,
+                column: 12,
+            },
+            end: LineColumn {
+                line: 4,
+                column: 79,
+            },
+        }
+    );
+}
+
+#[test]
+fn display_width_of_tabs_and_wide_chars() {
+    assert_eq!(display_width("abc", 4), 3);
+    assert_eq!(display_width("\t", 4), 4);
+    assert_eq!(display_width("a\tb", 4), 5);
+    assert_eq!(display_width("\t\t", 8), 16);
+    assert_eq!(display_width("日本語", 4), 6);
+    assert_eq!(display_width("🚤w", 4), 3);
+}
+
+#[test]
+fn reflow_wide_chars() {
+    reflow_fluff!(20 break ["漢字の単語 かな カナ 한국어 단어"]
+            => "漢字の単語 かな\n/// カナ 한국어 단어");
+}
+
+#[test]
+fn reflow_tab_indentation() {
+    const CONTENT: &'static str =
+        "\t/// Indented by a tab which is as wide as four columns\n\t/// here.\n\tstruct Fluffy;";
+    const EXPECTED: &'static str =
+        "Indented by a tab\n\t/// which is as wide as\n\t/// four columns here.";
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    let chunk = &chunks[0];
+    let lines = CONTENT.lines().collect::<Vec<_>>();
+
+    const CFG: ReflowConfig = ReflowConfig {
+        max_line_length: 30,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: None,
+        rustfmt: false,
+        dev_comments: false,
+    };
+    let suggestions = reflow(&ContentOrigin::TestEntityRust, chunk, &lines, &CFG)
+        .expect("Reflow is working. qed");
+    assert_eq!(dbg!(&suggestions).len(), 1);
+    assert_eq!(suggestions[0].replacements, vec![EXPECTED.to_owned()]);
+}
+
+#[test]
+fn reflow_comment_width_excludes_indentation() {
+    const CONTENT: &'static str = r#"
+        /// A comment which is indented deeply, yet only the comment width counts.
+        struct Fluffy;"#;
+    const EXPECTED: &'static str = "A comment which is\n        /// indented deeply, yet only\n        /// the comment width counts.";
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, false);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    let chunk = &chunks[0];
+
+    const CFG: ReflowConfig = ReflowConfig {
+        max_line_length: 100,
+        strategy: ReflowStrategy::Greedy,
+        tab_width: 4,
+        comment_width: Some(30),
+        rustfmt: false,
+        dev_comments: false,
+    };
+    let suggestions =
+        reflow(&ContentOrigin::TestEntityRust, chunk, &[], &CFG).expect("Reflow is working. qed");
+    assert_eq!(dbg!(&suggestions).len(), 1);
+    assert_eq!(suggestions[0].replacements, vec![EXPECTED.to_owned()]);
+}
+
+#[test]
+fn reflow_block_doc_gutter() {
+    reflow_content!(30usize break ContentOrigin::TestEntityRust,
+        "/**\n * A block doc comment with a gutter, which is too long.\n */\nstruct Fluffy;"
+    => applied "/**\n * A block doc comment with a\n * gutter, which is too long.\n */\nstruct Fluffy;");
+}
+
+#[test]
+fn reflow_block_doc_closing_in_last_line() {
+    reflow_content!(30usize break ContentOrigin::TestEntityRust,
+        "/** A block doc comment with a gutter,\n * closed in the last line. */\nstruct Fluffy;"
+    => applied "/** A block doc comment with a\n * gutter, closed in the last\n * line. */\nstruct Fluffy;");
+}
+
+#[test]
+fn reflow_block_doc_without_gutter() {
+    reflow_content!(40usize break ContentOrigin::TestEntityRust,
+        "mod fluffy {\n    /*! An inner block doc comment without a gutter,\n        aligned with the first line. */\n}"
+    => applied "mod fluffy {\n    /*! An inner block doc comment\n        without a gutter, aligned with\n        the first line. */\n}");
+}
+
+#[test]
+fn reflow_dev_comments() {
+    const CONTENT: &'static str = r#"
+fn fluffy() {
+    /* A developer block comment which exceeds the width.
+     */
+    // A developer line comment which exceeds the width.
+    let _ = 1;
+}"#;
+
+    let docs = Documentation::load_from_str(ContentOrigin::TestEntityRust, CONTENT, true);
+    let chunks = docs
+        .get(&ContentOrigin::TestEntityRust)
+        .expect("Contains test data. qed");
+    assert_eq!(chunks.len(), 2);
+
+    let mut cfg = ReflowConfig {
+        max_line_length: 40,
+        ..Default::default()
+    };
+    let reflow = Reflow::new(&cfg).unwrap();
+    let suggestions = reflow
+        .check(&ContentOrigin::TestEntityRust, chunks)
+        .expect("Reflow is working. qed");
+    // developer comments are only reflowed if requested
+    assert!(dbg!(&suggestions).is_empty());
+
+    cfg.dev_comments = true;
+    let reflow = Reflow::new(&cfg).unwrap();
+    let suggestions = reflow
+        .check(&ContentOrigin::TestEntityRust, chunks)
+        .expect("Reflow is working. qed");
+    assert_eq!(dbg!(&suggestions).len(), 2);
+    assert_eq!(
+        suggestions[0].replacements,
+        vec!["A developer block comment which\n       exceeds the width.".to_owned()]
+    );
+    assert_eq!(
+        suggestions[1].replacements,
+        vec!["A developer line comment which\n    // exceeds the width.".to_owned()]
+    );
+}
@@ -18,24 +18,48 @@ use std::convert::TryFrom;
 
 use enumflags2::BitFlags;
 use rayon::iter::{IntoParallelRefMutIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
 
 use crate::{Range, Span};
 
 /// Bitflag of available checkers by compilation / configuration.
-#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, BitFlags, Eq, PartialEq, Hash, Deserialize, Serialize)]
 #[repr(u16)]
 pub enum Detector {
     /// Hunspell lib based detector.
+    #[serde(alias = "hunspell")]
     Hunspell = 0b0_0001,
     /// Language tool server based detection.
+    #[serde(alias = "languagetool")]
+    #[serde(alias = "Languagetool")]
     LanguageTool = 0b0_0010,
     /// Language server rules based on nlp detector.
+    #[serde(alias = "nlprules")]
+    #[serde(alias = "Nlprules")]
     NlpRules = 0b0_0100,
     /// Reflow according to a given max column.
+    #[serde(alias = "reflow")]
     Reflow = 0b0_1000,
+    /// Project specific regular expression based rules.
+    #[serde(alias = "customrules")]
+    #[serde(alias = "custom_rules")]
+    CustomRules = 0b1_0000,
+    /// Consistent terminology across all documentation.
+    #[serde(alias = "terminology")]
+    Terminology = 0b10_0000,
+    /// Structure of rustdoc comments.
+    #[serde(alias = "rustdoc")]
+    Rustdoc = 0b100_0000,
+    /// Doubled words, `a` and `an` and common misspellings, without a dictionary.
+    #[serde(alias = "typos")]
+    Typos = 0b1000_0000,
+    /// Dictionaries of hunspell, checked without `libhunspell`.
+    #[serde(alias = "nativehunspell")]
+    #[serde(alias = "native_hunspell")]
+    NativeHunspell = 0b1_0000_0000,
     /// Detection of nothing, a test helper.
     #[cfg(test)]
-    Dummy = 0b1_0000,
+    Dummy = 0b1000_0000_0000_0000,
 }
 
 impl Detector {
@@ -46,12 +70,75 @@ impl Detector {
             Self::NlpRules => "NlpRules",
             Self::LanguageTool => "LanguageTool",
             Self::Reflow => "Reflow",
+            Self::CustomRules => "CustomRules",
+            Self::Terminology => "Terminology",
+            Self::Rustdoc => "Rustdoc",
+            Self::Typos => "Typos",
+            Self::NativeHunspell => "NativeHunspell",
             #[cfg(test)]
             Self::Dummy => "Dummy",
         }
     }
 }
 
+/// How severe a finding is, which decides if it fails a run.
+///
+/// Ordered from the least to the most severe.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Deserialize,
+    Serialize,
+    clap::ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Not reported at all.
+    #[serde(alias = "allow")]
+    #[value(alias = "allow")]
+    Off,
+    /// Reported, but a hint only.
+    Info,
+    /// Reported as a warning.
+    #[serde(alias = "warn", alias = "warnings")]
+    #[value(alias = "warn", alias = "warnings")]
+    Warning,
+    /// Reported as an error.
+    #[serde(alias = "errors")]
+    #[value(alias = "errors")]
+    Error,
+}
+
+impl Default for Severity {
+    fn default() -> Self {
+        Self::Error
+    }
+}
+
+impl Severity {
+    /// Converts the severity to its static str representation.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Info => "info",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(self.as_str())
+    }
+}
+
 /// Terminal size in characters.
 ///
 /// Returns `80usize` for tests and in case the terminal size
@@ -73,14 +160,42 @@ pub fn get_terminal_size() -> usize {
     DEFAULT_TERMINAL_SIZE
 }
 
-// impl
-// // TODO use this to display included compiled backends
-// fn list_available() {
-//     match detector {
-//         Detector::Hunspell => cfg!(feature="hunspell"),
-//         Detector::LanguageTool => cfg!(feature="languagetool"),
-//     }
-// }
+impl Detector {
+    /// Check if the detector is included in this build.
+    pub const fn is_available(&self) -> bool {
+        match self {
+            Self::Hunspell => cfg!(feature = "hunspell"),
+            Self::NlpRules => cfg!(feature = "nlprules"),
+            Self::LanguageTool => cfg!(feature = "languagetool"),
+            Self::Reflow => true,
+            Self::CustomRules => true,
+            Self::Terminology => true,
+            Self::Rustdoc => true,
+            Self::Typos => true,
+            Self::NativeHunspell => true,
+            #[cfg(test)]
+            Self::Dummy => true,
+        }
+    }
+
+    /// List all detectors and if they are included in this build.
+    pub fn list_available() -> Vec<(Self, bool)> {
+        [
+            Self::Hunspell,
+            Self::NlpRules,
+            Self::LanguageTool,
+            Self::Reflow,
+            Self::CustomRules,
+            Self::Terminology,
+            Self::Rustdoc,
+            Self::Typos,
+            Self::NativeHunspell,
+        ]
+        .iter()
+        .map(|detector| (*detector, detector.is_available()))
+        .collect()
+    }
+}
 
 use std::fmt;
 
@@ -292,6 +407,21 @@ pub fn condition_display_content(
     (conditioned_line, offset, marker_size)
 }
 
+/// Identifies the rule of a detector, which yielded a suggestion.
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct RuleId {
+    /// The category the rule belongs to.
+    pub category: String,
+    /// The identifier of the rule itself.
+    pub id: String,
+}
+
+impl fmt::Display for RuleId {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}/{}", self.category, self.id)
+    }
+}
+
 /// A suggestion for certain offending span.
 #[derive(Clone, Hash, PartialEq, Eq)]
 pub struct Suggestion<'s> {
@@ -310,14 +440,24 @@ pub struct Suggestion<'s> {
     pub replacements: Vec<String>,
     /// Descriptive reason for the suggestion.
     pub description: Option<String>,
+    /// The rule which yielded the suggestion, for detectors with a set of
+    /// rules which can be disabled individually.
+    pub rule: Option<RuleId>,
+    /// How severe the finding is, as configured for the detector or rule.
+    pub severity: Severity,
 }
 
 impl<'s> fmt::Display for Suggestion<'s> {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         use console::Style;
 
-        let highlight = Style::new().bold().white();
         let error = Style::new().bold().red();
+        let highlight = Style::new().bold().white();
+        let label = match self.severity {
+            Severity::Error | Severity::Off => error.clone(),
+            Severity::Warning => Style::new().bold().yellow(),
+            Severity::Info => Style::new().bold().cyan(),
+        };
         let arrow_marker = Style::new().blue();
         let context_marker = Style::new().bold().blue();
         let fix = Style::new().green();
@@ -326,7 +466,7 @@ impl<'s> fmt::Display for Suggestion<'s> {
         let line_number_digit_count = self.span.start.line.to_string().len();
         let indent = 3 + line_number_digit_count;
 
-        error.apply_to("error").fmt(formatter)?;
+        label.apply_to(self.severity.as_str()).fmt(formatter)?;
         highlight
             .apply_to(format!(": spellcheck({})", &self.detector))
             .fmt(formatter)?;
@@ -368,6 +508,8 @@ impl<'s> fmt::Display for Suggestion<'s> {
         // assumes the _mistake_ is within one line
         // if not we chop it down to the first line
         let mistake_lines = self.chunk.find_covered_lines(self.range.clone());
+        // empty documentation does not cover any line
+        let empty = 0..0;
         let (line_range, start_of_line_offset) = mistake_lines
             .first()
             .map(|line_range| {
@@ -376,7 +518,7 @@ impl<'s> fmt::Display for Suggestion<'s> {
                     self.range.start.saturating_sub(line_range.start),
                 )
             })
-            .expect("Lines covered must exist");
+            .unwrap_or((&empty, 0));
 
         let intra_line_mistake_range = Range {
             start: start_of_line_offset,
@@ -483,8 +625,13 @@ impl<'s> fmt::Display for Suggestion<'s> {
                 .fmt(formatter)?;
         }
 
-        if let Some(ref description) = self.description {
-            writeln!(formatter, "   {}", description)?;
+        match (&self.description, &self.rule) {
+            (Some(description), Some(rule)) => {
+                writeln!(formatter, "   {} [{}]", description, rule)?;
+            }
+            (Some(description), None) => writeln!(formatter, "   {}", description)?,
+            (None, Some(rule)) => writeln!(formatter, "   [{}]", rule)?,
+            (None, None) => {}
         }
         Ok(())
     }
@@ -645,6 +792,32 @@ impl<'s> SuggestionSet<'s> {
     pub fn total_count(&self) -> usize {
         self.per_file.iter().map(|(_origin, vec)| vec.len()).sum()
     }
+
+    /// Assign the configured severity to all suggestions, others keep the one
+    /// of their checker, and drop the ones which are turned off.
+    pub fn apply_severity(&mut self, config: &crate::config::SeverityConfig) {
+        for suggestions in self.per_file.values_mut() {
+            for suggestion in suggestions.iter_mut() {
+                if let Some(severity) =
+                    config.severity(suggestion.detector, suggestion.rule.as_ref())
+                {
+                    suggestion.severity = severity;
+                }
+            }
+            suggestions.retain(|suggestion| suggestion.severity != Severity::Off);
+        }
+        self.per_file
+            .retain(|_origin, suggestions| !suggestions.is_empty());
+    }
+
+    /// The severity of the most severe suggestion, `None` if there are none.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.per_file
+            .values()
+            .flatten()
+            .map(|suggestion| suggestion.severity)
+            .max()
+    }
 }
 
 impl<'s> IntoIterator for SuggestionSet<'s> {
@@ -720,6 +893,8 @@ mod tests {
                 "replacement_1".to_owned(),
                 "replacement_2".to_owned(),
             ],
+            rule: None,
+            severity: Severity::Error,
             description: Some("Possible spelling mistake found.".to_owned()),
         };
 
@@ -767,6 +942,8 @@ mod tests {
                 },
             },
             replacements: vec![],
+            rule: None,
+            severity: Severity::Error,
             description: Some("Possible spelling mistake found.".to_owned()),
         };
 
@@ -843,6 +1020,8 @@ mod tests {
                 "replacement_1".to_owned(),
                 "replacement_2".to_owned(),
             ],
+            rule: None,
+            severity: Severity::Error,
             description: Some("Possible spelling mistake found.".to_owned()),
         };
 
@@ -909,6 +1088,8 @@ mod tests {
                 "replacement_1".to_owned(),
                 "replacement_2".to_owned(),
             ],
+            rule: None,
+            severity: Severity::Error,
             description: Some("Possible spelling mistake found.".to_owned()),
         };
 
@@ -966,6 +1147,8 @@ mod tests {
             },
             range: 2..6,
             replacements: vec!["whocares".to_owned()],
+            rule: None,
+            severity: Severity::Error,
             description: None,
         };
 
@@ -974,4 +1157,55 @@ mod tests {
         log::info!("fmt debug=\n{:?}\n<", suggestion);
         log::info!("fmt display=\n{}\n<", suggestion);
     }
+
+    #[test]
+    fn severity_per_rule() {
+        let chunk = CheckableChunk::from_str(
+            " Is it dyrck again?",
+            indexmap::indexmap! { 0..18 => Span {
+                    start: LineColumn { line: 1, column: 0 },
+                    end: LineColumn { line: 1, column: 17 },
+                }
+            },
+            CommentVariant::TripleSlash,
+        );
+        let suggestion = |id: &str| Suggestion {
+            detector: Detector::Dummy,
+            origin: ContentOrigin::TestEntityRust,
+            chunk: &chunk,
+            range: 7..12,
+            span: Span {
+                start: LineColumn { line: 1, column: 6 },
+                end: LineColumn {
+                    line: 1,
+                    column: 10,
+                },
+            },
+            replacements: vec![],
+            rule: Some(RuleId {
+                category: "TYPOS".to_owned(),
+                id: id.to_owned(),
+            }),
+            severity: Severity::Error,
+            description: None,
+        };
+        let config: crate::config::SeverityConfig =
+            toml::from_str("[rules]\nTYPOS = \"warn\"\nDYRCK = \"off\"\n").unwrap();
+
+        let mut set = SuggestionSet::new();
+        set.add(ContentOrigin::TestEntityRust, suggestion("DYRCK.1"));
+        set.apply_severity(&config);
+        assert_eq!(set.len(), 0);
+        assert_eq!(set.max_severity(), None);
+
+        set.add(ContentOrigin::TestEntityRust, suggestion("DYRCK.1"));
+        set.add(ContentOrigin::TestEntityRust, suggestion("OTHER"));
+        set.apply_severity(&config);
+        assert_eq!(set.total_count(), 1);
+        assert_eq!(set.max_severity(), Some(Severity::Warning));
+        assert_display_eq(
+            set.iter().next().unwrap().1[0].clone(),
+            "warning: spellcheck(Dummy)\n  --> /tmp/test/entity.rs:1\n   |\n 1 |  Is it dyrck again?\n   |        ^^^^^\n   |   [TYPOS/OTHER]\n",
+        );
+    }
 }
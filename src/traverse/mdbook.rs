@@ -0,0 +1,162 @@
+//! Discover the chapters of a [mdBook](https://rust-lang.github.io/mdBook/).
+//!
+//! The book is described by a `book.toml`, which points to the source
+//! directory containing a `SUMMARY.md`. All local chapters linked
+//! in there are checked as common mark files.
+
+use super::CheckEntity;
+
+use anyhow::{anyhow, Error, Result};
+use indexmap::IndexSet;
+use log::{debug, trace, warn};
+use pulldown_cmark::{Event, Parser, Tag};
+use serde::Deserialize;
+
+use std::path::{Path, PathBuf};
+
+/// The relevant subset of a `book.toml`.
+#[derive(Deserialize, Debug, Default)]
+struct BookToml {
+    #[serde(default)]
+    book: BookSection,
+}
+
+#[derive(Deserialize, Debug)]
+struct BookSection {
+    #[serde(default = "default_src")]
+    src: PathBuf,
+}
+
+impl Default for BookSection {
+    fn default() -> Self {
+        Self { src: default_src() }
+    }
+}
+
+fn default_src() -> PathBuf {
+    PathBuf::from("src")
+}
+
+/// Check if the directory contains a mdBook, and return the path to the
+/// `book.toml` if so.
+pub(crate) fn find_book_toml<P: AsRef<Path>>(dir: P) -> Option<PathBuf> {
+    let book_toml = dir.as_ref().join("book.toml");
+    if book_toml.is_file() {
+        Some(book_toml)
+    } else {
+        None
+    }
+}
+
+/// Extract all chapters linked from a `SUMMARY.md` file content.
+///
+/// Draft chapters without a destination as well as remote links
+/// are skipped, the returned paths are relative to the book source dir.
+fn extract_chapters(summary: &str) -> Vec<PathBuf> {
+    Parser::new(summary)
+        .filter_map(|event| match event {
+            Event::Start(Tag::Link(_link_type, destination, _title)) => Some(destination),
+            _ => None,
+        })
+        .filter_map(|destination| {
+            // strip anchors, they are irrelevant for the chapter file
+            let destination = destination.split('#').next().unwrap_or_default();
+            if destination.is_empty() || destination.contains("://") {
+                trace!("Skipping non-local chapter {:?}", destination);
+                None
+            } else {
+                Some(PathBuf::from(destination))
+            }
+        })
+        .collect()
+}
+
+/// Collect the summary and all chapters of the book described by `book_toml`.
+pub(crate) fn extract_book<P: AsRef<Path>>(book_toml: P) -> Result<Vec<CheckEntity>> {
+    let book_toml = book_toml.as_ref();
+    let book_dir = book_toml
+        .parent()
+        .ok_or_else(|| anyhow!("Book file {} has no parent dir", book_toml.display()))?;
+
+    let content = std::fs::read_to_string(book_toml).map_err(|e| {
+        Error::from(e).context(anyhow!("Failed to read book file {}", book_toml.display()))
+    })?;
+    let BookToml { book } = toml::from_str::<BookToml>(content.as_str()).map_err(|e| {
+        Error::from(e).context(anyhow!("Failed to parse book file {}", book_toml.display()))
+    })?;
+
+    let src_dir = book_dir.join(book.src);
+    let summary = src_dir.join("SUMMARY.md");
+    let summary_content = std::fs::read_to_string(&summary).map_err(|e| {
+        Error::from(e).context(anyhow!("Failed to read book summary {}", summary.display()))
+    })?;
+
+    // chapters may be referenced more than once, i.e. for nested sections
+    let mut chapters = IndexSet::with_capacity(64);
+    chapters.insert(summary.clone());
+    for chapter in extract_chapters(summary_content.as_str()) {
+        let chapter = src_dir.join(chapter);
+        if chapter.is_file() {
+            chapters.insert(chapter);
+        } else {
+            warn!(
+                "Chapter {} listed in {} does not exist",
+                chapter.display(),
+                summary.display()
+            );
+        }
+    }
+    debug!(
+        "Book {} has {} files to check",
+        book_toml.display(),
+        chapters.len()
+    );
+    Ok(chapters.into_iter().map(CheckEntity::Markdown).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_chapters() {
+        const SUMMARY: &str = r#"# Summary
+
+[Introduction](README.md)
+
+- [Getting started](start/intro.md#setup)
+    - [Nested](start/nested.md)
+- [Draft]()
+- [Elsewhere](https://example.com/foo.md)
+
+---
+
+[Contributors](misc/contributors.md)
+"#;
+        assert_eq!(
+            extract_chapters(SUMMARY),
+            vec![
+                PathBuf::from("README.md"),
+                PathBuf::from("start/intro.md"),
+                PathBuf::from("start/nested.md"),
+                PathBuf::from("misc/contributors.md"),
+            ]
+        );
+    }
+
+    #[test]
+    fn book_toml_src() {
+        let book = toml::from_str::<BookToml>(
+            r#"
+[book]
+title = "Demo"
+src = "chapters"
+"#,
+        )
+        .unwrap();
+        assert_eq!(book.book.src, PathBuf::from("chapters"));
+
+        let book = toml::from_str::<BookToml>("").unwrap();
+        assert_eq!(book.book.src, PathBuf::from("src"));
+    }
+}
@@ -25,6 +25,8 @@ use std::collections::VecDeque;
 mod iter;
 pub use iter::*;
 
+mod mdbook;
+
 use proc_macro2::Spacing;
 use proc_macro2::TokenStream;
 use proc_macro2::TokenTree;
@@ -260,6 +262,25 @@ fn handle_manifest<P: AsRef<Path>>(manifest_dir: P, skip_readme: bool) -> Result
         acc.extend(v);
     }
 
+    // a book living next to the crate, either in the manifest dir itself or
+    // a direct sub directory such as `book/` or `guide/`
+    let books = std::iter::once(manifest_dir.to_owned())
+        .chain(
+            fs::read_dir(manifest_dir)?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir()),
+        )
+        .filter_map(mdbook::find_book_toml)
+        .collect::<Vec<_>>();
+    for book_toml in books {
+        trace!("Handling book {}", book_toml.display());
+        match mdbook::extract_book(&book_toml) {
+            Ok(chapters) => acc.extend(chapters),
+            Err(e) => warn!("Failed to extract book {}: {:?}", book_toml.display(), e),
+        }
+    }
+
     if let Some(workspace) = manifest.workspace {
         trace!("Handling manifest workspace");
         workspace
@@ -313,6 +334,7 @@ pub(crate) fn extract(
     #[derive(Debug, Clone)]
     enum Extraction {
         Manifest(PathBuf),
+        Book(PathBuf),
         Missing(PathBuf),
         Source(PathBuf),
         Markdown(PathBuf),
@@ -339,6 +361,7 @@ pub(crate) fn extract(
             if meta.is_file() {
                 match path.file_name().map(|x| x.to_str()).flatten() {
                     Some(file_name) if file_name == "Cargo.toml" => Extraction::Manifest(path),
+                    Some(file_name) if file_name == "book.toml" => Extraction::Book(path),
                     Some(file_name) if file_name.ends_with(".md") => Extraction::Markdown(path),
                     Some(file_name) if file_name.ends_with(".rs") => Extraction::Source(path),
                     _ => {
@@ -350,6 +373,8 @@ pub(crate) fn extract(
                 let cargo_toml = to_manifest_dir(&path).unwrap().join("Cargo.toml");
                 if cargo_toml.is_file() {
                     Extraction::Manifest(cargo_toml)
+                } else if let Some(book_toml) = mdbook::find_book_toml(&path) {
+                    Extraction::Book(book_toml)
                 } else {
                     // TODO should we just collect all .rs files here instead?
 
@@ -357,6 +382,22 @@ pub(crate) fn extract(
                     // will cause to yield all "^.*\.rs$" files in that dir
                     // which is what we want in this case
                     flow.extend(TraverseModulesIter::with_depth_limit(&path, 0)?);
+                    // standalone markdown trees, such as `docs/`, only descend if recursive
+                    files_to_check.extend(
+                        walkdir::WalkDir::new(&path)
+                            .max_depth(if recurse { usize::MAX } else { 1 })
+                            .same_file_system(true)
+                            .into_iter()
+                            .filter_entry(|entry| {
+                                entry.depth() == 0
+                                    || !entry.file_name().to_string_lossy().starts_with('.')
+                            })
+                            .filter_map(|entry| entry.ok())
+                            .filter(|entry| entry.file_type().is_file())
+                            .map(|entry| entry.into_path())
+                            .filter(|path| path.extension().map_or(false, |ext| ext == "md"))
+                            .map(Extraction::Markdown),
+                    );
                     continue;
                 }
             } else {
@@ -379,6 +420,10 @@ pub(crate) fn extract(
                     let manifest_list = handle_manifest(cargo_toml_path, skip_readme)?;
                     acc.extend(manifest_list);
                 }
+                Extraction::Book(ref book_toml_path) => {
+                    let chapters = mdbook::extract_book(book_toml_path)?;
+                    acc.extend(chapters);
+                }
                 Extraction::Missing(ref missing_path) => warn!(
                     "File passed as argument or listed in Cargo.toml manifest does not exist: {}",
                     missing_path.display()
@@ -389,6 +434,11 @@ pub(crate) fn extract(
             Ok(acc)
         })?;
 
+    // chapters of a book might also be referenced as readme or passed explicitly
+    let files_to_check = files_to_check
+        .into_iter()
+        .collect::<indexmap::IndexSet<CheckEntity>>();
+
     // stage 4 - expand from the passed source files, if recursive, recurse down the module train
     let combined: Documentation = files_to_check
         .into_iter()
@@ -569,6 +619,9 @@ mod tests {
     fn traverse_manifest_1() {
         extract_test!(["Cargo.toml"] + false => [
             "README.md",
+            "book/src/SUMMARY.md",
+            "book/src/intro.md",
+            "book/src/chapters/one.md",
             "src/lib.rs",
             "src/main.rs",
             "src/nested/again/mod.rs",
@@ -599,6 +652,9 @@ mod tests {
 
     extract_test!(traverse_manifest_dir_rec, ["."] + true => [
         "README.md",
+        "book/src/SUMMARY.md",
+        "book/src/intro.md",
+        "book/src/chapters/one.md",
         "src/lib.rs",
         "src/main.rs",
         "src/nested/again/mod.rs",
@@ -612,6 +668,9 @@ mod tests {
 
     extract_test!(traverse_manifest_rec, ["Cargo.toml"] + true => [
         "README.md",
+        "book/src/SUMMARY.md",
+        "book/src/intro.md",
+        "book/src/chapters/one.md",
         "src/lib.rs",
         "src/main.rs",
         "src/nested/again/mod.rs",
@@ -636,4 +695,27 @@ mod tests {
         "src/nested/justtwo.rs",
         "src/nested/mod.rs"
     ]);
+
+    extract_test!(traverse_book_dir, ["book"] + false => [
+        "book/src/SUMMARY.md",
+        "book/src/intro.md",
+        "book/src/chapters/one.md",
+    ]);
+
+    extract_test!(traverse_book_toml, ["book/book.toml"] + false => [
+        "book/src/SUMMARY.md",
+        "book/src/intro.md",
+        "book/src/chapters/one.md",
+    ]);
+
+    extract_test!(traverse_markdown_dir_1, ["book/src"] + false => [
+        "book/src/SUMMARY.md",
+        "book/src/intro.md",
+    ]);
+
+    extract_test!(traverse_markdown_dir_rec, ["book/src"] + true => [
+        "book/src/SUMMARY.md",
+        "book/src/intro.md",
+        "book/src/chapters/one.md",
+    ]);
 }
@@ -1,5 +1,6 @@
 use crate::{LineColumn, Range, Span};
 use anyhow::{anyhow, bail, Result};
+use std::hash::Hasher;
 use std::io::Read;
 use std::path::Path;
 
@@ -14,7 +15,7 @@ pub fn iter_with_line_column_from<'a>(
     struct State {
         cursor: LineColumn,
         previous_char_was_newline: bool,
-    };
+    }
     let initial = State {
         cursor: start_point,
         previous_char_was_newline: false,
@@ -43,6 +44,33 @@ pub fn iter_with_line_column<'a>(
     iter_with_line_column_from(s, LineColumn { line: 1, column: 0 })
 }
 
+/// A digest of the content of a file, to detect modifications since it was read.
+///
+/// Only stable within a single run.
+pub fn content_digest(content: &str) -> u64 {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    hasher.write(content.as_bytes());
+    hasher.finish()
+}
+
+/// The streaming equivalent of [`content_digest`].
+///
+/// # Errors
+/// Returns the first error of `reader`, except for interruptions.
+pub fn reader_digest(mut reader: impl Read) -> std::io::Result<u64> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => hasher.write(&buf[..n]),
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(hasher.finish())
+}
+
 /// Extract `span` from a `Read`-able source as `String`.
 ///
 /// # Errors
@@ -263,6 +291,18 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    #[test]
+    fn digest_streaming() {
+        let content = "Alpha\nbeta🐠\r\ngamma";
+        let (first, second) = content.as_bytes().split_at(9);
+        assert_eq!(
+            reader_digest(first.chain(second)).unwrap(),
+            content_digest(content)
+        );
+        assert_ne!(content_digest(content), content_digest("Alpha"));
+    }
+
     macro_rules! lcc {
         ($line:literal, $column:literal, $c:literal) => {
             (